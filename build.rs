@@ -6,6 +6,7 @@ use std::ffi::OsString;
 use std::fs::copy;
 use std::fs::create_dir_all;
 use std::fs::hard_link;
+use std::fs::remove_file;
 use std::io::Error;
 use std::io::ErrorKind;
 use std::io::Result;
@@ -169,6 +170,20 @@ fn gsym(src: &Path, dst: impl AsRef<OsStr>) {
     let () = adjust_mtime(&dst).unwrap();
 }
 
+/// Combine the split DWARF files referenced by `src` into a DWARF
+/// package at `dst` using `llvm-dwp`.
+fn dwp(src: &Path, dst: impl AsRef<OsStr>) {
+    let dst = src.with_file_name(dst);
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+
+    let dwp = env::var_os("LLVM_DWP").unwrap_or_else(|| OsString::from("llvm-dwp"));
+
+    let () = run(dwp, ["-e".as_ref(), src, "-o".as_ref(), &dst]).expect("failed to run `llvm-dwp`");
+
+    let () = adjust_mtime(&dst).unwrap();
+}
+
 /// Invoke `strip` on a copy of `src` placed at `dst`.
 fn strip(src: &Path, dst: impl AsRef<OsStr>, options: &[&str]) {
     let dst = src.with_file_name(dst);
@@ -185,6 +200,23 @@ fn strip(src: &Path, dst: impl AsRef<OsStr>, options: &[&str]) {
     let () = adjust_mtime(&dst).unwrap();
 }
 
+/// Invoke `objcopy` to create a copy of `src` at `dst`.
+fn objcopy(src: &Path, dst: impl AsRef<OsStr>, options: &[&str]) {
+    let dst = src.with_file_name(dst);
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+
+    let () = run(
+        "objcopy",
+        options
+            .iter()
+            .map(OsStr::new)
+            .chain([src.as_os_str(), dst.as_os_str()]),
+    )
+    .expect("failed to run `objcopy`");
+    let () = adjust_mtime(&dst).unwrap();
+}
+
 /// Strip all DWARF information from an ELF binary, in an attempt to
 /// leave only ELF symbols in place.
 fn elf(src: &Path, dst: impl AsRef<OsStr>) {
@@ -227,6 +259,36 @@ fn unpack_xz(_src: &Path, _dst: &Path) {
     unimplemented!()
 }
 
+/// Compress a file using xz.
+#[cfg(feature = "xz2")]
+fn pack_xz(src: &Path, dst: &Path) {
+    use std::fs::File;
+    use std::io::copy;
+    use xz2::read::XzEncoder;
+
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+
+    let src_file = File::options().create(false).read(true).open(src).unwrap();
+    let mut encoder = XzEncoder::new(src_file, 6);
+
+    let mut dst_file = File::options()
+        .create(true)
+        .truncate(true)
+        .read(false)
+        .write(true)
+        .open(dst)
+        .unwrap();
+
+    let _bytes = copy(&mut encoder, &mut dst_file).unwrap();
+    let () = adjust_mtime(dst).unwrap();
+}
+
+#[cfg(not(feature = "xz2"))]
+fn pack_xz(_src: &Path, _dst: &Path) {
+    unimplemented!()
+}
+
 
 /// Put files in a zip archive, uncompressed.
 #[cfg(feature = "zip")]
@@ -294,6 +356,17 @@ fn prepare_test_files(crate_root: &Path) {
         "libtest-so-no-separate-code.so",
         &["-shared", "-fPIC", "-Wl,--build-id=md5,-z,noseparate-code"],
     );
+    cc(
+        &src,
+        "libtest-so-32.so",
+        &[
+            "-m32",
+            "-shared",
+            "-fPIC",
+            "-nostdlib",
+            "-Wl,--build-id=sha1",
+        ],
+    );
 
     let src = crate_root.join("data").join("test-exe.c");
     cc(&src, "test-no-debug.bin", &["-g0", "-Wl,--build-id=none"]);
@@ -302,6 +375,9 @@ fn prepare_test_files(crate_root: &Path) {
     cc(&src, "test-dwarf-v4.bin", &["-gstrict-dwarf", "-gdwarf-4"]);
     cc(&src, "test-dwarf-v5.bin", &["-gstrict-dwarf", "-gdwarf-5"]);
 
+    let src = crate_root.join("data").join("test-dwarf-v5.bin");
+    strip(&src, "test-dwarf-v5-stripped.bin", &["--strip-all"]);
+
     let src = crate_root.join("data").join("test-stable-addresses.c");
     let src_cu2 = crate_root.join("data").join("test-stable-addresses-cu2.c");
     let src_cu2 = src_cu2.to_str().unwrap();
@@ -322,6 +398,28 @@ fn prepare_test_files(crate_root: &Path) {
             src_cu2,
         ],
     );
+    cc(
+        &src,
+        "test-stable-addresses-32.bin",
+        &[
+            "-m32",
+            "-fno-pic",
+            "-no-pie",
+            "-gdwarf-4",
+            "-T",
+            ld_script,
+            "-Wl,--build-id=none",
+            "-O0",
+            "-nostdlib",
+            src_cu2,
+        ],
+    );
+    // A relocatable object file, akin to a kernel module.
+    cc(
+        &src,
+        "test-stable-addresses-rel.bin",
+        &["-c", "-fno-pic", "-gdwarf-4", "-O0"],
+    );
     cc(
         &src,
         "test-stable-addresses-no-dwarf.bin",
@@ -337,15 +435,122 @@ fn prepare_test_files(crate_root: &Path) {
         ],
     );
 
+    cc(
+        &src,
+        "test-stable-addresses-split.bin",
+        &[
+            "-gsplit-dwarf",
+            "-gdwarf-5",
+            "-T",
+            ld_script,
+            "-Wl,--build-id=none",
+            "-O0",
+            "-nostdlib",
+            src_cu2,
+        ],
+    );
+    cc(
+        &src,
+        "test-stable-addresses-dwp.bin",
+        &[
+            "-gsplit-dwarf",
+            "-gdwarf-4",
+            "-T",
+            ld_script,
+            "-Wl,--build-id=none",
+            "-O0",
+            "-nostdlib",
+            src_cu2,
+        ],
+    );
+
+    let src = crate_root
+        .join("data")
+        .join("test-stable-addresses-dwp.bin");
+    dwp(&src, "test-stable-addresses-dwp.bin.dwp");
+    // Remove the individual `.dwo` files, so that all split units have
+    // to be looked up in the package.
+    for cu in ["test-stable-addresses", "test-stable-addresses-cu2"] {
+        let dwo = src.with_file_name(format!("test-stable-addresses-dwp.bin-{cu}.dwo"));
+        let () = remove_file(&dwo)
+            .or_else(|err| {
+                if err.kind() == ErrorKind::NotFound {
+                    Ok(())
+                } else {
+                    Err(err)
+                }
+            })
+            .unwrap();
+    }
+
     let src = crate_root.join("data").join("test-stable-addresses.bin");
     gsym(&src, "test-stable-addresses.gsym");
     dwarf(&src, "test-stable-addresses-dwarf-only.bin");
+    objcopy(&src, "test-stable-addresses.dbg", &["--only-keep-debug"]);
+    // A stand-in for a kernel image, which has the `_stext` symbol mark
+    // the start of the kernel's text.
+    objcopy(
+        &src,
+        "test-stable-addresses-kernel.bin",
+        &["--add-symbol", "_stext=.text:0,global"],
+    );
+    objcopy(
+        &src,
+        "test-stable-addresses-compressed-debug-zlib.bin",
+        &["--compress-debug-sections=zlib"],
+    );
+    objcopy(
+        &src,
+        "test-stable-addresses-compressed-debug-zlib-gnu.bin",
+        &["--compress-debug-sections=zlib-gnu"],
+    );
+    objcopy(
+        &src,
+        "test-stable-addresses-compressed-debug-zstd.bin",
+        &["--compress-debug-sections=zstd"],
+    );
+
+    let dbg = crate_root.join("data").join("test-stable-addresses.dbg");
+    let debug_link = format!("--add-gnu-debuglink={}", dbg.display());
+    objcopy(
+        &src,
+        "test-stable-addresses-stripped-with-link.bin",
+        &["--strip-all", &debug_link],
+    );
+
+    // A stripped binary carrying its symbol table in an xz compressed
+    // ELF file inside the `.gnu_debugdata` section ("MiniDebugInfo").
+    objcopy(
+        &src,
+        "test-stable-addresses-mini-debug-info.dbg",
+        &["--only-keep-debug", "--strip-debug"],
+    );
+    let dbg = crate_root
+        .join("data")
+        .join("test-stable-addresses-mini-debug-info.dbg");
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+    let dbg_xz = Path::new(&out_dir).join("test-stable-addresses-mini-debug-info.dbg.xz");
+    pack_xz(&dbg, &dbg_xz);
+    let debug_data = format!("--add-section=.gnu_debugdata={}", dbg_xz.display());
+    objcopy(
+        &src,
+        "test-stable-addresses-mini-debug-info.bin",
+        &["--strip-all", &debug_data],
+    );
 
     let src = crate_root.join("data").join("kallsyms.xz");
     let mut dst = src.clone();
     assert!(dst.set_extension(""));
     unpack_xz(&src, &dst);
 
+    // Big endian cross toolchains are not commonly available and so we
+    // work with a pre-built binary. See `test-big-endian.rs` for
+    // details.
+    let src = crate_root.join("data").join("test-big-endian.bin.xz");
+    let mut dst = src.clone();
+    assert!(dst.set_extension(""));
+    unpack_xz(&src, &dst);
+
     let () = create_dir_all(crate_root.join("data").join("zip-dir")).unwrap();
     let () = hard_link(
         crate_root.join("data").join("test-no-debug.bin"),
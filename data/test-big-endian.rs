@@ -0,0 +1,41 @@
+//! The sample program used to generate `test-big-endian.bin`, a big
+//! endian (s390x) counterpart to `test-stable-addresses.bin`.
+//!
+//! As cross toolchains for big endian targets are not commonly
+//! available, the binary is checked in (in compressed form). It can be
+//! regenerated using:
+//! ```sh
+//! $ rustc +nightly --target s390x-unknown-linux-gnu --crate-type=lib \
+//!     --emit=obj -g -C opt-level=0 --remap-path-prefix=$PWD= \
+//!     test-big-endian.rs -o test-big-endian.o
+//! $ rust-lld -flavor gnu -T test-big-endian.ld --build-id=sha1 \
+//!     --gc-sections -e _start -o test-big-endian.bin test-big-endian.o
+//! $ xz --keep test-big-endian.bin
+//! ```
+
+#![feature(no_core, lang_items)]
+#![allow(internal_features)]
+#![no_core]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+
+impl Copy for u32 {}
+
+
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn factorial(n: u32) -> u32 {
+    n
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> u32 {
+    factorial(5)
+}
@@ -37,3 +37,18 @@ main(int argc, const char *argv[]) {
   dummy();
   return 0;
 }
+
+static inline __attribute__((always_inline)) unsigned int
+factorial_2nd_layer_inline_wrapper() {
+  return factorial(6);
+}
+
+static inline __attribute__((always_inline)) unsigned int
+factorial_inline_wrapper() {
+  return factorial_2nd_layer_inline_wrapper();
+}
+
+__attribute__((section(".text.inline"))) unsigned int
+factorial_inline_test() {
+  return factorial_inline_wrapper();
+}
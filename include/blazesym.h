@@ -42,6 +42,10 @@ typedef enum blaze_user_addr_meta_kind {
    * [`blaze_user_addr_meta_variant::elf`] is valid.
    */
   BLAZE_USER_ADDR_ELF,
+  /**
+   * [`blaze_user_addr_meta_variant::vdso`] is valid.
+   */
+  BLAZE_USER_ADDR_VDSO,
 } blaze_user_addr_meta_kind;
 
 /**
@@ -140,6 +144,20 @@ typedef struct blaze_user_addr_meta_elf {
   uint8_t *build_id;
 } blaze_user_addr_meta_elf;
 
+/**
+ * C compatible version of [`Vdso`].
+ */
+typedef struct blaze_user_addr_meta_vdso {
+  /**
+   * The length of the build ID, in bytes.
+   */
+  size_t build_id_len;
+  /**
+   * The optional build ID of the vDSO, if found.
+   */
+  uint8_t *build_id;
+} blaze_user_addr_meta_vdso;
+
 /**
  * C compatible version of [`Unknown`].
  */
@@ -159,6 +177,10 @@ typedef union blaze_user_addr_meta_variant {
    * Valid on [`blaze_user_addr_meta_kind::BLAZE_USER_ADDR_ELF`].
    */
   struct blaze_user_addr_meta_elf elf;
+  /**
+   * Valid on [`blaze_user_addr_meta_kind::BLAZE_USER_ADDR_VDSO`].
+   */
+  struct blaze_user_addr_meta_vdso vdso;
   /**
    * Valid on [`blaze_user_addr_meta_kind::BLAZE_USER_ADDR_UNKNOWN`].
    */
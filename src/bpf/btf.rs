@@ -0,0 +1,275 @@
+//! Parsing support for the BPF Type Format (BTF).
+//!
+//! We only care about a small subset of the information contained in
+//! BTF: the names of `BTF_KIND_FUNC` types, which are referenced by a
+//! program's function information, and the string section, which is
+//! referenced by a program's line information. Everything else is
+//! merely skipped over.
+//!
+//! See <https://www.kernel.org/doc/html/latest/bpf/btf.html>
+
+use std::ffi::CStr;
+
+use crate::util::ReadRaw as _;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The magic number identifying BTF data.
+const BTF_MAGIC: u16 = 0xeb9f;
+
+const BTF_KIND_INT: u32 = 1;
+const BTF_KIND_PTR: u32 = 2;
+const BTF_KIND_ARRAY: u32 = 3;
+const BTF_KIND_STRUCT: u32 = 4;
+const BTF_KIND_UNION: u32 = 5;
+const BTF_KIND_ENUM: u32 = 6;
+const BTF_KIND_FWD: u32 = 7;
+const BTF_KIND_TYPEDEF: u32 = 8;
+const BTF_KIND_VOLATILE: u32 = 9;
+const BTF_KIND_CONST: u32 = 10;
+const BTF_KIND_RESTRICT: u32 = 11;
+const BTF_KIND_FUNC: u32 = 12;
+const BTF_KIND_FUNC_PROTO: u32 = 13;
+const BTF_KIND_VAR: u32 = 14;
+const BTF_KIND_DATASEC: u32 = 15;
+const BTF_KIND_FLOAT: u32 = 16;
+const BTF_KIND_DECL_TAG: u32 = 17;
+const BTF_KIND_TYPE_TAG: u32 = 18;
+const BTF_KIND_ENUM64: u32 = 19;
+
+
+/// The size of the kind specific data following a `struct btf_type`
+/// of the given kind with `vlen` members.
+fn kind_data_size(kind: u32, vlen: usize) -> Option<usize> {
+    let size = match kind {
+        BTF_KIND_INT => 4,
+        BTF_KIND_PTR | BTF_KIND_FWD | BTF_KIND_TYPEDEF | BTF_KIND_VOLATILE | BTF_KIND_CONST
+        | BTF_KIND_RESTRICT | BTF_KIND_FUNC | BTF_KIND_FLOAT | BTF_KIND_TYPE_TAG => 0,
+        // `struct btf_array`
+        BTF_KIND_ARRAY => 12,
+        // `struct btf_member`
+        BTF_KIND_STRUCT | BTF_KIND_UNION => vlen * 12,
+        // `struct btf_enum`
+        BTF_KIND_ENUM => vlen * 8,
+        // `struct btf_param`
+        BTF_KIND_FUNC_PROTO => vlen * 8,
+        // `struct btf_var`
+        BTF_KIND_VAR => 4,
+        // `struct btf_var_secinfo`
+        BTF_KIND_DATASEC => vlen * 12,
+        // `struct btf_decl_tag`
+        BTF_KIND_DECL_TAG => 4,
+        // `struct btf_enum64`
+        BTF_KIND_ENUM64 => vlen * 12,
+        _ => return None,
+    };
+    Some(size)
+}
+
+
+/// Parsed BTF data.
+#[derive(Debug)]
+pub(crate) struct Btf<'dat> {
+    /// The name offsets of all types, indexed by type ID minus one,
+    /// along with a flag indicating whether the type is a
+    /// `BTF_KIND_FUNC`.
+    types: Vec<(u32, bool)>,
+    /// The string section.
+    strs: &'dat [u8],
+}
+
+impl<'dat> Btf<'dat> {
+    /// Parse BTF data, as reported by the kernel for a loaded BTF
+    /// object.
+    pub fn parse(data: &'dat [u8]) -> Result<Self> {
+        let mut header = data;
+        let magic = header
+            .read_u16()
+            .ok_or_invalid_data(|| "failed to read BTF magic")?;
+        if magic != BTF_MAGIC {
+            return Err(Error::with_invalid_data(format!(
+                "encountered unexpected BTF magic: {magic:#x}"
+            )))
+        }
+        let _version = header
+            .read_u8()
+            .ok_or_invalid_data(|| "failed to read BTF version")?;
+        let _flags = header
+            .read_u8()
+            .ok_or_invalid_data(|| "failed to read BTF flags")?;
+        let (hdr_len, type_off, type_len, str_off, str_len) = (|| {
+            Some((
+                header.read_u32()? as usize,
+                header.read_u32()? as usize,
+                header.read_u32()? as usize,
+                header.read_u32()? as usize,
+                header.read_u32()? as usize,
+            ))
+        })()
+        .ok_or_invalid_data(|| "failed to read BTF header")?;
+
+        let section = |off: usize, len: usize| {
+            let start = hdr_len.checked_add(off)?;
+            data.get(start..start.checked_add(len)?)
+        };
+        let mut type_data = section(type_off, type_len)
+            .ok_or_invalid_data(|| "BTF type section is out of bounds")?;
+        let strs = section(str_off, str_len)
+            .ok_or_invalid_data(|| "BTF string section is out of bounds")?;
+
+        let mut types = Vec::new();
+        while !type_data.is_empty() {
+            let (name_off, info) = (|| Some((type_data.read_u32()?, type_data.read_u32()?)))()
+                .ok_or_invalid_data(|| format!("failed to read BTF type {}", types.len() + 1))?;
+            // `size` or `type`, depending on the kind.
+            let _size_or_type = type_data
+                .read_u32()
+                .ok_or_invalid_data(|| format!("failed to read BTF type {}", types.len() + 1))?;
+
+            let kind = (info >> 24) & 0x1f;
+            let vlen = (info & 0xffff) as usize;
+            let size = kind_data_size(kind, vlen).ok_or_invalid_data(|| {
+                format!(
+                    "encountered unsupported BTF kind {kind} in type {}",
+                    types.len() + 1
+                )
+            })?;
+            let _data = type_data
+                .read_slice(size)
+                .ok_or_invalid_data(|| format!("failed to read BTF type {}", types.len() + 1))?;
+
+            let () = types.push((name_off, kind == BTF_KIND_FUNC));
+        }
+
+        let slf = Self { types, strs };
+        Ok(slf)
+    }
+
+    /// Retrieve the string at the given offset into the string
+    /// section.
+    pub fn str(&self, offset: u32) -> Option<&'dat str> {
+        let mut strs = self.strs.get(offset as usize..)?;
+        let s = strs.read_cstr()?;
+        CStr::to_str(s).ok()
+    }
+
+    /// Retrieve the name of the `BTF_KIND_FUNC` type with the given ID.
+    pub fn func_name(&self, type_id: u32) -> Option<&'dat str> {
+        let idx = (type_id as usize).checked_sub(1)?;
+        let (name_off, is_func) = self.types.get(idx)?;
+        if *is_func {
+            self.str(*name_off)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use test_log::test;
+
+
+    /// A builder for BTF blobs as reported by the kernel.
+    pub(crate) struct BtfBuilder {
+        types: Vec<u8>,
+        strs: Vec<u8>,
+        count: u32,
+    }
+
+    impl BtfBuilder {
+        pub fn new() -> Self {
+            Self {
+                types: Vec::new(),
+                // The string at offset zero is always the empty string.
+                strs: vec![0],
+                count: 0,
+            }
+        }
+
+        /// Add a string, returning its offset.
+        pub fn add_str(&mut self, s: &str) -> u32 {
+            let off = self.strs.len() as u32;
+            let () = self.strs.extend_from_slice(s.as_bytes());
+            let () = self.strs.push(0);
+            off
+        }
+
+        /// Add a type, returning its ID.
+        pub fn add_type(&mut self, name: &str, kind: u32, vlen: u16, data: &[u8]) -> u32 {
+            let name_off = if name.is_empty() {
+                0
+            } else {
+                self.add_str(name)
+            };
+            let info = (kind << 24) | u32::from(vlen);
+            let () = self.types.extend_from_slice(&name_off.to_ne_bytes());
+            let () = self.types.extend_from_slice(&info.to_ne_bytes());
+            let () = self.types.extend_from_slice(&0u32.to_ne_bytes());
+            let () = self.types.extend_from_slice(data);
+            self.count += 1;
+            self.count
+        }
+
+        /// Add a `BTF_KIND_FUNC` type along with its prototype,
+        /// returning the former's ID.
+        pub fn add_func(&mut self, name: &str) -> u32 {
+            let _proto = self.add_type("", BTF_KIND_FUNC_PROTO, 1, &[0; 8]);
+            self.add_type(name, BTF_KIND_FUNC, 0, &[])
+        }
+
+        pub fn build(&self) -> Vec<u8> {
+            let hdr_len = 24u32;
+            let mut data = Vec::new();
+            let () = data.extend_from_slice(&BTF_MAGIC.to_ne_bytes());
+            let () = data.push(1);
+            let () = data.push(0);
+            let () = data.extend_from_slice(&hdr_len.to_ne_bytes());
+            let () = data.extend_from_slice(&0u32.to_ne_bytes());
+            let () = data.extend_from_slice(&(self.types.len() as u32).to_ne_bytes());
+            let () = data.extend_from_slice(&(self.types.len() as u32).to_ne_bytes());
+            let () = data.extend_from_slice(&(self.strs.len() as u32).to_ne_bytes());
+            let () = data.extend_from_slice(&self.types);
+            let () = data.extend_from_slice(&self.strs);
+            data
+        }
+    }
+
+
+    /// Check that we can look up function names and strings in BTF
+    /// data.
+    #[test]
+    fn btf_parsing() {
+        let mut builder = BtfBuilder::new();
+        let int = builder.add_type("int", BTF_KIND_INT, 0, &[0; 4]);
+        let strct = builder.add_type("foo", BTF_KIND_STRUCT, 2, &[0; 24]);
+        let func = builder.add_func("handle_exec");
+        let file = builder.add_str("/src/prog.bpf.c");
+        let data = builder.build();
+
+        let btf = Btf::parse(&data).unwrap();
+        assert_eq!(btf.func_name(func), Some("handle_exec"));
+        assert_eq!(btf.func_name(int), None);
+        assert_eq!(btf.func_name(strct), None);
+        assert_eq!(btf.func_name(0), None);
+        assert_eq!(btf.func_name(func + 1), None);
+        assert_eq!(btf.str(file), Some("/src/prog.bpf.c"));
+        assert_eq!(btf.str(u32::MAX), None);
+
+        let err = Btf::parse(&data[..10]).unwrap_err();
+        assert!(
+            err.to_string().contains("failed to read BTF header"),
+            "{err}"
+        );
+
+        let mut data = data;
+        data[0] = 0;
+        let err = Btf::parse(&data).unwrap_err();
+        assert!(err.to_string().contains("unexpected BTF magic"), "{err}");
+    }
+}
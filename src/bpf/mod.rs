@@ -0,0 +1,79 @@
+//! Support for symbolizing addresses in JITed BPF programs.
+//!
+//! The kernel reports JITed BPF programs in kallsyms, but only as
+//! `bpf_prog_<tag>_<name>`. Programs loaded with BTF carry function
+//! and line information, which the kernel reports as part of the
+//! program's `struct bpf_prog_info`. This information, along with the
+//! program's BTF, allows us to report real function names as well as
+//! source code locations.
+
+mod btf;
+mod prog;
+mod sys;
+
+use crate::log::debug;
+use crate::Addr;
+use crate::ErrorExt as _;
+use crate::Result;
+
+pub(crate) use prog::BpfProgResolver;
+
+
+/// The prefix of kallsyms names of JITed BPF programs.
+pub(crate) const BPF_PROG_SYM_PREFIX: &str = "bpf_prog_";
+
+
+/// Create resolvers for all loaded BPF programs containing at least
+/// one of the provided addresses.
+///
+/// Programs for which no usable information is available (e.g.,
+/// because they were loaded without BTF) are skipped.
+pub(crate) fn create_prog_resolvers(addrs: &[Addr]) -> Result<Vec<BpfProgResolver>> {
+    let mut resolvers = Vec::new();
+    let mut id = 0;
+
+    while let Some(next_id) =
+        sys::next_prog_id(id).context("failed to enumerate loaded BPF programs")?
+    {
+        id = next_id;
+
+        let mut info = if let Some(info) = sys::prog_info(id)
+            .with_context(|| format!("failed to retrieve information for BPF program {id}"))?
+        {
+            info
+        } else {
+            continue
+        };
+
+        if !addrs.iter().any(|addr| info.contains(*addr)) {
+            continue
+        }
+
+        if info.btf_id == 0 {
+            debug!("BPF program {id} ({}) has no BTF; ignoring...", info.name);
+            continue
+        }
+
+        info.btf = match sys::btf_data(info.btf_id) {
+            Ok(btf) => btf,
+            Err(err) => {
+                debug!(
+                    "failed to retrieve BTF for BPF program {id} ({}): {err}; ignoring...",
+                    info.name
+                );
+                continue
+            }
+        };
+
+        match BpfProgResolver::from_info(&info) {
+            Ok(resolver) => resolvers.push(resolver),
+            Err(err) => {
+                debug!(
+                    "failed to use BPF program {id} ({}): {err}; ignoring...",
+                    info.name
+                )
+            }
+        }
+    }
+    Ok(resolvers)
+}
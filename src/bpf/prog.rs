@@ -0,0 +1,451 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use crate::inspect::FindAddrOpts;
+use crate::inspect::SymInfo;
+use crate::symbolize::AddrLineInfo;
+use crate::util::split_path;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::IntInlinedFn;
+use crate::IntSym;
+use crate::IntoError as _;
+use crate::Result;
+use crate::SrcLang;
+use crate::SymResolver;
+
+use super::btf::Btf;
+
+
+/// The size of a `struct bpf_func_info`.
+const FUNC_INFO_SIZE: usize = 8;
+/// The size of a `struct bpf_line_info`.
+const LINE_INFO_SIZE: usize = 16;
+
+
+/// Raw information about a loaded BPF program, as reported by the
+/// kernel in `struct bpf_prog_info` and the arrays it references.
+#[derive(Debug, Default)]
+pub(crate) struct ProgInfo {
+    /// The (possibly truncated) name of the program.
+    pub name: String,
+    /// The start addresses of the JITed (sub-)programs.
+    pub jited_ksyms: Vec<u64>,
+    /// The sizes of the JITed (sub-)programs.
+    pub jited_func_lens: Vec<u32>,
+    /// An array of `struct bpf_func_info`, one for each sub-program.
+    pub func_info: Vec<u8>,
+    /// The size of a single record in `func_info`.
+    pub func_info_rec_size: usize,
+    /// An array of `struct bpf_line_info`.
+    pub line_info: Vec<u8>,
+    /// The size of a single record in `line_info`.
+    pub line_info_rec_size: usize,
+    /// The JITed addresses corresponding to the records in
+    /// `line_info`.
+    pub jited_line_info: Vec<u64>,
+    /// The ID of the program's BTF object.
+    pub btf_id: u32,
+    /// The raw data of the program's BTF object.
+    pub btf: Vec<u8>,
+}
+
+impl ProgInfo {
+    /// Check whether the JITed program contains the given address.
+    pub fn contains(&self, addr: Addr) -> bool {
+        self.jited_ksyms
+            .iter()
+            .zip(self.jited_func_lens.iter())
+            .any(|(start, len)| {
+                *start != 0 && (*start..start + u64::from(*len)).contains(&(addr as u64))
+            })
+    }
+}
+
+
+/// A function (i.e., a sub-program) of a BPF program.
+#[derive(Debug)]
+struct Func {
+    /// The JITed start address of the function.
+    addr: Addr,
+    /// The size of the JITed function, in bytes.
+    size: u64,
+    /// The name of the function.
+    name: String,
+}
+
+
+/// A mapping of a JITed address to a source code location.
+#[derive(Debug)]
+struct Line {
+    /// The JITed address.
+    addr: Addr,
+    /// The index of the source file in `BpfProgResolver::files`.
+    file: usize,
+    /// The line number.
+    line: u32,
+    /// The column number.
+    column: u16,
+}
+
+
+/// A symbol resolver for a single loaded BPF program.
+pub(crate) struct BpfProgResolver {
+    /// The name of the program.
+    name: String,
+    /// The program's functions, sorted by address.
+    funcs: Vec<Func>,
+    /// Line information, sorted by address.
+    lines: Vec<Line>,
+    /// The source files referenced by `lines`.
+    files: Vec<String>,
+}
+
+impl BpfProgResolver {
+    /// Create a resolver from raw program information.
+    pub fn from_info(info: &ProgInfo) -> Result<Self> {
+        let ProgInfo {
+            name,
+            jited_ksyms,
+            jited_func_lens,
+            func_info,
+            func_info_rec_size,
+            line_info,
+            line_info_rec_size,
+            jited_line_info,
+            btf_id: _,
+            btf,
+        } = info;
+
+        if jited_ksyms.iter().all(|addr| *addr == 0) {
+            return Err(Error::with_unsupported(
+                "kernel did not report JITed addresses for BPF program",
+            ))
+        }
+        if jited_ksyms.len() != jited_func_lens.len() {
+            return Err(Error::with_invalid_data(format!(
+                "number of JITed functions ({}) does not match number of function lengths ({})",
+                jited_ksyms.len(),
+                jited_func_lens.len()
+            )))
+        }
+        if *func_info_rec_size < FUNC_INFO_SIZE || func_info.len() % func_info_rec_size != 0 {
+            return Err(Error::with_invalid_data(format!(
+                "BPF function information has unexpected record size {func_info_rec_size}"
+            )))
+        }
+        if func_info.len() / func_info_rec_size != jited_ksyms.len() {
+            return Err(Error::with_invalid_data(format!(
+                "number of function information records ({}) does not match number of JITed functions ({})",
+                func_info.len() / func_info_rec_size,
+                jited_ksyms.len()
+            )))
+        }
+
+        let btf = Btf::parse(btf)?;
+
+        let mut funcs = func_info
+            .chunks_exact(*func_info_rec_size)
+            .zip(jited_ksyms.iter().zip(jited_func_lens.iter()))
+            .enumerate()
+            .map(|(idx, (mut record, (addr, len)))| {
+                let (_insn_off, type_id) = (|| Some((record.read_u32()?, record.read_u32()?)))()
+                    .ok_or_invalid_data(|| {
+                        format!("failed to read function information record {idx}")
+                    })?;
+                let name = btf.func_name(type_id).ok_or_invalid_data(|| {
+                    format!("failed to find BTF function name for type {type_id}")
+                })?;
+                let func = Func {
+                    addr: *addr as Addr,
+                    size: u64::from(*len),
+                    name: name.to_string(),
+                };
+                Ok(func)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let () = funcs.sort_by_key(|func| func.addr);
+
+        let mut files = Vec::<String>::new();
+        let mut lines = Vec::new();
+
+        if !line_info.is_empty() {
+            if *line_info_rec_size < LINE_INFO_SIZE || line_info.len() % line_info_rec_size != 0 {
+                return Err(Error::with_invalid_data(format!(
+                    "BPF line information has unexpected record size {line_info_rec_size}"
+                )))
+            }
+
+            for (idx, (mut record, addr)) in line_info
+                .chunks_exact(*line_info_rec_size)
+                .zip(jited_line_info.iter())
+                .enumerate()
+            {
+                let (_insn_off, file_name_off, _line_off, line_col) = (|| {
+                    Some((
+                        record.read_u32()?,
+                        record.read_u32()?,
+                        record.read_u32()?,
+                        record.read_u32()?,
+                    ))
+                })()
+                .ok_or_invalid_data(|| format!("failed to read line information record {idx}"))?;
+
+                // Line information for instructions that got removed
+                // by the verifier is not JITed.
+                if *addr == 0 {
+                    continue
+                }
+
+                let file_name = btf.str(file_name_off).ok_or_invalid_data(|| {
+                    format!("failed to find BTF file name at offset {file_name_off}")
+                })?;
+                // Files are typically referenced by consecutive records
+                // and there usually are only a few of them.
+                let file = if let Some(file) = files.iter().rposition(|file| file == file_name) {
+                    file
+                } else {
+                    let () = files.push(file_name.to_string());
+                    files.len() - 1
+                };
+
+                let line = Line {
+                    addr: *addr as Addr,
+                    file,
+                    line: line_col >> 10,
+                    column: (line_col & 0x3ff) as u16,
+                };
+                let () = lines.push(line);
+            }
+            let () = lines.sort_by_key(|line| line.addr);
+        }
+
+        let slf = Self {
+            name: name.clone(),
+            funcs,
+            lines,
+            files,
+        };
+        Ok(slf)
+    }
+
+    fn find_func(&self, addr: Addr) -> Option<&Func> {
+        let idx = self.funcs.partition_point(|func| func.addr <= addr);
+        let func = self.funcs.get(idx.checked_sub(1)?)?;
+        (addr < func.addr.saturating_add(func.size as Addr)).then_some(func)
+    }
+
+    /// Check whether the program contains the given address.
+    pub fn contains(&self, addr: Addr) -> bool {
+        self.find_func(addr).is_some()
+    }
+}
+
+impl SymResolver for BpfProgResolver {
+    fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
+        let syms = self
+            .find_func(addr)
+            .map(|func| IntSym {
+                name: &func.name,
+                addr: func.addr,
+                // BPF programs are typically written in C, but we
+                // have no way of knowing for sure.
+                lang: SrcLang::Unknown,
+                module: None,
+            })
+            .into_iter()
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_addr(&self, _name: &str, _opts: &FindAddrOpts) -> Result<Vec<SymInfo>> {
+        Ok(Vec::new())
+    }
+
+    fn find_line_info(&self, addr: Addr) -> Result<Option<AddrLineInfo<'_>>> {
+        let func = if let Some(func) = self.find_func(addr) {
+            func
+        } else {
+            return Ok(None)
+        };
+
+        let idx = self.lines.partition_point(|line| line.addr <= addr);
+        let line = if let Some(line) = idx.checked_sub(1).and_then(|idx| self.lines.get(idx)) {
+            line
+        } else {
+            return Ok(None)
+        };
+        // Line information of a different function is of no use.
+        if line.addr < func.addr {
+            return Ok(None)
+        }
+
+        let (dir, file) = split_path(&self.files[line.file]);
+        let info = AddrLineInfo {
+            dir,
+            file,
+            line: (line.line != 0).then_some(line.line),
+            column: (line.column != 0).then_some(line.column),
+        };
+        Ok(Some(info))
+    }
+
+    fn find_inlined_fns(&self, _addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        Ok(Vec::new())
+    }
+
+    fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
+        None
+    }
+}
+
+impl Debug for BpfProgResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "BpfProgResolver {}", self.name)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::OsStr;
+    use std::path::Path;
+
+    use test_log::test;
+
+    use crate::bpf::btf::tests::BtfBuilder;
+
+
+    /// Create `ProgInfo` as the kernel would report it for a program
+    /// consisting of a main program and one sub-program.
+    fn prog_info() -> ProgInfo {
+        let mut btf = BtfBuilder::new();
+        let main = btf.add_func("handle_exec");
+        let sub = btf.add_func("lookup_task");
+        let file = btf.add_str("/src/bpf/exec.bpf.c");
+        let src_line = btf.add_str("int handle_exec(void *ctx)");
+
+        let func_info = [(0u32, main), (12, sub)]
+            .iter()
+            .flat_map(|(insn_off, type_id)| {
+                insn_off
+                    .to_ne_bytes()
+                    .into_iter()
+                    .chain(type_id.to_ne_bytes())
+            })
+            .collect();
+
+        let line_info = [
+            (0u32, 21u32, 3u32),
+            (4, 23, 5),
+            (8, 0, 0),
+            (12, 42, 1),
+            (14, 44, 12),
+        ]
+        .iter()
+        .flat_map(|(insn_off, line, col)| {
+            [*insn_off, file, src_line, (line << 10) | col]
+                .into_iter()
+                .flat_map(u32::to_ne_bytes)
+        })
+        .collect();
+
+        ProgInfo {
+            name: "handle_exec".to_string(),
+            jited_ksyms: vec![0xffffffffc0001000, 0xffffffffc0002000],
+            jited_func_lens: vec![0x100, 0x40],
+            func_info,
+            func_info_rec_size: 8,
+            line_info,
+            line_info_rec_size: 16,
+            // The third line record got removed by the verifier.
+            jited_line_info: vec![
+                0xffffffffc0001000,
+                0xffffffffc0001020,
+                0,
+                0xffffffffc0002000,
+                0xffffffffc0002010,
+            ],
+            btf_id: 42,
+            btf: btf.build(),
+        }
+    }
+
+
+    /// Check that we can symbolize addresses using BPF program
+    /// information.
+    #[test]
+    fn prog_symbolization() {
+        let info = prog_info();
+        assert!(info.contains(0xffffffffc00010ff));
+        assert!(!info.contains(0xffffffffc0001100));
+
+        let resolver = BpfProgResolver::from_info(&info).unwrap();
+        assert_eq!(
+            format!("{resolver:?}"),
+            "BpfProgResolver handle_exec".to_string()
+        );
+
+        let syms = resolver.find_syms(0xffffffffc0001024).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "handle_exec");
+        assert_eq!(syms[0].addr, 0xffffffffc0001000);
+
+        let info = resolver
+            .find_line_info(0xffffffffc0001024)
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.dir, Path::new("/src/bpf"));
+        assert_eq!(info.file, OsStr::new("exec.bpf.c"));
+        assert_eq!(info.line, Some(23));
+        assert_eq!(info.column, Some(5));
+
+        let syms = resolver.find_syms(0xffffffffc0002011).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "lookup_task");
+        assert_eq!(syms[0].addr, 0xffffffffc0002000);
+
+        let info = resolver
+            .find_line_info(0xffffffffc0002011)
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.line, Some(44));
+        assert_eq!(info.column, Some(12));
+
+        // Addresses outside of any function don't resolve.
+        assert!(resolver.find_syms(0xffffffffc0001100).unwrap().is_empty());
+        assert!(resolver
+            .find_line_info(0xffffffffc0001100)
+            .unwrap()
+            .is_none());
+    }
+
+    /// Check that we reject program information lacking the data we
+    /// need.
+    #[test]
+    fn prog_info_errors() {
+        let mut info = prog_info();
+        info.jited_ksyms = vec![0, 0];
+        let err = BpfProgResolver::from_info(&info).unwrap_err();
+        assert!(err.to_string().contains("JITed addresses"), "{err}");
+
+        let mut info = prog_info();
+        let _len = info.jited_func_lens.pop();
+        let err = BpfProgResolver::from_info(&info).unwrap_err();
+        assert!(err.to_string().contains("function lengths"), "{err}");
+
+        let mut info = prog_info();
+        info.func_info_rec_size = 6;
+        let err = BpfProgResolver::from_info(&info).unwrap_err();
+        assert!(err.to_string().contains("record size"), "{err}");
+
+        let mut info = prog_info();
+        info.btf = Vec::new();
+        let err = BpfProgResolver::from_info(&info).unwrap_err();
+        assert!(err.to_string().contains("BTF magic"), "{err}");
+    }
+}
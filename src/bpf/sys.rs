@@ -0,0 +1,241 @@
+//! Thin wrappers around the `bpf(2)` system call, as necessary for
+//! retrieving information about loaded BPF programs.
+//!
+//! All of the functionality in here requires the `CAP_SYS_ADMIN` (or
+//! `CAP_BPF` and `CAP_PERFMON`) capability.
+
+use std::io;
+use std::mem::size_of;
+use std::os::unix::io::RawFd;
+
+use libc::ENOENT;
+
+use super::prog::ProgInfo;
+
+
+const BPF_PROG_GET_NEXT_ID: i32 = 11;
+const BPF_PROG_GET_FD_BY_ID: i32 = 13;
+const BPF_OBJ_GET_INFO_BY_FD: i32 = 15;
+const BPF_BTF_GET_FD_BY_ID: i32 = 19;
+
+
+/// The `union bpf_attr` variant used by `BPF_*_GET_NEXT_ID` and
+/// `BPF_*_GET_FD_BY_ID` commands.
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct IdAttr {
+    id: u32,
+    next_id: u32,
+    open_flags: u32,
+}
+
+/// The `union bpf_attr` variant used by the `BPF_OBJ_GET_INFO_BY_FD`
+/// command.
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct InfoAttr {
+    bpf_fd: u32,
+    info_len: u32,
+    info: u64,
+}
+
+/// A prefix of `struct bpf_prog_info`, containing all the members we
+/// are interested in.
+#[allow(dead_code, non_camel_case_types)]
+#[repr(C)]
+#[derive(Default)]
+struct bpf_prog_info {
+    type_: u32,
+    id: u32,
+    tag: [u8; 8],
+    jited_prog_len: u32,
+    xlated_prog_len: u32,
+    jited_prog_insns: u64,
+    xlated_prog_insns: u64,
+    load_time: u64,
+    created_by_uid: u32,
+    nr_map_ids: u32,
+    map_ids: u64,
+    name: [u8; 16],
+    ifindex: u32,
+    gpl_compatible: u32,
+    netns_dev: u64,
+    netns_ino: u64,
+    nr_jited_ksyms: u32,
+    nr_jited_func_lens: u32,
+    jited_ksyms: u64,
+    jited_func_lens: u64,
+    btf_id: u32,
+    func_info_rec_size: u32,
+    func_info: u64,
+    nr_func_info: u32,
+    nr_line_info: u32,
+    line_info: u64,
+    jited_line_info: u64,
+    nr_jited_line_info: u32,
+    line_info_rec_size: u32,
+    jited_line_info_rec_size: u32,
+    _pad: u32,
+}
+
+/// `struct bpf_btf_info`
+#[allow(dead_code, non_camel_case_types)]
+#[repr(C)]
+#[derive(Default)]
+struct bpf_btf_info {
+    btf: u64,
+    btf_size: u32,
+    id: u32,
+    name: u64,
+    name_len: u32,
+    kernel_btf: u32,
+}
+
+
+/// An owned file descriptor referencing a BPF object.
+struct Fd(RawFd);
+
+impl Drop for Fd {
+    fn drop(&mut self) {
+        // SAFETY: We own the file descriptor.
+        let _rc = unsafe { libc::close(self.0) };
+    }
+}
+
+
+fn bpf<T>(cmd: i32, attr: &mut T) -> io::Result<RawFd> {
+    // SAFETY: `attr` is a valid `union bpf_attr` variant of the
+    //         provided size.
+    let rc = unsafe { libc::syscall(libc::SYS_bpf, cmd, attr as *mut T, size_of::<T>() as u32) };
+    if rc < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(rc as RawFd)
+    }
+}
+
+/// Open a file descriptor for the object with the given ID, returning
+/// `None` if no such object exists (anymore).
+fn fd_by_id(cmd: i32, id: u32) -> io::Result<Option<Fd>> {
+    let mut attr = IdAttr {
+        id,
+        ..Default::default()
+    };
+    match bpf(cmd, &mut attr) {
+        Ok(fd) => Ok(Some(Fd(fd))),
+        Err(err) if err.raw_os_error() == Some(ENOENT) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn obj_info<T>(fd: &Fd, info: &mut T) -> io::Result<()> {
+    let mut attr = InfoAttr {
+        bpf_fd: fd.0 as u32,
+        info_len: size_of::<T>() as u32,
+        info: info as *mut T as u64,
+    };
+    let _rc = bpf(BPF_OBJ_GET_INFO_BY_FD, &mut attr)?;
+    Ok(())
+}
+
+
+/// Retrieve the ID of the next loaded BPF program following the one
+/// with ID `start`, if any.
+pub(crate) fn next_prog_id(start: u32) -> io::Result<Option<u32>> {
+    let mut attr = IdAttr {
+        id: start,
+        ..Default::default()
+    };
+    match bpf(BPF_PROG_GET_NEXT_ID, &mut attr) {
+        Ok(_) => Ok(Some(attr.next_id)),
+        Err(err) if err.raw_os_error() == Some(ENOENT) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Retrieve information about the BPF program with the given ID,
+/// returning `None` if it got unloaded in the meantime.
+///
+/// BTF data are not retrieved; [`btf_data`] can be used for that.
+pub(crate) fn prog_info(id: u32) -> io::Result<Option<ProgInfo>> {
+    let fd = if let Some(fd) = fd_by_id(BPF_PROG_GET_FD_BY_ID, id)? {
+        fd
+    } else {
+        return Ok(None)
+    };
+
+    // The first query only tells us how much space we need for the
+    // various arrays.
+    let mut info = bpf_prog_info::default();
+    let () = obj_info(&fd, &mut info)?;
+
+    let mut jited_ksyms = vec![0u64; info.nr_jited_ksyms as usize];
+    let mut jited_func_lens = vec![0u32; info.nr_jited_func_lens as usize];
+    let mut func_info = vec![0u8; info.nr_func_info as usize * info.func_info_rec_size as usize];
+    let mut line_info = vec![0u8; info.nr_line_info as usize * info.line_info_rec_size as usize];
+    let mut jited_line_info = vec![0u64; info.nr_jited_line_info as usize];
+
+    let mut full = bpf_prog_info {
+        nr_jited_ksyms: info.nr_jited_ksyms,
+        nr_jited_func_lens: info.nr_jited_func_lens,
+        jited_ksyms: jited_ksyms.as_mut_ptr() as u64,
+        jited_func_lens: jited_func_lens.as_mut_ptr() as u64,
+        func_info_rec_size: info.func_info_rec_size,
+        func_info: func_info.as_mut_ptr() as u64,
+        nr_func_info: info.nr_func_info,
+        nr_line_info: info.nr_line_info,
+        line_info: line_info.as_mut_ptr() as u64,
+        jited_line_info: jited_line_info.as_mut_ptr() as u64,
+        nr_jited_line_info: info.nr_jited_line_info,
+        line_info_rec_size: info.line_info_rec_size,
+        jited_line_info_rec_size: size_of::<u64>() as u32,
+        ..Default::default()
+    };
+    let () = obj_info(&fd, &mut full)?;
+
+    let len = full
+        .name
+        .iter()
+        .position(|byte| *byte == b'\0')
+        .unwrap_or(full.name.len());
+    let name = String::from_utf8_lossy(&full.name[..len]).into_owned();
+
+    let info = ProgInfo {
+        name,
+        jited_ksyms,
+        jited_func_lens,
+        func_info,
+        func_info_rec_size: full.func_info_rec_size as usize,
+        line_info,
+        line_info_rec_size: full.line_info_rec_size as usize,
+        jited_line_info,
+        btf_id: full.btf_id,
+        btf: Vec::new(),
+    };
+    Ok(Some(info))
+}
+
+/// Retrieve the raw data of the BTF object with the given ID.
+pub(crate) fn btf_data(id: u32) -> io::Result<Vec<u8>> {
+    let fd = fd_by_id(BPF_BTF_GET_FD_BY_ID, id)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("BTF object {id} does not exist"),
+        )
+    })?;
+
+    let mut info = bpf_btf_info::default();
+    let () = obj_info(&fd, &mut info)?;
+
+    let mut data = vec![0u8; info.btf_size as usize];
+    let mut info = bpf_btf_info {
+        btf: data.as_mut_ptr() as u64,
+        btf_size: info.btf_size,
+        ..Default::default()
+    };
+    let () = obj_info(&fd, &mut info)?;
+    let () = data.truncate(info.btf_size as usize);
+    Ok(data)
+}
@@ -0,0 +1,5 @@
+mod parser;
+mod resolver;
+mod types;
+
+pub use resolver::BreakpadResolver;
@@ -0,0 +1,279 @@
+//! Parser of the Breakpad symbol file format.
+//!
+//! A Breakpad symbol file is a line based text format. Each line
+//! contains a record, introduced by a keyword:
+//!
+//! * `MODULE`: describes the module (e.g., the executable) the file
+//!   belongs to
+//! * `FILE`: maps a number to a source file name
+//! * `INLINE_ORIGIN`: maps a number to the name of an inlined function
+//! * `FUNC`: describes a function's address range and name
+//! * `INLINE`: describes a function inlined into the preceding `FUNC`
+//! * `PUBLIC`: describes a symbol without size information
+//! * `INFO` and `STACK`: provide information irrelevant for
+//!   symbolization
+//!
+//! Lines without a keyword are line records, mapping an address range
+//! of the preceding `FUNC` to a source code line. All addresses and
+//! sizes are hexadecimal, while all other numbers are decimal.
+//!
+//! See <https://chromium.googlesource.com/breakpad/breakpad/+/HEAD/docs/symbol_files.md>
+
+use std::str;
+use std::str::FromStr;
+
+use crate::log::debug;
+use crate::Addr;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+use super::types::Func;
+use super::types::Inlinee;
+use super::types::Line;
+use super::types::Public;
+use super::types::SymbolFile;
+
+
+/// Parse a hexadecimal number.
+fn parse_hex(s: &str) -> Option<u64> {
+    u64::from_str_radix(s, 16).ok()
+}
+
+/// Parse a decimal number.
+fn parse_dec<T>(s: &str) -> Option<T>
+where
+    T: FromStr,
+{
+    s.parse().ok()
+}
+
+
+/// Parse the remainder of a `FILE` or `INLINE_ORIGIN` record, i.e., a
+/// number followed by a name.
+fn parse_numbered_name(rest: &str) -> Option<(u32, &str)> {
+    let (num, name) = rest.split_once(' ')?;
+    Some((parse_dec(num)?, name))
+}
+
+/// Parse the remainder of a `FUNC` record.
+fn parse_func(rest: &str) -> Option<Func<'_>> {
+    let rest = rest.strip_prefix("m ").unwrap_or(rest);
+    let mut fields = rest.splitn(4, ' ');
+    let addr = parse_hex(fields.next()?)? as Addr;
+    let size = parse_hex(fields.next()?)?;
+    let _param_size = parse_hex(fields.next()?)?;
+    let name = fields.next().unwrap_or("");
+
+    let func = Func {
+        addr,
+        size,
+        name,
+        lines: Vec::new(),
+        inlinees: Vec::new(),
+    };
+    Some(func)
+}
+
+/// Parse the remainder of a `PUBLIC` record.
+fn parse_public(rest: &str) -> Option<Public<'_>> {
+    let rest = rest.strip_prefix("m ").unwrap_or(rest);
+    let mut fields = rest.splitn(3, ' ');
+    let addr = parse_hex(fields.next()?)? as Addr;
+    let _param_size = parse_hex(fields.next()?)?;
+    let name = fields.next().unwrap_or("");
+
+    let public = Public { addr, name };
+    Some(public)
+}
+
+/// Parse the remainder of an `INLINE` record.
+fn parse_inline(rest: &str) -> Option<Inlinee> {
+    let mut fields = rest.split(' ');
+    let depth = parse_dec(fields.next()?)?;
+    let call_line = parse_dec(fields.next()?)?;
+    let call_file = parse_dec(fields.next()?)?;
+    let origin = parse_dec(fields.next()?)?;
+
+    let mut ranges = Vec::new();
+    while let Some(addr) = fields.next() {
+        let addr = parse_hex(addr)? as Addr;
+        let size = parse_hex(fields.next()?)? as Addr;
+        let () = ranges.push(addr..addr.saturating_add(size));
+    }
+
+    if ranges.is_empty() {
+        return None
+    }
+
+    let inlinee = Inlinee {
+        depth,
+        call_line,
+        call_file,
+        origin,
+        ranges,
+    };
+    Some(inlinee)
+}
+
+/// Parse a line record.
+fn parse_line_record(line: &str) -> Option<Line> {
+    let mut fields = line.split(' ');
+    let addr = parse_hex(fields.next()?)? as Addr;
+    let size = parse_hex(fields.next()?)?;
+    let line = parse_dec(fields.next()?)?;
+    let file = parse_dec(fields.next()?)?;
+
+    let line = Line {
+        addr,
+        size,
+        line,
+        file,
+    };
+    Some(line)
+}
+
+
+/// Parse a single line of a symbol file, adding the record it contains
+/// to `file`.
+fn parse_record<'dat>(line: &'dat str, file: &mut SymbolFile<'dat>) -> Option<()> {
+    let line = line.trim_end();
+    if line.is_empty() {
+        return Some(())
+    }
+
+    let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+    match keyword {
+        "MODULE" | "INFO" | "STACK" => (),
+        "FILE" => {
+            let (num, name) = parse_numbered_name(rest)?;
+            let _prev = file.files.insert(num, name);
+        }
+        "INLINE_ORIGIN" => {
+            let (num, name) = parse_numbered_name(rest)?;
+            let _prev = file.inline_origins.insert(num, name);
+        }
+        "FUNC" => {
+            let func = parse_func(rest)?;
+            let () = file.funcs.push(func);
+        }
+        "PUBLIC" => {
+            let public = parse_public(rest)?;
+            let () = file.publics.push(public);
+        }
+        "INLINE" => {
+            let inlinee = parse_inline(rest)?;
+            let func = file.funcs.last_mut()?;
+            let () = func.inlinees.push(inlinee);
+        }
+        _ if keyword.bytes().all(|b| b.is_ascii_uppercase() || b == b'_') => {
+            debug!("ignoring unknown Breakpad record: {line}");
+        }
+        _ => {
+            let line = parse_line_record(line)?;
+            let func = file.funcs.last_mut()?;
+            let () = func.lines.push(line);
+        }
+    }
+    Some(())
+}
+
+
+/// Parse the Breakpad symbol file contained in `data`.
+pub(super) fn parse(data: &[u8]) -> Result<SymbolFile<'_>> {
+    let text = str::from_utf8(data).map_err(Error::with_invalid_data)?;
+    let mut file = SymbolFile::default();
+
+    for (idx, line) in text.lines().enumerate() {
+        let () = parse_record(line, &mut file).ok_or_invalid_data(|| {
+            format!(
+                "encountered malformed Breakpad record in line {}: {line}",
+                idx + 1
+            )
+        })?;
+    }
+
+    let () = file.funcs.sort_by_key(|func| func.addr);
+    let () = file
+        .funcs
+        .iter_mut()
+        .for_each(|func| func.lines.sort_by_key(|line| line.addr));
+    let () = file.publics.sort_by_key(|public| public.addr);
+    Ok(file)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+
+    /// Check that we can parse the various records of a symbol file.
+    #[test]
+    fn record_parsing() {
+        let data = br#"MODULE Linux x86_64 000000000000000000000000000000000 test.bin
+INFO CODE_ID 00000000000000000000000000000000
+FILE 0 /tmp/some file.c
+INLINE_ORIGIN 3 inlined_fn
+FUNC m 2000 10 0 func(int, char)
+2008 8 12 0
+INLINE 0 7 0 3 2004 4 200c 2
+2000 8 10 0
+PUBLIC 1000 0 public_sym
+STACK CFI INIT 2000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^
+"#;
+        let file = parse(data).unwrap();
+        assert_eq!(file.files.get(&0), Some(&"/tmp/some file.c"));
+        assert_eq!(file.inline_origins.get(&3), Some(&"inlined_fn"));
+        assert_eq!(file.publics.len(), 1);
+        assert_eq!(file.publics[0].addr, 0x1000);
+        assert_eq!(file.publics[0].name, "public_sym");
+
+        assert_eq!(file.funcs.len(), 1);
+        let func = &file.funcs[0];
+        assert_eq!(func.addr, 0x2000);
+        assert_eq!(func.size, 0x10);
+        assert_eq!(func.name, "func(int, char)");
+        assert_eq!(func.lines.len(), 2);
+        // Lines should have been sorted.
+        assert_eq!(func.lines[0].addr, 0x2000);
+        assert_eq!(func.lines[0].line, 10);
+        assert_eq!(func.lines[1].addr, 0x2008);
+        assert_eq!(func.lines[1].line, 12);
+
+        assert_eq!(func.inlinees.len(), 1);
+        let inlinee = &func.inlinees[0];
+        assert_eq!(inlinee.depth, 0);
+        assert_eq!(inlinee.call_line, 7);
+        assert_eq!(inlinee.call_file, 0);
+        assert_eq!(inlinee.origin, 3);
+        assert_eq!(inlinee.ranges, vec![0x2004..0x2008, 0x200c..0x200e]);
+
+        assert_eq!(file.find_func(0x1fff).map(|func| func.name), None);
+        assert_eq!(
+            file.find_func(0x200f).map(|func| func.name),
+            Some(func.name)
+        );
+        assert_eq!(file.find_func(0x2010).map(|func| func.name), None);
+        assert_eq!(file.find_public(0x1000).unwrap().name, "public_sym");
+        assert_eq!(file.find_public(0x1fff).unwrap().name, "public_sym");
+        // The public symbol does not extend past the function.
+        assert!(file.find_public(0x2010).is_none());
+    }
+
+    /// Check that we report malformed records properly.
+    #[test]
+    fn malformed_record_parsing() {
+        let err = parse(b"FILE 0\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+
+        let err = parse(b"FILE 0 test.c\nFUNC 1000 xyz 0 func\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+
+        // A line record has to be preceded by a function.
+        let err = parse(b"1000 8 10 0\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+    }
+}
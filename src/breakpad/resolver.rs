@@ -0,0 +1,298 @@
+use std::ffi::OsStr;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::inspect::FindAddrOpts;
+use crate::inspect::SymInfo;
+use crate::mmap::Mmap;
+use crate::symbolize::AddrLineInfo;
+use crate::util::split_path;
+use crate::Addr;
+use crate::IntInlinedFn;
+use crate::IntSym;
+use crate::IntoError as _;
+use crate::Result;
+use crate::SrcLang;
+use crate::SymResolver;
+
+use super::parser::parse;
+use super::types::SymbolFile;
+
+
+/// The symbol resolver for the Breakpad symbol file format.
+pub struct BreakpadResolver<'dat> {
+    file_name: Option<PathBuf>,
+    // SAFETY: This member should be listed before `_mmap` to make sure
+    //         we never end up with dangling references.
+    symbols: SymbolFile<'dat>,
+    /// The memory mapping backing `symbols`, if loaded from a file.
+    _mmap: Option<Mmap>,
+}
+
+impl BreakpadResolver<'static> {
+    /// Create a `BreakpadResolver` that loads data from the provided
+    /// file.
+    pub fn new(file_name: PathBuf) -> Result<Self> {
+        let mmap = Mmap::builder().open(&file_name)?;
+        let symbols = parse(&mmap)?;
+        let slf = Self {
+            file_name: Some(file_name),
+            // SAFETY: We own the underlying `Mmap` object and never hand
+            //         out any 'static references to its data. So it is
+            //         safe for us to transmute the lifetime.
+            symbols: unsafe { mem::transmute::<SymbolFile<'_>, SymbolFile<'static>>(symbols) },
+            _mmap: Some(mmap),
+        };
+
+        Ok(slf)
+    }
+}
+
+impl<'dat> BreakpadResolver<'dat> {
+    /// Create a `BreakpadResolver` that works on the provided "raw"
+    /// Breakpad symbol file data.
+    pub(crate) fn with_data(data: &'dat [u8]) -> Result<Self> {
+        let symbols = parse(data)?;
+        let slf = Self {
+            file_name: None,
+            symbols,
+            _mmap: None,
+        };
+
+        Ok(slf)
+    }
+
+    /// Look up the source file with the provided `FILE` record number.
+    fn file(&self, num: u32) -> Result<(&Path, &OsStr)> {
+        let path = self
+            .symbols
+            .files
+            .get(&num)
+            .ok_or_invalid_data(|| format!("failed to find FILE record {num}"))?;
+        Ok(split_path(path))
+    }
+}
+
+impl SymResolver for BreakpadResolver<'_> {
+    fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
+        let (name, sym_addr) = if let Some(func) = self.symbols.find_func(addr) {
+            (func.name, func.addr)
+        } else if let Some(public) = self.symbols.find_public(addr) {
+            (public.name, public.addr)
+        } else {
+            return Ok(Vec::new())
+        };
+
+        let sym = IntSym {
+            name,
+            addr: sym_addr,
+            // Breakpad does not carry any source code language
+            // information.
+            lang: SrcLang::Unknown,
+            module: None,
+        };
+        Ok(vec![sym])
+    }
+
+    fn find_addr(&self, _name: &str, _opts: &FindAddrOpts) -> Result<Vec<SymInfo>> {
+        // Breakpad symbol files are not indexed by name. We may support
+        // this functionality in the future if needed.
+        Ok(Vec::new())
+    }
+
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip(self), fields(file = debug(&self.file_name))))]
+    fn find_line_info(&self, addr: Addr) -> Result<Option<AddrLineInfo<'_>>> {
+        let line = if let Some(line) = self
+            .symbols
+            .find_func(addr)
+            .and_then(|func| func.find_line(addr))
+        {
+            line
+        } else {
+            return Ok(None)
+        };
+
+        let (dir, file) = self.file(line.file)?;
+        let info = AddrLineInfo {
+            dir,
+            file,
+            line: (line.line != 0).then_some(line.line),
+            column: None,
+        };
+        Ok(Some(info))
+    }
+
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        let func = if let Some(func) = self.symbols.find_func(addr) {
+            func
+        } else {
+            return Ok(Vec::new())
+        };
+
+        let mut inlinees = func
+            .inlinees
+            .iter()
+            .filter(|inlinee| inlinee.ranges.iter().any(|range| range.contains(&addr)))
+            .collect::<Vec<_>>();
+        let () = inlinees.sort_by_key(|inlinee| inlinee.depth);
+
+        inlinees
+            .into_iter()
+            .map(|inlinee| {
+                let name = self
+                    .symbols
+                    .inline_origins
+                    .get(&inlinee.origin)
+                    .ok_or_invalid_data(|| {
+                        format!("failed to find INLINE_ORIGIN record {}", inlinee.origin)
+                    })?;
+                let (dir, file) = self.file(inlinee.call_file)?;
+                let call_site = AddrLineInfo {
+                    dir,
+                    file,
+                    line: (inlinee.call_line != 0).then_some(inlinee.call_line),
+                    column: None,
+                };
+
+                let inlined = IntInlinedFn {
+                    name,
+                    call_site: Some(call_site),
+                    // Breakpad does not carry any source code language
+                    // information.
+                    lang: SrcLang::Unknown,
+                };
+                Ok(inlined)
+            })
+            .collect()
+    }
+
+    fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
+        // Unavailable
+        None
+    }
+}
+
+impl Debug for BreakpadResolver<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let path = self
+            .file_name
+            .as_deref()
+            .unwrap_or_else(|| Path::new("<unknown-file>"));
+        write!(f, "Breakpad {}", path.display())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs::read as read_file;
+
+    use test_log::test;
+
+
+    /// Check that we can create a `BreakpadResolver` using a "raw" slice
+    /// of data.
+    #[test]
+    fn creation_from_raw_data() {
+        let test_sym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.sym");
+        let data = read_file(test_sym).unwrap();
+
+        let resolver = BreakpadResolver::with_data(&data).unwrap();
+        assert_eq!(resolver.file_name, None);
+        assert_eq!(format!("{resolver:?}"), "Breakpad <unknown-file>");
+    }
+
+    /// Check that we can find symbols, including ones only described by
+    /// `PUBLIC` records.
+    #[test]
+    fn find_syms() {
+        let test_sym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.sym");
+        let resolver = BreakpadResolver::new(test_sym).unwrap();
+
+        let syms = resolver.find_syms(0x2000105).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "factorial");
+        assert_eq!(syms[0].addr, 0x2000100);
+
+        let syms = resolver.find_syms(0x200005e).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "dummy");
+        assert_eq!(syms[0].addr, 0x200005d);
+
+        // There is nothing before `main`.
+        let syms = resolver.find_syms(0x1ffffff).unwrap();
+        assert!(syms.is_empty());
+    }
+
+    /// Make sure that we can find file line information for a function,
+    /// if available.
+    #[test]
+    fn find_line_info() {
+        let test_sym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.sym");
+        let resolver = BreakpadResolver::new(test_sym).unwrap();
+
+        let info = resolver.find_line_info(0x2000000).unwrap().unwrap();
+        assert_eq!(info.line, Some(34));
+        assert_eq!(info.dir, Path::new("/tmp/blazesym/data"));
+        assert_eq!(info.file, "test-stable-addresses.c");
+
+        let info = resolver.find_line_info(0x2000100).unwrap().unwrap();
+        assert_eq!(info.line, Some(8));
+        assert_eq!(info.file, "test-stable-addresses.c");
+
+        let info = resolver.find_line_info(0x200003f).unwrap().unwrap();
+        assert_eq!(info.line, Some(9));
+        assert_eq!(info.file, "test-stable-addresses-cu2.c");
+
+        // `dummy` is only described by a `PUBLIC` record and has no line
+        // information.
+        let info = resolver.find_line_info(0x200005d).unwrap();
+        assert!(info.is_none());
+    }
+
+    /// Check that we can find the functions inlined at an address.
+    #[test]
+    fn find_inlined_fns() {
+        let test_sym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.sym");
+        let resolver = BreakpadResolver::new(test_sym).unwrap();
+
+        // `factorial` does not have any inlined functions.
+        let inlined = resolver.find_inlined_fns(0x2000100).unwrap();
+        assert!(inlined.is_empty());
+
+        let inlined = resolver.find_inlined_fns(0x2000208).unwrap();
+        assert_eq!(inlined.len(), 2);
+
+        let outer = &inlined[0];
+        assert_eq!(outer.name, "factorial_inline_wrapper");
+        let call_site = outer.call_site.as_ref().unwrap();
+        assert_eq!(call_site.file, "test-stable-addresses.c");
+        assert_eq!(call_site.line, Some(53));
+
+        let inner = &inlined[1];
+        assert_eq!(inner.name, "factorial_2nd_layer_inline_wrapper");
+        let call_site = inner.call_site.as_ref().unwrap();
+        assert_eq!(call_site.file, "test-stable-addresses.c");
+        assert_eq!(call_site.line, Some(48));
+
+        // At this address only the outer function is inlined.
+        let inlined = resolver.find_inlined_fns(0x200020e).unwrap();
+        assert_eq!(inlined.len(), 1);
+        assert_eq!(inlined[0].name, "factorial_inline_wrapper");
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::Addr;
+
+
+/// A `FUNC` record, along with the line and `INLINE` records following
+/// it.
+#[derive(Debug)]
+pub(crate) struct Func<'dat> {
+    /// The function's start address.
+    pub addr: Addr,
+    /// The size of the function's code, in bytes.
+    pub size: u64,
+    /// The function's name.
+    pub name: &'dat str,
+    /// The function's line records, sorted by address.
+    pub lines: Vec<Line>,
+    /// The functions inlined into this one.
+    pub inlinees: Vec<Inlinee>,
+}
+
+impl Func<'_> {
+    /// Check whether the function's code covers `addr`.
+    #[inline]
+    pub fn contains(&self, addr: Addr) -> bool {
+        (self.addr..self.addr.saturating_add(self.size as Addr)).contains(&addr)
+    }
+
+    /// Find the line record covering `addr`.
+    pub fn find_line(&self, addr: Addr) -> Option<&Line> {
+        let idx = self.lines.partition_point(|line| line.addr <= addr);
+        let line = self.lines.get(idx.checked_sub(1)?)?;
+        (addr < line.addr.saturating_add(line.size as Addr)).then_some(line)
+    }
+}
+
+
+/// A line record, mapping an address range to a source code line.
+#[derive(Debug)]
+pub(crate) struct Line {
+    /// The start address of the range.
+    pub addr: Addr,
+    /// The size of the range, in bytes.
+    pub size: u64,
+    /// The line number.
+    pub line: u32,
+    /// The number of the `FILE` record describing the source file.
+    pub file: u32,
+}
+
+
+/// An `INLINE` record, describing a function that got inlined.
+#[derive(Debug)]
+pub(crate) struct Inlinee {
+    /// The nesting level, with `0` being the outermost inlined
+    /// function.
+    pub depth: u32,
+    /// The line of the call site in the calling function.
+    pub call_line: u32,
+    /// The number of the `FILE` record describing the call site's
+    /// source file.
+    pub call_file: u32,
+    /// The number of the `INLINE_ORIGIN` record naming the inlined
+    /// function.
+    pub origin: u32,
+    /// The address ranges covered by the inlined function.
+    pub ranges: Vec<Range<Addr>>,
+}
+
+
+/// A `PUBLIC` record, i.e., a symbol without size information.
+#[derive(Debug)]
+pub(crate) struct Public<'dat> {
+    /// The symbol's address.
+    pub addr: Addr,
+    /// The symbol's name.
+    pub name: &'dat str,
+}
+
+
+/// The parsed contents of a Breakpad symbol file.
+#[derive(Debug, Default)]
+pub(crate) struct SymbolFile<'dat> {
+    /// Source file names, by `FILE` record number.
+    pub files: HashMap<u32, &'dat str>,
+    /// Inlined function names, by `INLINE_ORIGIN` record number.
+    pub inline_origins: HashMap<u32, &'dat str>,
+    /// All functions, sorted by address.
+    pub funcs: Vec<Func<'dat>>,
+    /// All public symbols, sorted by address.
+    pub publics: Vec<Public<'dat>>,
+}
+
+impl<'dat> SymbolFile<'dat> {
+    /// Find the function covering `addr`.
+    pub fn find_func(&self, addr: Addr) -> Option<&Func<'dat>> {
+        let idx = self.funcs.partition_point(|func| func.addr <= addr);
+        let func = self.funcs.get(idx.checked_sub(1)?)?;
+        func.contains(addr).then_some(func)
+    }
+
+    /// Find the public symbol covering `addr`.
+    ///
+    /// Public symbols do not carry size information and are assumed to
+    /// extend until the next public symbol or function.
+    pub fn find_public(&self, addr: Addr) -> Option<&Public<'dat>> {
+        let idx = self.publics.partition_point(|public| public.addr <= addr);
+        let public = self.publics.get(idx.checked_sub(1)?)?;
+
+        let idx = self.funcs.partition_point(|func| func.addr <= addr);
+        if let Some(func) = idx.checked_sub(1).and_then(|idx| self.funcs.get(idx)) {
+            if func.addr > public.addr {
+                return None
+            }
+        }
+        Some(public)
+    }
+}
@@ -17,6 +17,7 @@ use crate::normalize::NormalizedUserAddrs;
 use crate::normalize::Normalizer;
 use crate::normalize::Unknown;
 use crate::normalize::UserAddrMeta;
+use crate::normalize::Vdso;
 use crate::util::slice_from_user_array;
 use crate::Addr;
 
@@ -80,6 +81,8 @@ pub enum blaze_user_addr_meta_kind {
     BLAZE_USER_ADDR_APK_ELF,
     /// [`blaze_user_addr_meta_variant::elf`] is valid.
     BLAZE_USER_ADDR_ELF,
+    /// [`blaze_user_addr_meta_variant::vdso`] is valid.
+    BLAZE_USER_ADDR_VDSO,
 }
 
 
@@ -104,6 +107,7 @@ impl From<ApkElf> for blaze_user_addr_meta_apk_elf {
             apk_path,
             elf_path,
             elf_build_id,
+            access: _,
             _non_exhaustive: (),
         } = other;
         Self {
@@ -153,6 +157,7 @@ impl From<blaze_user_addr_meta_apk_elf> for ApkElf {
                 Box::<[u8]>::from_raw(slice::from_raw_parts_mut(elf_build_id, elf_build_id_len))
                     .into_vec()
             }),
+            access: None,
             _non_exhaustive: (),
         }
     }
@@ -176,6 +181,7 @@ impl From<Elf> for blaze_user_addr_meta_elf {
         let Elf {
             path,
             build_id,
+            access: _,
             _non_exhaustive: (),
         } = other;
         Self {
@@ -214,6 +220,61 @@ impl From<blaze_user_addr_meta_elf> for Elf {
             path: PathBuf::from(OsString::from_vec(
                 unsafe { CString::from_raw(path) }.into_bytes(),
             )),
+            build_id: (!build_id.is_null()).then(|| unsafe {
+                Box::<[u8]>::from_raw(slice::from_raw_parts_mut(build_id, build_id_len)).into_vec()
+            }),
+            access: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+
+/// C compatible version of [`Vdso`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_user_addr_meta_vdso {
+    /// The length of the build ID, in bytes.
+    pub build_id_len: usize,
+    /// The optional build ID of the vDSO, if found.
+    pub build_id: *mut u8,
+}
+
+impl From<Vdso> for blaze_user_addr_meta_vdso {
+    fn from(other: Vdso) -> Self {
+        let Vdso {
+            build_id,
+            _non_exhaustive: (),
+        } = other;
+        Self {
+            build_id_len: build_id
+                .as_ref()
+                .map(|build_id| build_id.len())
+                .unwrap_or(0),
+            build_id: build_id
+                .map(|build_id| {
+                    // SAFETY: We know the pointer is valid because it
+                    //         came from a `Box`.
+                    unsafe {
+                        Box::into_raw(build_id.into_boxed_slice())
+                            .as_mut()
+                            .unwrap()
+                            .as_mut_ptr()
+                    }
+                })
+                .unwrap_or_else(ptr::null_mut),
+        }
+    }
+}
+
+impl From<blaze_user_addr_meta_vdso> for Vdso {
+    fn from(other: blaze_user_addr_meta_vdso) -> Self {
+        let blaze_user_addr_meta_vdso {
+            build_id_len,
+            build_id,
+        } = other;
+
+        Vdso {
             build_id: (!build_id.is_null()).then(|| unsafe {
                 Box::<[u8]>::from_raw(slice::from_raw_parts_mut(build_id, build_id_len)).into_vec()
             }),
@@ -256,6 +317,8 @@ pub union blaze_user_addr_meta_variant {
     pub apk_elf: ManuallyDrop<blaze_user_addr_meta_apk_elf>,
     /// Valid on [`blaze_user_addr_meta_kind::BLAZE_USER_ADDR_ELF`].
     pub elf: ManuallyDrop<blaze_user_addr_meta_elf>,
+    /// Valid on [`blaze_user_addr_meta_kind::BLAZE_USER_ADDR_VDSO`].
+    pub vdso: ManuallyDrop<blaze_user_addr_meta_vdso>,
     /// Valid on [`blaze_user_addr_meta_kind::BLAZE_USER_ADDR_UNKNOWN`].
     pub unknown: ManuallyDrop<blaze_user_addr_meta_unknown>,
 }
@@ -288,6 +351,12 @@ impl From<UserAddrMeta> for blaze_user_addr_meta {
                     elf: ManuallyDrop::new(blaze_user_addr_meta_elf::from(elf)),
                 },
             },
+            UserAddrMeta::Vdso(vdso) => Self {
+                kind: blaze_user_addr_meta_kind::BLAZE_USER_ADDR_VDSO,
+                variant: blaze_user_addr_meta_variant {
+                    vdso: ManuallyDrop::new(blaze_user_addr_meta_vdso::from(vdso)),
+                },
+            },
             UserAddrMeta::Unknown(unknown) => Self {
                 kind: blaze_user_addr_meta_kind::BLAZE_USER_ADDR_UNKNOWN,
                 variant: blaze_user_addr_meta_variant {
@@ -471,6 +540,9 @@ pub unsafe extern "C" fn blaze_user_addrs_free(addrs: *mut blaze_normalized_user
             blaze_user_addr_meta_kind::BLAZE_USER_ADDR_ELF => {
                 let _elf = Elf::from(ManuallyDrop::into_inner(unsafe { addr_meta.variant.elf }));
             }
+            blaze_user_addr_meta_kind::BLAZE_USER_ADDR_VDSO => {
+                let _vdso = Vdso::from(ManuallyDrop::into_inner(unsafe { addr_meta.variant.vdso }));
+            }
             blaze_user_addr_meta_kind::BLAZE_USER_ADDR_UNKNOWN => {
                 let _unknown = Unknown::from(ManuallyDrop::into_inner(unsafe {
                     addr_meta.variant.unknown
@@ -571,6 +643,7 @@ mod tests {
             apk_path: PathBuf::from("/tmp/archive.apk"),
             elf_path: PathBuf::from("file.so"),
             elf_build_id: Some(vec![0x01, 0x02, 0x03, 0x04]),
+            access: None,
             _non_exhaustive: (),
         };
 
@@ -581,6 +654,7 @@ mod tests {
             apk_path: PathBuf::new(),
             elf_path: PathBuf::new(),
             elf_build_id: None,
+            access: None,
             _non_exhaustive: (),
         };
 
@@ -595,10 +669,32 @@ mod tests {
         let elf = Elf {
             path: PathBuf::from("/tmp/file.so"),
             build_id: Some(vec![0x01, 0x02, 0x03, 0x04]),
+            access: None,
             _non_exhaustive: (),
         };
 
         let elf_new = Elf::from(blaze_user_addr_meta_elf::from(elf.clone()));
         assert_eq!(elf_new, elf);
     }
+
+    /// Check that we can convert a [`Vdso`] into a
+    /// [`blaze_user_addr_meta_vdso`] and back.
+    #[test]
+    fn vdso_conversion() {
+        let vdso = Vdso {
+            build_id: Some(vec![0x01, 0x02, 0x03, 0x04]),
+            _non_exhaustive: (),
+        };
+
+        let vdso_new = Vdso::from(blaze_user_addr_meta_vdso::from(vdso.clone()));
+        assert_eq!(vdso_new, vdso);
+
+        let vdso = Vdso {
+            build_id: None,
+            _non_exhaustive: (),
+        };
+
+        let vdso_new = Vdso::from(blaze_user_addr_meta_vdso::from(vdso.clone()));
+        assert_eq!(vdso_new, vdso);
+    }
 }
@@ -84,6 +84,9 @@ impl From<&blaze_symbolize_src_kernel> for Kernel {
         Self {
             kallsyms: (!kallsyms.is_null()).then(|| unsafe { from_cstr(*kallsyms) }),
             kernel_image: (!kernel_image.is_null()).then(|| unsafe { from_cstr(*kernel_image) }),
+            modules_dir: None,
+            bpf_prog_info: false,
+            kaslr_offset: None,
             _non_exhaustive: (),
         }
     }
@@ -109,6 +112,9 @@ impl From<&blaze_symbolize_src_process> for Process {
         let blaze_symbolize_src_process { pid } = process;
         Self {
             pid: (*pid).into(),
+            perf_map: None,
+            jitdump: None,
+            jitdump_time: None,
             _non_exhaustive: (),
         }
     }
@@ -0,0 +1,321 @@
+//! Support for reconstructing the memory map of a process from an ELF
+//! core file.
+//!
+//! The kernel records the files mapped into a process in the core
+//! file's `NT_FILE` note. Along with the permissions of the loadable
+//! segments, this information allows us to create the equivalent of
+//! the process' proc maps entries at the time of the dump.
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::elf::types::PF_R;
+use crate::elf::types::PF_W;
+use crate::elf::types::PF_X;
+use crate::elf::types::PT_LOAD;
+use crate::elf::types::PT_NOTE;
+use crate::elf::ElfParser;
+use crate::maps::EntryPath;
+use crate::maps::MapsEntry;
+use crate::maps::PathName;
+use crate::mmap::Mmap;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The type of the note describing the files mapped into the process.
+const NT_FILE: u32 = 0x46494c45;
+
+
+/// Round `size` up to the alignment of ELF note entries.
+#[inline]
+fn note_align(size: usize) -> Option<usize> {
+    Some(size.checked_add(3)? & !3)
+}
+
+
+/// A reader for the raw data of a core file, taking into account its
+/// word size and byte order.
+struct Reader {
+    is_32bit: bool,
+    swap: bool,
+}
+
+impl Reader {
+    fn read_u32(&self, data: &mut &[u8]) -> Option<u32> {
+        let value = data.read_u32()?;
+        Some(if self.swap { value.swap_bytes() } else { value })
+    }
+
+    fn read_word(&self, data: &mut &[u8]) -> Option<u64> {
+        if self.is_32bit {
+            self.read_u32(data).map(u64::from)
+        } else {
+            let value = data.read_u64()?;
+            Some(if self.swap { value.swap_bytes() } else { value })
+        }
+    }
+
+    /// Find the description of the `NT_FILE` note in a sequence of raw
+    /// ELF notes.
+    fn find_file_note<'dat>(&self, mut notes: &'dat [u8]) -> Option<&'dat [u8]> {
+        while !notes.is_empty() {
+            let namesz = self.read_u32(&mut notes)? as usize;
+            let descsz = self.read_u32(&mut notes)? as usize;
+            let type_ = self.read_u32(&mut notes)?;
+            let name = notes.read_slice(note_align(namesz)?)?;
+            let desc = notes.read_slice(note_align(descsz)?)?;
+
+            if type_ == NT_FILE && name.get(..namesz) == Some(b"CORE\0") {
+                return Some(&desc[..descsz])
+            }
+        }
+        None
+    }
+
+    /// Parse the description of an `NT_FILE` note into a list of
+    /// `(range, file offset, path)` tuples.
+    fn parse_file_note(&self, mut desc: &[u8]) -> Option<Vec<(u64, u64, u64, PathBuf)>> {
+        let count = self.read_word(&mut desc)? as usize;
+        let page_size = self.read_word(&mut desc)?;
+
+        let mut ranges = Vec::with_capacity(count.min(desc.len()));
+        for _ in 0..count {
+            let start = self.read_word(&mut desc)?;
+            let end = self.read_word(&mut desc)?;
+            let page_offset = self.read_word(&mut desc)?;
+            let () = ranges.push((start, end, page_offset.checked_mul(page_size)?));
+        }
+
+        ranges
+            .into_iter()
+            .map(|(start, end, offset)| {
+                let path = desc.read_cstr()?;
+                let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+                Some((start, end, offset, path.to_path_buf()))
+            })
+            .collect()
+    }
+}
+
+
+/// Reconstruct the proc maps entries of file backed memory of the
+/// process captured in the core file at `path`.
+///
+/// Paths to mapped files are interpreted relative to `sysroot`, if
+/// provided. Entries are reported sorted by start address.
+pub(crate) fn parse_core_maps(path: &Path, sysroot: Option<&Path>) -> Result<Vec<MapsEntry>> {
+    let mmap = Mmap::builder()
+        .open(path)
+        .with_context(|| format!("failed to open core file {}", path.display()))?;
+    let parser = ElfParser::from_mmap(mmap.clone());
+    if !parser.is_core()? {
+        return Err(Error::with_invalid_data(format!(
+            "{} is not a core file",
+            path.display()
+        )))
+    }
+
+    let reader = Reader {
+        is_32bit: parser.is_32bit()?,
+        swap: parser.is_swapped()?,
+    };
+    let phdrs = parser.program_headers()?;
+
+    let desc = phdrs
+        .iter()
+        .filter(|phdr| phdr.p_type == PT_NOTE)
+        .find_map(|phdr| {
+            let start = usize::try_from(phdr.p_offset).ok()?;
+            let end = start.checked_add(usize::try_from(phdr.p_filesz).ok()?)?;
+            reader.find_file_note(mmap.get(start..end)?)
+        })
+        .ok_or_invalid_data(|| format!("core file {} has no NT_FILE note", path.display()))?;
+
+    let files = reader.parse_file_note(desc).ok_or_invalid_data(|| {
+        format!(
+            "failed to parse NT_FILE note of core file {}",
+            path.display()
+        )
+    })?;
+
+    let mut entries = files
+        .into_iter()
+        .map(|(start, end, offset, file)| {
+            // Mapped files have a corresponding loadable segment, from
+            // which we can learn the mapping's permissions. If we can't
+            // find it, assume the mapping to be executable, so that
+            // addresses inside it are not dismissed.
+            let flags = phdrs
+                .iter()
+                .find(|phdr| phdr.p_type == PT_LOAD && phdr.p_vaddr == start)
+                .map(|phdr| phdr.p_flags)
+                .unwrap_or(PF_R | PF_X);
+            let mode = [(PF_R, 0b1000), (PF_W, 0b0100), (PF_X, 0b0010)]
+                .iter()
+                .filter(|(flag, _bit)| flags & flag != 0)
+                .fold(0b0001, |mode, (_flag, bit)| mode | bit);
+
+            let symbolic_path = file
+                .to_str()
+                .and_then(|file| file.strip_suffix(" (deleted)"))
+                .map(PathBuf::from)
+                .unwrap_or(file);
+            let maps_file = match sysroot {
+                Some(sysroot) => {
+                    sysroot.join(symbolic_path.strip_prefix("/").unwrap_or(&symbolic_path))
+                }
+                None => symbolic_path.clone(),
+            };
+
+            MapsEntry {
+                range: start as Addr..end as Addr,
+                mode,
+                offset,
+                path_name: Some(PathName::Path(EntryPath::new(
+                    maps_file,
+                    symbolic_path,
+                    None,
+                ))),
+            }
+        })
+        .collect::<Vec<_>>();
+    let () = entries.sort_by_key(|entry| entry.range.start);
+    Ok(entries)
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use std::fs::write;
+
+    use tempfile::tempdir;
+    use test_log::test;
+
+    use crate::elf::types::ET_CORE;
+
+
+    /// Create a minimal 64 bit core file describing the provided
+    /// `(start, end, file offset, path, segment flags)` mappings.
+    pub(crate) fn make_core_file(mappings: &[(u64, u64, u64, &str, u32)]) -> Vec<u8> {
+        const EHDR_SIZE: u16 = 64;
+        const PHDR_SIZE: u16 = 56;
+        const PAGE_SIZE: u64 = 4096;
+
+        let mut desc = Vec::new();
+        let () = desc.extend_from_slice(&(mappings.len() as u64).to_ne_bytes());
+        let () = desc.extend_from_slice(&PAGE_SIZE.to_ne_bytes());
+        for (start, end, offset, _path, _flags) in mappings {
+            let () = desc.extend_from_slice(&start.to_ne_bytes());
+            let () = desc.extend_from_slice(&end.to_ne_bytes());
+            let () = desc.extend_from_slice(&(offset / PAGE_SIZE).to_ne_bytes());
+        }
+        for (_start, _end, _offset, path, _flags) in mappings {
+            let () = desc.extend_from_slice(path.as_bytes());
+            let () = desc.push(0);
+        }
+        let () = desc.resize(note_align(desc.len()).unwrap(), 0);
+
+        let mut note = Vec::new();
+        let () = note.extend_from_slice(&5u32.to_ne_bytes());
+        let () = note.extend_from_slice(&(desc.len() as u32).to_ne_bytes());
+        let () = note.extend_from_slice(&NT_FILE.to_ne_bytes());
+        let () = note.extend_from_slice(b"CORE\0\0\0\0");
+        let () = note.extend_from_slice(&desc);
+
+        let phnum = mappings.len() as u16 + 1;
+        let note_offset = u64::from(EHDR_SIZE) + u64::from(phnum) * u64::from(PHDR_SIZE);
+
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(b"\x7fELF");
+        // 64 bit, native byte order, version 1.
+        let () = data.push(2);
+        let () = data.push(if cfg!(target_endian = "little") { 1 } else { 2 });
+        let () = data.push(1);
+        let () = data.resize(16, 0);
+        let () = data.extend_from_slice(&ET_CORE.to_ne_bytes());
+        // e_machine
+        let () = data.extend_from_slice(&62u16.to_ne_bytes());
+        // e_version
+        let () = data.extend_from_slice(&1u32.to_ne_bytes());
+        // e_entry
+        let () = data.extend_from_slice(&0u64.to_ne_bytes());
+        // e_phoff
+        let () = data.extend_from_slice(&u64::from(EHDR_SIZE).to_ne_bytes());
+        // e_shoff
+        let () = data.extend_from_slice(&0u64.to_ne_bytes());
+        // e_flags
+        let () = data.extend_from_slice(&0u32.to_ne_bytes());
+        let () = data.extend_from_slice(&EHDR_SIZE.to_ne_bytes());
+        let () = data.extend_from_slice(&PHDR_SIZE.to_ne_bytes());
+        let () = data.extend_from_slice(&phnum.to_ne_bytes());
+        // e_shentsize, e_shnum, e_shstrndx
+        let () = data.extend_from_slice(&[0; 6]);
+
+        let mut add_phdr =
+            |type_: u32, flags: u32, offset: u64, vaddr: u64, filesz: u64, memsz: u64| {
+                let () = data.extend_from_slice(&type_.to_ne_bytes());
+                let () = data.extend_from_slice(&flags.to_ne_bytes());
+                let () = data.extend_from_slice(&offset.to_ne_bytes());
+                let () = data.extend_from_slice(&vaddr.to_ne_bytes());
+                let () = data.extend_from_slice(&vaddr.to_ne_bytes());
+                let () = data.extend_from_slice(&filesz.to_ne_bytes());
+                let () = data.extend_from_slice(&memsz.to_ne_bytes());
+                let () = data.extend_from_slice(&PAGE_SIZE.to_ne_bytes());
+            };
+
+        let () = add_phdr(PT_NOTE, 0, note_offset, 0, note.len() as u64, 0);
+        for (start, end, _offset, _path, flags) in mappings {
+            let () = add_phdr(PT_LOAD, *flags, 0, *start, 0, end - start);
+        }
+        let () = data.extend_from_slice(&note);
+        data
+    }
+
+
+    /// Check that we can reconstruct proc maps entries from a core
+    /// file.
+    #[test]
+    fn core_maps_parsing() {
+        let core = make_core_file(&[
+            (0x7000, 0x8000, 0x2000, "/usr/lib/libfoo.so", PF_R),
+            (0x1000, 0x3000, 0x0, "/usr/bin/app (deleted)", PF_R | PF_X),
+        ]);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("core");
+        let () = write(&path, &core).unwrap();
+
+        let entries = parse_core_maps(&path, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].range, 0x1000..0x3000);
+        assert_eq!(entries[0].mode, 0b1011);
+        assert_eq!(entries[0].offset, 0);
+        let path_name = entries[0].path_name.as_ref().unwrap().as_path().unwrap();
+        assert_eq!(path_name.symbolic_path, Path::new("/usr/bin/app"));
+        assert_eq!(path_name.maps_file, Path::new("/usr/bin/app"));
+
+        assert_eq!(entries[1].range, 0x7000..0x8000);
+        assert_eq!(entries[1].mode, 0b1001);
+        assert_eq!(entries[1].offset, 0x2000);
+
+        let entries = parse_core_maps(&path, Some(Path::new("/sysroot"))).unwrap();
+        let path_name = entries[1].path_name.as_ref().unwrap().as_path().unwrap();
+        assert_eq!(path_name.symbolic_path, Path::new("/usr/lib/libfoo.so"));
+        assert_eq!(path_name.maps_file, Path::new("/sysroot/usr/lib/libfoo.so"));
+
+        // A regular ELF file is not a core file.
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let err = parse_core_maps(&elf, None).unwrap_err();
+        assert!(err.to_string().contains("is not a core file"), "{err}");
+    }
+}
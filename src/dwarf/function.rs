@@ -32,6 +32,7 @@ use std::fmt::Result as FmtResult;
 
 use gimli::Error;
 
+use super::lazy::LazyCell;
 use super::range::RangeAttributes;
 use super::reader::R;
 
@@ -131,6 +132,23 @@ pub(crate) struct Function<'dwarf> {
     pub(crate) name: Option<R<'dwarf>>,
     /// The function's range (begin and end address).
     pub(crate) range: Option<gimli::Range>,
+    /// The lazily parsed functions inlined into this one.
+    pub(crate) inlined: LazyCell<Result<InlinedFunctions<'dwarf>, Error>>,
+}
+
+impl<'dwarf> Function<'dwarf> {
+    /// Parse (or retrieve the already parsed) inlined functions contained in
+    /// this function.
+    pub(crate) fn parse_inlined_functions(
+        &self,
+        unit: &gimli::Unit<R<'dwarf>>,
+        sections: &gimli::Dwarf<R<'dwarf>>,
+    ) -> Result<&InlinedFunctions<'dwarf>, Error> {
+        self.inlined
+            .borrow_with(|| InlinedFunctions::parse(self.dw_die_offset, unit, sections))
+            .as_ref()
+            .map_err(Error::clone)
+    }
 }
 
 impl Debug for Function<'_> {
@@ -139,6 +157,7 @@ impl Debug for Function<'_> {
             dw_die_offset,
             name,
             range,
+            inlined: _,
         } = self;
 
         f.debug_struct(stringify!(Function))
@@ -242,6 +261,7 @@ impl<'dwarf> Functions<'dwarf> {
                             dw_die_offset,
                             name,
                             range: ranges.bounds(),
+                            inlined: LazyCell::new(),
                         };
                         functions.push(function);
                     }
@@ -283,6 +303,294 @@ impl<'dwarf> Functions<'dwarf> {
 }
 
 
+/// A single address range of an inlined function.
+#[derive(Debug)]
+pub(crate) struct InlinedFunctionAddress {
+    range: gimli::Range,
+    /// The nesting depth of the inlined function, with zero
+    /// representing a function inlined directly into the subprogram.
+    call_depth: usize,
+    /// An index into `InlinedFunctions::functions`.
+    function: usize,
+}
+
+
+/// A function that got inlined into a `DW_TAG_subprogram`, as
+/// described by a `DW_TAG_inlined_subroutine` entry.
+pub(crate) struct InlinedFunction<'dwarf> {
+    pub(crate) dw_die_offset: gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>,
+    /// The function's name, if present.
+    pub(crate) name: Option<R<'dwarf>>,
+    /// The index of the file (in the unit's line program) containing
+    /// the call site.
+    pub(crate) call_file: Option<u64>,
+    /// The line of the call site, with zero meaning "unknown".
+    pub(crate) call_line: u32,
+    /// The column of the call site, with zero meaning "unknown".
+    pub(crate) call_column: u32,
+}
+
+impl Debug for InlinedFunction<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            dw_die_offset,
+            name,
+            call_file,
+            call_line,
+            call_column,
+        } = self;
+
+        f.debug_struct(stringify!(InlinedFunction))
+            .field("dw_die_offset", dw_die_offset)
+            .field(
+                "name",
+                match name.as_ref().and_then(|r| r.to_string().ok()) {
+                    Some(ref s) => s,
+                    None => &name,
+                },
+            )
+            .field("call_file", call_file)
+            .field("call_line", call_line)
+            .field("call_column", call_column)
+            .finish()
+    }
+}
+
+
+/// The inlined functions of a single `DW_TAG_subprogram`.
+#[derive(Debug)]
+pub(crate) struct InlinedFunctions<'dwarf> {
+    /// List of all `DW_TAG_inlined_subroutine` details in the function.
+    pub(crate) functions: Box<[InlinedFunction<'dwarf>]>,
+    /// List of `DW_TAG_inlined_subroutine` address ranges in the function.
+    pub(crate) addresses: Box<[InlinedFunctionAddress]>,
+}
+
+impl<'dwarf> InlinedFunctions<'dwarf> {
+    fn parse(
+        dw_die_offset: gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>,
+        unit: &gimli::Unit<R<'dwarf>>,
+        sections: &gimli::Dwarf<R<'dwarf>>,
+    ) -> Result<Self, Error> {
+        let mut entries = unit.entries_raw(Some(dw_die_offset))?;
+        let depth = entries.next_depth();
+        let abbrev = entries
+            .read_abbreviation()?
+            .ok_or(Error::NoEntryAtGivenOffset)?;
+        debug_assert_eq!(abbrev.tag(), gimli::DW_TAG_subprogram);
+        let () = entries.skip_attributes(abbrev.attributes())?;
+
+        let mut functions = Vec::new();
+        let mut addresses = Vec::new();
+        let () = Self::parse_children(
+            &mut entries,
+            depth,
+            unit,
+            sections,
+            &mut functions,
+            &mut addresses,
+            0,
+        )?;
+
+        // Sort ranges in "breadth-first traversal order", i.e., first by
+        // call depth and then by start address. That allows us to find the
+        // range containing an address at a certain depth using binary
+        // search. Note that ordering by start address first would not
+        // work, because a lookup at a given depth could not tell whether an
+        // encountered deeper range lies before or after the target.
+        addresses.sort_by(|addr1, addr2| {
+            addr1
+                .call_depth
+                .cmp(&addr2.call_depth)
+                .then_with(|| addr1.range.begin.cmp(&addr2.range.begin))
+        });
+
+        Ok(Self {
+            functions: functions.into_boxed_slice(),
+            addresses: addresses.into_boxed_slice(),
+        })
+    }
+
+    fn parse_children(
+        entries: &mut gimli::EntriesRaw<'_, '_, R<'dwarf>>,
+        depth: isize,
+        unit: &gimli::Unit<R<'dwarf>>,
+        sections: &gimli::Dwarf<R<'dwarf>>,
+        functions: &mut Vec<InlinedFunction<'dwarf>>,
+        addresses: &mut Vec<InlinedFunctionAddress>,
+        call_depth: usize,
+    ) -> Result<(), Error> {
+        loop {
+            let dw_die_offset = entries.next_offset();
+            let next_depth = entries.next_depth();
+            if next_depth <= depth {
+                return Ok(())
+            }
+
+            if let Some(abbrev) = entries.read_abbreviation()? {
+                match abbrev.tag() {
+                    gimli::DW_TAG_subprogram => {
+                        // Nested functions are not inlined into us and
+                        // are covered as functions in their own right.
+                        let () = entries.skip_attributes(abbrev.attributes())?;
+                        while entries.next_depth() > next_depth {
+                            if let Some(abbrev) = entries.read_abbreviation()? {
+                                let () = entries.skip_attributes(abbrev.attributes())?;
+                            }
+                        }
+                    }
+                    gimli::DW_TAG_inlined_subroutine => {
+                        let () = Self::parse_inlined(
+                            dw_die_offset,
+                            entries,
+                            abbrev,
+                            next_depth,
+                            unit,
+                            sections,
+                            functions,
+                            addresses,
+                            call_depth,
+                        )?;
+                    }
+                    _ => {
+                        let () = entries.skip_attributes(abbrev.attributes())?;
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn parse_inlined(
+        dw_die_offset: gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>,
+        entries: &mut gimli::EntriesRaw<'_, '_, R<'dwarf>>,
+        abbrev: &gimli::Abbreviation,
+        depth: isize,
+        unit: &gimli::Unit<R<'dwarf>>,
+        sections: &gimli::Dwarf<R<'dwarf>>,
+        functions: &mut Vec<InlinedFunction<'dwarf>>,
+        addresses: &mut Vec<InlinedFunctionAddress>,
+        call_depth: usize,
+    ) -> Result<(), Error> {
+        let mut ranges = RangeAttributes::default();
+        let mut name = None;
+        let mut call_file = None;
+        let mut call_line = 0;
+        let mut call_column = 0;
+        for spec in abbrev.attributes() {
+            let attr = entries.read_attribute(*spec)?;
+            match attr.name() {
+                gimli::DW_AT_low_pc => match attr.value() {
+                    gimli::AttributeValue::Addr(val) => ranges.low_pc = Some(val),
+                    gimli::AttributeValue::DebugAddrIndex(index) => {
+                        ranges.low_pc = Some(sections.address(unit, index)?);
+                    }
+                    _ => {}
+                },
+                gimli::DW_AT_high_pc => match attr.value() {
+                    gimli::AttributeValue::Addr(val) => ranges.high_pc = Some(val),
+                    gimli::AttributeValue::DebugAddrIndex(index) => {
+                        ranges.high_pc = Some(sections.address(unit, index)?);
+                    }
+                    gimli::AttributeValue::Udata(val) => ranges.size = Some(val),
+                    _ => {}
+                },
+                gimli::DW_AT_ranges => {
+                    ranges.ranges_offset = sections.attr_ranges_offset(unit, attr.value())?;
+                }
+                gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
+                    if let Ok(val) = sections.attr_string(unit, attr.value()) {
+                        name = Some(val);
+                    }
+                }
+                gimli::DW_AT_name if name.is_none() => {
+                    name = sections.attr_string(unit, attr.value()).ok();
+                }
+                gimli::DW_AT_abstract_origin | gimli::DW_AT_specification if name.is_none() => {
+                    name = name_attr(attr.value(), unit, sections, 16)?;
+                }
+                gimli::DW_AT_call_file => {
+                    // Prior to DWARF 5 a file index of zero means "no
+                    // file", but starting with version 5 it is a valid
+                    // index into the file table.
+                    if let gimli::AttributeValue::FileIndex(idx) = attr.value() {
+                        if idx > 0 || unit.header.version() >= 5 {
+                            call_file = Some(idx);
+                        }
+                    }
+                }
+                gimli::DW_AT_call_line => {
+                    call_line = attr.udata_value().unwrap_or(0) as u32;
+                }
+                gimli::DW_AT_call_column => {
+                    call_column = attr.udata_value().unwrap_or(0) as u32;
+                }
+                _ => {}
+            }
+        }
+
+        let function = functions.len();
+        let () = functions.push(InlinedFunction {
+            dw_die_offset,
+            name,
+            call_file,
+            call_line,
+            call_column,
+        });
+
+        let _added = ranges.for_each_range(sections, unit, |range| {
+            addresses.push(InlinedFunctionAddress {
+                range,
+                call_depth,
+                function,
+            });
+        })?;
+
+        Self::parse_children(
+            entries,
+            depth,
+            unit,
+            sections,
+            functions,
+            addresses,
+            call_depth + 1,
+        )
+    }
+
+    /// Find the chain of inlined functions covering `probe`.
+    ///
+    /// Functions are reported from the outermost to the innermost one.
+    pub(crate) fn find_inlined_functions(&self, probe: u64) -> Vec<&InlinedFunction<'dwarf>> {
+        let mut functions = Vec::new();
+        let mut addresses = &self.addresses[..];
+        loop {
+            let call_depth = functions.len();
+            let result = addresses.binary_search_by(|address| {
+                if address.call_depth > call_depth {
+                    Ordering::Greater
+                } else if address.call_depth < call_depth {
+                    Ordering::Less
+                } else if address.range.begin > probe {
+                    Ordering::Greater
+                } else if address.range.end <= probe {
+                    Ordering::Less
+                } else {
+                    Ordering::Equal
+                }
+            });
+
+            if let Ok(idx) = result {
+                let function = addresses[idx].function;
+                let () = functions.push(&self.functions[function]);
+                addresses = &addresses[idx + 1..];
+            } else {
+                break functions
+            }
+        }
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,9 +614,19 @@ mod tests {
             dw_die_offset: gimli::UnitOffset(24),
             name: None,
             range: None,
+            inlined: LazyCell::new(),
         };
         assert_ne!(format!("{func:?}"), "");
 
+        let inlined = InlinedFunction {
+            dw_die_offset: gimli::UnitOffset(42),
+            name: None,
+            call_file: Some(1),
+            call_line: 3,
+            call_column: 0,
+        };
+        assert_ne!(format!("{inlined:?}"), "");
+
         let funcs = Functions {
             functions: Box::default(),
             addresses: Box::default(),
@@ -25,30 +25,115 @@
 // > IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // > DEALINGS IN THE SOFTWARE.
 
-use core::cell::UnsafeCell;
+use std::cell::UnsafeCell;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
+
+/// A cell that is initialized lazily, on first access, and that can be
+/// shared between threads.
 pub struct LazyCell<T> {
+    /// Whether `contents` has been initialized.
+    initialized: AtomicBool,
+    /// Serializes the initialization of `contents`.
+    lock: Mutex<()>,
     contents: UnsafeCell<Option<T>>,
 }
+
+// SAFETY: `contents` is only ever written to once, while holding `lock`
+//         and before `initialized` is set. Only shared references are
+//         handed out and only after `initialized` got set.
+unsafe impl<T> Sync for LazyCell<T> where T: Send + Sync {}
+
 impl<T> LazyCell<T> {
     pub fn new() -> LazyCell<T> {
         LazyCell {
+            initialized: AtomicBool::new(false),
+            lock: Mutex::new(()),
             contents: UnsafeCell::new(None),
         }
     }
 
+    pub fn borrow(&self) -> Option<&T> {
+        if self.initialized.load(Ordering::Acquire) {
+            // SAFETY: Initialization has completed and `contents` will
+            //         not be modified anymore.
+            unsafe { &*self.contents.get() }.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the cell's value, initializing it using `closure` if
+    /// that has not happened yet.
+    ///
+    /// The cell's lock is held while `closure` runs, so that threads
+    /// racing for initialization wait for the value instead of computing
+    /// it as well. As a consequence, `closure` must not access the cell
+    /// itself, as doing so would deadlock.
     pub fn borrow_with(&self, closure: impl FnOnce() -> T) -> &T {
         // First check if we're already initialized...
-        let ptr = self.contents.get();
-        if let Some(val) = unsafe { &*ptr } {
+        if let Some(val) = self.borrow() {
             return val
         }
-        // Note that while we're executing `closure` our `borrow_with` may
-        // be called recursively. This means we need to check again after
-        // the closure has executed. For that we use the `get_or_insert`
-        // method which will only perform mutation if we aren't already
-        // `Some`.
-        let val = closure();
-        unsafe { (*ptr).get_or_insert(val) }
+
+        {
+            // `contents` is only written after `closure` returned, so
+            // a panic in an earlier invocation did not leave anything
+            // in an inconsistent state and we can ignore poisoning.
+            let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+            // Another thread may have initialized the cell while we were
+            // waiting for the lock.
+            if !self.initialized.load(Ordering::Acquire) {
+                let val = closure();
+                // SAFETY: We hold the lock and `contents` has not been
+                //         initialized, so nobody else is accessing it.
+                unsafe { *self.contents.get() = Some(val) };
+                let () = self.initialized.store(true, Ordering::Release);
+            }
+        }
+        // SANITY: `contents` is guaranteed to be initialized at this
+        //         point.
+        self.borrow().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Barrier;
+    use std::thread;
+
+    use test_log::test;
+
+
+    /// Check that a cell accessed concurrently is initialized only once.
+    #[test]
+    fn concurrent_initialization() {
+        const THREADS: usize = 8;
+
+        let cell = LazyCell::new();
+        let count = AtomicUsize::new(0);
+        let barrier = Barrier::new(THREADS);
+
+        let () = thread::scope(|s| {
+            for _ in 0..THREADS {
+                let _handle = s.spawn(|| {
+                    let _result = barrier.wait();
+                    let val = cell.borrow_with(|| {
+                        let _prev = count.fetch_add(1, Ordering::Relaxed);
+                        42
+                    });
+                    assert_eq!(*val, 42);
+                });
+            }
+        });
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        assert_eq!(cell.borrow(), Some(&42));
     }
 }
@@ -5,6 +5,7 @@ mod location;
 mod range;
 mod reader;
 mod resolver;
+mod split;
 mod unit;
 mod units;
 
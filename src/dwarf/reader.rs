@@ -1,31 +1,54 @@
 use gimli::EndianSlice;
+use gimli::RunTimeEndian;
 use gimli::SectionId;
 
 use crate::elf::ElfParser;
 use crate::Result;
 
 
-#[cfg(target_endian = "little")]
-type Endianess = gimli::LittleEndian;
-#[cfg(target_endian = "big")]
-type Endianess = gimli::BigEndian;
-
 /// The gimli reader type we currently use. Could be made generic if
 /// need be, but we keep things simple while we can.
-pub(crate) type R<'dat> = EndianSlice<'dat, Endianess>;
+///
+/// The byte order is determined at run time, based on that of the ELF
+/// file the data stems from.
+pub(crate) type R<'dat> = EndianSlice<'dat, RunTimeEndian>;
+
+
+/// Create a reader for the provided section data, using the byte order
+/// of the ELF file represented by `parser`.
+pub(super) fn make_reader<'dat>(parser: &ElfParser, data: &'dat [u8]) -> Result<R<'dat>> {
+    let endian = if parser.is_big_endian()? {
+        RunTimeEndian::Big
+    } else {
+        RunTimeEndian::Little
+    };
+    let reader = EndianSlice::new(data, endian);
+    Ok(reader)
+}
 
 
 pub(super) fn load_section(parser: &ElfParser, id: SectionId) -> Result<R<'_>> {
-    let result = parser.find_section(id.name())?;
+    let result = parser.find_debug_section(id.name())?;
     let data = match result {
-        Some(idx) => parser.section_data(idx)?,
+        Some(idx) => parser.section_data_decompressed(idx)?,
         // Make sure to return empty data if a section does not exist.
         None => &[],
     };
+    make_reader(parser, data)
+}
+
+/// Load a section from a split DWARF object (`.dwo`) or package
+/// (`.dwp`) file.
+pub(super) fn load_dwo_section(parser: &ElfParser, id: SectionId) -> Result<R<'_>> {
+    let result = match id.dwo_name() {
+        Some(name) => parser.find_section(name)?,
+        None => None,
+    };
 
-    #[cfg(target_endian = "little")]
-    let reader = EndianSlice::new(data, gimli::LittleEndian);
-    #[cfg(target_endian = "big")]
-    let reader = EndianSlice::new(data, gimli::BigEndian);
-    Ok(reader)
+    let data = match result {
+        Some(idx) => parser.section_data_decompressed(idx)?,
+        // Make sure to return empty data if a section does not exist.
+        None => &[],
+    };
+    make_reader(parser, data)
 }
@@ -6,7 +6,7 @@ use std::fmt::Result as FmtResult;
 use std::mem;
 use std::ops::Deref as _;
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use gimli::Dwarf;
 
@@ -17,12 +17,14 @@ use crate::inspect::SymType;
 use crate::symbolize::AddrLineInfo;
 use crate::Addr;
 use crate::Error;
+use crate::IntInlinedFn;
 use crate::IntSym;
 use crate::Result;
 use crate::SrcLang;
 
 use super::location::Location;
 use super::reader;
+use super::split::DwarfPackage;
 use super::units::Units;
 
 
@@ -47,10 +49,14 @@ pub(crate) struct DwarfResolver {
     /// The lazily parsed compilation units of the DWARF file.
     /// SAFETY: We must not hand out references with a 'static lifetime to
     ///         this member. Rather, they should never outlive `self`.
-    ///         Furthermore, this member has to be listed before `parser`
-    ///         to make sure we never end up with a dangling reference.
+    ///         Furthermore, this member has to be listed before
+    ///         `_dwarf_parser` to make sure we never end up with a
+    ///         dangling reference.
     units: Units<'static>,
-    parser: Rc<ElfParser>,
+    /// The parser for the file providing the DWARF data. This may be a
+    /// separate debug file or just `parser` itself.
+    _dwarf_parser: Arc<ElfParser>,
+    parser: Arc<ElfParser>,
     line_number_info: bool,
     enable_debug_info_syms: bool,
 }
@@ -60,21 +66,35 @@ impl DwarfResolver {
         &self.parser
     }
 
+    /// Create a `DwarfResolver` for the DWARF data provided by `parser`.
+    ///
+    /// If `debug_parser` is provided, it represents a separate file
+    /// containing the DWARF data for `parser` and it is used in its
+    /// stead for reading said data. `parser` continues to be used for
+    /// everything else (ELF symbols, program headers, ...).
+    ///
+    /// `path` is the path to the binary, which is used for finding an
+    /// accompanying DWARF package (`.dwp`) containing split units.
     pub fn from_parser(
-        parser: Rc<ElfParser>,
+        parser: Arc<ElfParser>,
+        debug_parser: Option<Arc<ElfParser>>,
+        path: &Path,
         line_number_info: bool,
         debug_info_symbols: bool,
     ) -> Result<Self, Error> {
+        let dwarf_parser = debug_parser.unwrap_or_else(|| Arc::clone(&parser));
         // SAFETY: We own the `ElfParser` and make sure that it stays
         //         around while the `Units` object uses it. As such, it
         //         is fine to conjure a 'static lifetime here.
         let static_parser =
-            unsafe { mem::transmute::<&ElfParser, &'static ElfParser>(parser.deref()) };
+            unsafe { mem::transmute::<&ElfParser, &'static ElfParser>(dwarf_parser.deref()) };
         let mut load_section = |section| reader::load_section(static_parser, section);
         let dwarf = Dwarf::load(&mut load_section)?;
-        let units = Units::parse(dwarf)?;
+        let dwp = DwarfPackage::open_for(path);
+        let units = Units::parse(dwarf, dwp)?;
         let slf = Self {
             units,
+            _dwarf_parser: dwarf_parser,
             parser,
             line_number_info,
             enable_debug_info_syms: debug_info_symbols,
@@ -88,7 +108,13 @@ impl DwarfResolver {
     /// has .debug_line section.
     pub fn open(filename: &Path, debug_line_info: bool, debug_info_symbols: bool) -> Result<Self> {
         let parser = ElfParser::open(filename)?;
-        Self::from_parser(Rc::new(parser), debug_line_info, debug_info_symbols)
+        Self::from_parser(
+            Arc::new(parser),
+            None,
+            filename,
+            debug_line_info,
+            debug_info_symbols,
+        )
     }
 
     /// Find line information of an address.
@@ -122,6 +148,53 @@ impl DwarfResolver {
         }
     }
 
+    /// Find the functions inlined at an address.
+    ///
+    /// Functions are reported from the outermost to the innermost one,
+    /// each along with the location of its call site.
+    pub(crate) fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        if !self.line_number_info {
+            return Ok(Vec::new())
+        }
+
+        let result = self.units.find_inlined_functions(addr as u64)?;
+        if let Some((inlined, language)) = result {
+            inlined
+                .into_iter()
+                .map(|(function, location)| {
+                    let name = function
+                        .name
+                        .map(|name| name.to_string())
+                        .transpose()?
+                        .unwrap_or("");
+                    let call_site = location.map(|location| {
+                        let Location {
+                            dir,
+                            file,
+                            line,
+                            column,
+                        } = location;
+
+                        AddrLineInfo {
+                            dir,
+                            file,
+                            line,
+                            column: column.map(|col| col.try_into().unwrap_or(u16::MAX)),
+                        }
+                    });
+                    let inlined = IntInlinedFn {
+                        name,
+                        call_site,
+                        lang: language.into(),
+                    };
+                    Ok(inlined)
+                })
+                .collect()
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
     /// Lookup the symbol(s) at an address.
     pub(crate) fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>, Error> {
         // TODO: This conditional logic is weird and potentially
@@ -148,6 +221,7 @@ impl DwarfResolver {
                 name,
                 addr,
                 lang: language.into(),
+                module: None,
             };
             Ok(vec![sym])
         } else {
@@ -256,6 +330,74 @@ mod tests {
         assert!(line_info.column.is_some());
     }
 
+    /// Check that we can find the inlined functions covering an address.
+    #[test]
+    fn inlined_fn_finding() {
+        let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let resolver = DwarfResolver::open(bin_name.as_ref(), true, true).unwrap();
+
+        // `factorial_inline_test` does not contain any inlined code at
+        // its very beginning.
+        let inlined = resolver.find_inlined_fns(0x2000200).unwrap();
+        assert!(inlined.is_empty());
+
+        let inlined = resolver.find_inlined_fns(0x2000208).unwrap();
+        assert_eq!(inlined.len(), 2);
+
+        let outer = &inlined[0];
+        assert_eq!(outer.name, "factorial_inline_wrapper");
+        let call_site = outer.call_site.as_ref().unwrap();
+        assert_eq!(call_site.file, "test-stable-addresses.c");
+        assert_eq!(call_site.line, Some(53));
+
+        let inner = &inlined[1];
+        assert_eq!(inner.name, "factorial_2nd_layer_inline_wrapper");
+        let call_site = inner.call_site.as_ref().unwrap();
+        assert_eq!(call_site.file, "test-stable-addresses.c");
+        assert_eq!(call_site.line, Some(48));
+    }
+
+    /// Check that we can resolve addresses using split DWARF, both with
+    /// `.dwo` files and with a `.dwp` package.
+    #[test]
+    fn split_dwarf_lookup() {
+        for bin in [
+            "test-stable-addresses-split.bin",
+            "test-stable-addresses-dwp.bin",
+        ] {
+            let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+                .join("data")
+                .join(bin);
+            let resolver = DwarfResolver::open(bin_name.as_ref(), true, true).unwrap();
+
+            let syms = resolver.find_syms(0x2000100).unwrap();
+            assert_eq!(syms.len(), 1, "{bin}");
+            let sym = &syms[0];
+            assert_eq!(sym.name, "factorial");
+            assert_eq!(sym.addr, 0x2000100);
+
+            let line_info = resolver.find_line_info(0x2000100).unwrap().unwrap();
+            assert_eq!(line_info.file, "test-stable-addresses.c");
+            assert_eq!(line_info.line, Some(8));
+
+            let inlined = resolver.find_inlined_fns(0x2000208).unwrap();
+            assert_eq!(inlined.len(), 2, "{bin}");
+            assert_eq!(inlined[0].name, "factorial_inline_wrapper");
+            assert_eq!(inlined[1].name, "factorial_2nd_layer_inline_wrapper");
+
+            let opts = FindAddrOpts {
+                offset_in_file: false,
+                obj_file_name: false,
+                sym_type: SymType::Function,
+            };
+            let symbols = resolver.find_addr("factorial", &opts).unwrap();
+            assert_eq!(symbols.len(), 1);
+            assert_eq!(symbols[0].addr, 0x2000100);
+        }
+    }
+
     /// Check that we can look up a symbol in DWARF debug information.
     #[test]
     fn lookup_symbol() {
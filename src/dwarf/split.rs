@@ -0,0 +1,219 @@
+//! Support for split DWARF, i.e., debug information that the compiler
+//! moved out of the binary and into `.dwo` files (one per compilation
+//! unit) or a `.dwp` package combining them.
+//!
+//! In such a setup, the binary itself only contains skeleton units,
+//! which describe address ranges and line information, but refer to
+//! the split unit for everything else.
+
+use std::ffi::OsStr;
+use std::mem;
+use std::ops::Deref as _;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use gimli::Dwarf;
+
+use crate::elf::ElfParser;
+use crate::log::debug;
+use crate::log::warn;
+use crate::Result;
+
+use super::reader;
+use super::reader::R;
+
+
+/// Conjure up a reference to `parser` with an arbitrary lifetime.
+///
+/// # Safety
+/// The caller has to ensure that the `ElfParser` is kept alive (and
+/// not moved out of its `Box`) while the returned reference, or any
+/// data derived from it, is in use.
+unsafe fn extend_lifetime<'dwarf>(parser: &ElfParser) -> &'dwarf ElfParser {
+    unsafe { mem::transmute::<&ElfParser, &'dwarf ElfParser>(parser) }
+}
+
+
+/// A DWARF package (`.dwp`) file.
+pub(super) struct DwarfPackage<'dwarf> {
+    /// The parsed package.
+    /// SAFETY: This member has to be listed before `_parser` to make
+    ///         sure we never end up with a dangling reference.
+    package: gimli::DwarfPackage<R<'dwarf>>,
+    /// The parser backing `package`.
+    _parser: Box<ElfParser>,
+}
+
+impl<'dwarf> DwarfPackage<'dwarf> {
+    /// Open the DWARF package at `path`.
+    pub(super) fn open(path: &Path) -> Result<Self> {
+        let parser = Box::new(ElfParser::open(path)?);
+        // SAFETY: We keep the `ElfParser` around for as long as the
+        //         package referencing its data.
+        let static_parser = unsafe { extend_lifetime(parser.deref()) };
+        let mut load_section = |section| reader::load_dwo_section(static_parser, section);
+        let empty = reader::make_reader(static_parser, &[])?;
+        let package = gimli::DwarfPackage::load(&mut load_section, empty)?;
+        let slf = Self {
+            package,
+            _parser: parser,
+        };
+        Ok(slf)
+    }
+
+    /// Open the DWARF package for the binary at `path`, if one exists.
+    ///
+    /// By convention, the package is located next to the binary and
+    /// named after it, with an additional `.dwp` extension.
+    pub(super) fn open_for(path: &Path) -> Option<Self> {
+        let mut dwp = path.as_os_str().to_os_string();
+        let () = dwp.push(".dwp");
+        let dwp = PathBuf::from(dwp);
+        if !dwp.exists() {
+            return None
+        }
+
+        match Self::open(&dwp) {
+            Ok(package) => Some(package),
+            Err(err) => {
+                warn!("failed to open DWARF package {}: {err}", dwp.display());
+                None
+            }
+        }
+    }
+}
+
+
+/// A split compilation unit, along with the DWARF data it lives in.
+pub(super) struct DwoUnit<'dwarf> {
+    /// The DWARF data of the split unit.
+    pub dwarf: Dwarf<R<'dwarf>>,
+    /// The split unit itself.
+    pub dw_unit: gimli::Unit<R<'dwarf>>,
+    /// The unit's source code language.
+    pub lang: Option<gimli::DwLang>,
+    /// The parser backing `dwarf`, if the unit was loaded from a `.dwo`
+    /// file.
+    /// SAFETY: This member has to be listed after `dwarf` and `dw_unit`
+    ///         to make sure we never end up with a dangling reference.
+    _parser: Option<Box<ElfParser>>,
+}
+
+impl<'dwarf> DwoUnit<'dwarf> {
+    /// Load the split unit corresponding to the skeleton unit
+    /// `skeleton`.
+    ///
+    /// The unit is looked up in the DWARF package `dwp`, if provided,
+    /// and in the `.dwo` file referenced by the skeleton unit otherwise.
+    /// Missing or mismatching split units are not considered an error,
+    /// but result in `None`, as we can still fall back to information
+    /// provided by the skeleton unit itself.
+    pub(super) fn load(
+        skeleton: &gimli::Unit<R<'dwarf>>,
+        sections: &Dwarf<R<'dwarf>>,
+        dwp: Option<&DwarfPackage<'dwarf>>,
+    ) -> Result<Option<Self>, gimli::Error> {
+        let dwo_id = match skeleton.dwo_id {
+            Some(dwo_id) => dwo_id,
+            None => return Ok(None),
+        };
+
+        if let Some(dwp) = dwp {
+            if let Some(dwarf) = dwp.package.find_cu(dwo_id, sections)? {
+                return Self::from_dwarf(dwarf, skeleton, None)
+            }
+        }
+
+        let path = match dwo_path(skeleton, sections)? {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let parser = match ElfParser::open(&path) {
+            Ok(parser) => Box::new(parser),
+            Err(err) => {
+                debug!("failed to open split DWARF file {}: {err}", path.display());
+                return Ok(None)
+            }
+        };
+        // SAFETY: We keep the `ElfParser` around for as long as the
+        //         DWARF data referencing it.
+        let static_parser = unsafe { extend_lifetime(parser.deref()) };
+        let load_section = |section| reader::load_dwo_section(static_parser, section);
+        let mut dwarf = match Dwarf::load(load_section) {
+            Ok(dwarf) => dwarf,
+            Err(err) => {
+                warn!("failed to load split DWARF file {}: {err}", path.display());
+                return Ok(None)
+            }
+        };
+        let () = dwarf.make_dwo(sections);
+        Self::from_dwarf(dwarf, skeleton, Some(parser))
+    }
+
+    fn from_dwarf(
+        dwarf: Dwarf<R<'dwarf>>,
+        skeleton: &gimli::Unit<R<'dwarf>>,
+        parser: Option<Box<ElfParser>>,
+    ) -> Result<Option<Self>, gimli::Error> {
+        let mut headers = dwarf.units();
+        let header = loop {
+            match headers.next()? {
+                Some(header) => match header.type_() {
+                    gimli::UnitType::Type { .. } | gimli::UnitType::SplitType { .. } => continue,
+                    _ => break header,
+                },
+                None => return Ok(None),
+            }
+        };
+        let mut dw_unit = dwarf.unit(header)?;
+        if dw_unit.dwo_id != skeleton.dwo_id {
+            warn!(
+                "split DWARF unit ID {:?} does not match expected {:?}",
+                dw_unit.dwo_id, skeleton.dwo_id
+            );
+            return Ok(None)
+        }
+        let () = dw_unit.copy_relocated_attributes(skeleton);
+
+        let lang = {
+            let mut entries = dw_unit.entries();
+            match entries.next_dfs()? {
+                Some((_, entry)) => match entry.attr_value(gimli::DW_AT_language)? {
+                    Some(gimli::AttributeValue::Language(lang)) => Some(lang),
+                    _ => None,
+                },
+                None => None,
+            }
+        };
+
+        let slf = Self {
+            dwarf,
+            dw_unit,
+            lang,
+            _parser: parser,
+        };
+        Ok(Some(slf))
+    }
+}
+
+
+/// Determine the path of the `.dwo` file referenced by `skeleton`.
+///
+/// The name stored in the unit may be relative to its compilation
+/// directory.
+fn dwo_path<'dwarf>(
+    skeleton: &gimli::Unit<R<'dwarf>>,
+    sections: &Dwarf<R<'dwarf>>,
+) -> Result<Option<PathBuf>, gimli::Error> {
+    let name = match skeleton.dwo_name()? {
+        Some(name) => sections.attr_string(skeleton, name)?,
+        None => return Ok(None),
+    };
+    let name = Path::new(OsStr::from_bytes(name.slice()));
+    let path = match skeleton.comp_dir {
+        Some(dir) => Path::new(OsStr::from_bytes(dir.slice())).join(name),
+        None => name.to_path_buf(),
+    };
+    Ok(Some(path))
+}
@@ -27,11 +27,14 @@
 
 use super::function::Function;
 use super::function::Functions;
+use super::function::InlinedFunction;
 use super::lazy::LazyCell;
 use super::lines::Lines;
 use super::location::Location;
 use super::location::LocationRangeUnitIter;
 use super::reader::R;
+use super::split::DwarfPackage;
+use super::split::DwoUnit;
 
 
 pub(super) struct UnitRange {
@@ -46,6 +49,9 @@ pub(super) struct Unit<'dwarf> {
     lang: Option<gimli::DwLang>,
     lines: LazyCell<Result<Lines<'dwarf>, gimli::Error>>,
     funcs: LazyCell<Result<Functions<'dwarf>, gimli::Error>>,
+    /// The split unit corresponding to this unit, if it is a skeleton
+    /// unit.
+    dwo: LazyCell<Result<Option<DwoUnit<'dwarf>>, gimli::Error>>,
 }
 
 impl<'dwarf> Unit<'dwarf> {
@@ -59,6 +65,38 @@ impl<'dwarf> Unit<'dwarf> {
             lang,
             lines,
             funcs: LazyCell::new(),
+            dwo: LazyCell::new(),
+        }
+    }
+
+    /// Retrieve the DWARF data and unit describing this unit's
+    /// functions.
+    ///
+    /// For skeleton units of split DWARF, that is the split unit (if it
+    /// can be found), which is loaded on first use.
+    fn dwarf_and_unit<'unit>(
+        &'unit self,
+        sections: &'unit gimli::Dwarf<R<'dwarf>>,
+        dwp: Option<&DwarfPackage<'dwarf>>,
+    ) -> Result<
+        (
+            &'unit gimli::Dwarf<R<'dwarf>>,
+            &'unit gimli::Unit<R<'dwarf>>,
+        ),
+        gimli::Error,
+    > {
+        if self.dw_unit.dwo_id.is_none() {
+            return Ok((sections, &self.dw_unit))
+        }
+
+        let dwo = self
+            .dwo
+            .borrow_with(|| DwoUnit::load(&self.dw_unit, sections, dwp))
+            .as_ref()
+            .map_err(gimli::Error::clone)?;
+        match dwo {
+            Some(dwo) => Ok((&dwo.dwarf, &dwo.dw_unit)),
+            None => Ok((sections, &self.dw_unit)),
         }
     }
 
@@ -66,9 +104,10 @@ impl<'dwarf> Unit<'dwarf> {
     #[cfg(feature = "nightly")]
     pub(super) fn parse_functions<'unit>(
         &'unit self,
-        sections: &gimli::Dwarf<R<'dwarf>>,
+        sections: &'unit gimli::Dwarf<R<'dwarf>>,
+        dwp: Option<&DwarfPackage<'dwarf>>,
     ) -> Result<&'unit Functions<'dwarf>, gimli::Error> {
-        let unit = &self.dw_unit;
+        let (sections, unit) = self.dwarf_and_unit(sections, dwp)?;
         let functions = self.parse_functions_dwarf_and_unit(unit, sections)?;
         Ok(functions)
     }
@@ -120,9 +159,10 @@ impl<'dwarf> Unit<'dwarf> {
         &self,
         probe: u64,
         sections: &gimli::Dwarf<R<'dwarf>>,
+        dwp: Option<&DwarfPackage<'dwarf>>,
     ) -> Result<(Option<&Function<'dwarf>>, Option<Location<'_>>), gimli::Error> {
-        let unit = &self.dw_unit;
-        let functions = self.parse_functions_dwarf_and_unit(unit, sections)?;
+        let (dwarf, unit) = self.dwarf_and_unit(sections, dwp)?;
+        let functions = self.parse_functions_dwarf_and_unit(unit, dwarf)?;
         let function = match functions.find_address(probe) {
             Some(address) => {
                 let function_index = functions.addresses[address].function;
@@ -137,12 +177,58 @@ impl<'dwarf> Unit<'dwarf> {
         Ok((function, location))
     }
 
+    /// Find the chain of functions inlined at `probe`, along with the
+    /// source code location of each call site.
+    ///
+    /// Functions are reported from the outermost to the innermost one. A
+    /// result of `None` means that no function covers `probe`.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn find_inlined_functions(
+        &self,
+        probe: u64,
+        sections: &gimli::Dwarf<R<'dwarf>>,
+        dwp: Option<&DwarfPackage<'dwarf>>,
+    ) -> Result<Option<Vec<(&InlinedFunction<'dwarf>, Option<Location<'_>>)>>, gimli::Error> {
+        let (dwarf, unit) = self.dwarf_and_unit(sections, dwp)?;
+        let functions = self.parse_functions_dwarf_and_unit(unit, dwarf)?;
+        let function = match functions.find_address(probe) {
+            Some(address) => {
+                let function_index = functions.addresses[address].function;
+                &functions.functions[function_index]
+            }
+            None => return Ok(None),
+        };
+
+        let inlined = function.parse_inlined_functions(unit, dwarf)?;
+        // NB: Line information, which the call site file indices refer
+        //     to, is always provided by the skeleton unit.
+        let lines = self.parse_lines(sections)?;
+        let inlined = inlined
+            .find_inlined_functions(probe)
+            .into_iter()
+            .map(|inlined| {
+                let location = lines.and_then(|lines| {
+                    let (dir, file) = lines.files.get(inlined.call_file? as usize)?;
+                    Some(Location {
+                        dir,
+                        file,
+                        line: (inlined.call_line != 0).then_some(inlined.call_line),
+                        column: (inlined.call_column != 0).then_some(inlined.call_column),
+                    })
+                });
+                (inlined, location)
+            })
+            .collect();
+        Ok(Some(inlined))
+    }
+
     pub(super) fn find_name<'slf>(
         &'slf self,
         name: &str,
-        sections: &gimli::Dwarf<R<'dwarf>>,
+        sections: &'slf gimli::Dwarf<R<'dwarf>>,
+        dwp: Option<&DwarfPackage<'dwarf>>,
     ) -> Result<Option<&'slf Function<'dwarf>>, gimli::Error> {
-        let unit = &self.dw_unit;
+        let (sections, unit) = self.dwarf_and_unit(sections, dwp)?;
         let functions = self.parse_functions_dwarf_and_unit(unit, sections)?;
         for func in functions.functions.iter() {
             let name = Some(name.as_bytes());
@@ -156,6 +242,11 @@ impl<'dwarf> Unit<'dwarf> {
     /// Attempt to retrieve the compilation unit's source code language.
     #[inline]
     pub(super) fn language(&self) -> Option<gimli::DwLang> {
-        self.lang
+        // Skeleton units may not carry the language, in which case we
+        // check the split unit, provided it was loaded already.
+        self.lang.or_else(|| match self.dwo.borrow() {
+            Some(Ok(Some(dwo))) => dwo.lang,
+            _ => None,
+        })
     }
 }
@@ -29,11 +29,13 @@ use crate::ErrorExt as _;
 use crate::Result;
 
 use super::function::Function;
+use super::function::InlinedFunction;
 use super::lazy::LazyCell;
 use super::lines::Lines;
 use super::location::Location;
 use super::range::RangeAttributes;
 use super::reader::R;
+use super::split::DwarfPackage;
 use super::unit::Unit;
 use super::unit::UnitRange;
 
@@ -57,10 +59,18 @@ pub(crate) struct Units<'dwarf> {
     unit_ranges: Box<[UnitRange]>,
     /// All units along with meta-data.
     units: Box<[Unit<'dwarf>]>,
+    /// The DWARF package containing split units, if any.
+    dwp: Option<DwarfPackage<'dwarf>>,
 }
 
 impl<'dwarf> Units<'dwarf> {
-    pub(crate) fn parse(sections: gimli::Dwarf<R<'dwarf>>) -> Result<Self> {
+    /// Parse the units contained in `sections`.
+    ///
+    /// `dwp` is the DWARF package to look up split units in, if any.
+    pub(crate) fn parse(
+        sections: gimli::Dwarf<R<'dwarf>>,
+        dwp: Option<DwarfPackage<'dwarf>>,
+    ) -> Result<Self> {
         // Find all the references to compilation units in .debug_aranges.
         // Note that we always also iterate through all of .debug_info to
         // find compilation units, because .debug_aranges may be missing some.
@@ -224,6 +234,7 @@ impl<'dwarf> Units<'dwarf> {
             dwarf: sections,
             unit_ranges: unit_ranges.into_boxed_slice(),
             units: res_units.into_boxed_slice(),
+            dwp,
         };
         Ok(slf)
     }
@@ -301,7 +312,7 @@ impl<'dwarf> Units<'dwarf> {
     ) -> Result<Option<(&Function<'dwarf>, Option<gimli::DwLang>)>, gimli::Error> {
         let units_iter = self.find_units(probe);
         for unit in units_iter {
-            let result = unit.find_function_or_location(probe, &self.dwarf)?;
+            let result = unit.find_function_or_location(probe, &self.dwarf, self.dwp.as_ref())?;
             match result {
                 (Some(function), _) => return Ok(Some((function, unit.language()))),
                 (None, Some(_location)) => {
@@ -317,6 +328,31 @@ impl<'dwarf> Units<'dwarf> {
         Ok(None)
     }
 
+    /// Find the functions inlined at the given address, along with the
+    /// source code locations of their call sites.
+    ///
+    /// Functions are reported from the outermost to the innermost one.
+    #[allow(clippy::type_complexity)]
+    pub fn find_inlined_functions(
+        &self,
+        probe: u64,
+    ) -> Result<
+        Option<(
+            Vec<(&InlinedFunction<'dwarf>, Option<Location<'_>>)>,
+            Option<gimli::DwLang>,
+        )>,
+        gimli::Error,
+    > {
+        for unit in self.find_units(probe) {
+            if let Some(inlined) =
+                unit.find_inlined_functions(probe, &self.dwarf, self.dwp.as_ref())?
+            {
+                return Ok(Some((inlined, unit.language())))
+            }
+        }
+        Ok(None)
+    }
+
     /// Find the source file and line corresponding to the given virtual memory address.
     pub fn find_location(&self, probe: u64) -> Result<Option<Location<'_>>, gimli::Error> {
         for unit in self.find_units(probe) {
@@ -331,9 +367,10 @@ impl<'dwarf> Units<'dwarf> {
         &'slf self,
         name: &'s str,
     ) -> impl Iterator<Item = Result<&Function<'dwarf>, gimli::Error>> + 's {
-        self.units
-            .iter()
-            .filter_map(move |unit| unit.find_name(name, &self.dwarf).transpose())
+        self.units.iter().filter_map(move |unit| {
+            unit.find_name(name, &self.dwarf, self.dwp.as_ref())
+                .transpose()
+        })
     }
 
     /// Initialize all function data structures. This is used for benchmarks.
@@ -341,7 +378,7 @@ impl<'dwarf> Units<'dwarf> {
     #[cfg(feature = "nightly")]
     fn parse_functions(&self) -> Result<(), gimli::Error> {
         for unit in self.units.iter() {
-            let _functions = unit.parse_functions(&self.dwarf)?;
+            let _functions = unit.parse_functions(&self.dwarf, self.dwp.as_ref())?;
         }
         Ok(())
     }
@@ -408,7 +445,7 @@ mod tests {
             let parser = ElfParser::open(bin_name.as_ref()).unwrap();
             let mut load_section = |section| reader::load_section(&parser, section);
             let dwarf = Dwarf::<R>::load(&mut load_section).unwrap();
-            let units = Units::parse(dwarf).unwrap();
+            let units = Units::parse(dwarf, None).unwrap();
 
             // Double check that we actually did what we set out to do
             // by checking that we can find a function that we know
@@ -437,7 +474,7 @@ mod tests {
 
         let () = b.iter(|| {
             let dwarf = Dwarf::<R>::load(&mut load_section).unwrap();
-            let units = Units::parse(black_box(dwarf)).unwrap();
+            let units = Units::parse(black_box(dwarf), None).unwrap();
             let _funcs = black_box(units.parse_functions().unwrap());
         });
     }
@@ -468,7 +505,7 @@ mod tests {
 
         let () = b.iter(|| {
             let dwarf = Dwarf::<R>::load(&mut load_section).unwrap();
-            let units = Units::parse(black_box(dwarf)).unwrap();
+            let units = Units::parse(black_box(dwarf), None).unwrap();
             let _lines = black_box(units.parse_lines().unwrap());
         });
     }
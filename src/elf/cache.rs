@@ -1,21 +1,27 @@
-use std::cell::RefCell;
 use std::fs::File;
 use std::num::NonZeroUsize;
 use std::os::unix::io::AsRawFd;
 use std::path::Path;
-#[cfg(feature = "lru")]
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
+#[cfg(feature = "lru")]
+use std::sync::Mutex;
 
 #[cfg(feature = "lru")]
 use lru::LruCache;
 
 #[cfg(feature = "dwarf")]
 use crate::dwarf::DwarfResolver;
+use crate::log::warn;
 use crate::util::fstat;
 use crate::ErrorExt as _;
 use crate::Result;
 
+use super::debug_link::find_build_id_file;
+use super::debug_link::find_debug_file;
+use super::debug_link::DEFAULT_DEBUG_DIRS;
+#[cfg(feature = "debuginfod")]
+use super::DebugInfod;
 use super::ElfParser;
 
 // SAFETY: The provided value is non-zero.
@@ -24,16 +30,16 @@ const DFL_CACHE_MAX: NonZeroUsize = unsafe { NonZeroUsize::new_unchecked(1024) }
 #[derive(Clone, Debug)]
 pub(crate) enum ElfBackend {
     #[cfg(feature = "dwarf")]
-    Dwarf(Rc<DwarfResolver>), // ELF w/ DWARF
-    Elf(Rc<ElfParser>), // ELF w/o DWARF
+    Dwarf(Arc<DwarfResolver>), // ELF w/ DWARF
+    Elf(Arc<ElfParser>), // ELF w/o DWARF
 }
 
 #[cfg(test)]
 #[cfg(feature = "dwarf")]
 impl ElfBackend {
-    pub fn to_dwarf(&self) -> Option<Rc<DwarfResolver>> {
+    pub fn to_dwarf(&self) -> Option<Arc<DwarfResolver>> {
         if let Self::Dwarf(dwarf) = self {
-            Some(Rc::clone(dwarf))
+            Some(Arc::clone(dwarf))
         } else {
             None
         }
@@ -55,23 +61,60 @@ struct ElfCacheEntry {
 }
 
 impl ElfCacheEntry {
-    pub fn new(
+    pub fn new<F>(
+        path: &Path,
         file: File,
         line_number_info: bool,
         debug_info_symbols: bool,
-    ) -> Result<ElfCacheEntry> {
+        find_debug_file: F,
+    ) -> Result<ElfCacheEntry>
+    where
+        F: FnOnce(&ElfParser) -> Result<Option<ElfParser>>,
+    {
         let stat = fstat(file.as_raw_fd())?;
-        let parser = Rc::new(ElfParser::open_file(file)?);
+        let parser = Arc::new(ElfParser::open_file(file)?);
+
+        // If the file does not contain any debug information itself, check
+        // whether it has a companion file containing it. If so, we use
+        // that one as the source of DWARF data. Everything else (ELF
+        // symbols, program headers, ...) still comes from the file
+        // itself, as the two may differ in their layout.
+        // Failure to find such a file is not fatal, as we can still fall
+        // back to the symbol tables of the file itself.
+        let debug_parser = if cfg!(feature = "dwarf")
+            && (line_number_info || debug_info_symbols)
+            && parser.find_debug_section(".debug_info")?.is_none()
+        {
+            match find_debug_file(&parser) {
+                Ok(debug_parser) => debug_parser.map(Arc::new),
+                Err(err) => {
+                    warn!(
+                        "failed to find separate debug information for {}: {err}",
+                        path.display()
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         #[cfg(feature = "dwarf")]
-        let backend = ElfBackend::Dwarf(Rc::new(DwarfResolver::from_parser(
-            Rc::clone(&parser),
+        let backend = ElfBackend::Dwarf(Arc::new(DwarfResolver::from_parser(
+            parser,
+            debug_parser,
+            path,
             line_number_info,
             debug_info_symbols,
         )?));
 
         #[cfg(not(feature = "dwarf"))]
-        let backend = ElfBackend::Elf(parser);
+        let backend = {
+            // Without DWARF support we never look for separate debug
+            // information.
+            debug_assert!(debug_parser.is_none());
+            ElfBackend::Elf(parser)
+        };
 
         Ok(ElfCacheEntry {
             dev: stat.st_dev,
@@ -98,71 +141,140 @@ impl ElfCacheEntry {
 
 
 #[derive(Debug)]
-struct _ElfCache {
+pub(crate) struct ElfCache {
+    /// The cached entries.
+    ///
+    /// The lock is only ever held for lookups and insertions, never
+    /// while parsing files or fetching debug information, so that
+    /// concurrent users are not serialized.
     #[cfg(feature = "lru")]
-    cache: LruCache<PathBuf, ElfCacheEntry>,
+    cache: Mutex<LruCache<PathBuf, ElfCacheEntry>>,
     line_number_info: bool,
     debug_info_symbols: bool,
+    /// The directories to search for separate debug information.
+    debug_dirs: Box<[PathBuf]>,
+    /// The client used for fetching debug information that is not
+    /// available locally.
+    #[cfg(feature = "debuginfod")]
+    debuginfod: Option<DebugInfod>,
 }
 
-impl _ElfCache {
-    fn new(line_number_info: bool, debug_info_symbols: bool) -> _ElfCache {
-        _ElfCache {
+impl ElfCache {
+    pub fn new(
+        line_number_info: bool,
+        debug_info_symbols: bool,
+        debug_dirs: &[PathBuf],
+    ) -> ElfCache {
+        let debug_dirs = debug_dirs
+            .iter()
+            .cloned()
+            .chain(DEFAULT_DEBUG_DIRS.iter().map(PathBuf::from))
+            .collect();
+
+        ElfCache {
             #[cfg(feature = "lru")]
-            cache: LruCache::new(DFL_CACHE_MAX),
+            cache: Mutex::new(LruCache::new(DFL_CACHE_MAX)),
             line_number_info,
             debug_info_symbols,
+            debug_dirs,
+            #[cfg(feature = "debuginfod")]
+            debuginfod: None,
         }
     }
 
-    #[cfg(feature = "lru")]
-    fn find_or_create_backend(&mut self, file_name: &Path, file: File) -> Result<ElfBackend> {
-        if let Some(entry) = self.cache.get(file_name) {
-            let stat = fstat(file.as_raw_fd())?;
+    /// Set the client used for fetching debug information that is not
+    /// available locally.
+    #[cfg(feature = "debuginfod")]
+    pub fn set_debuginfod(&mut self, debuginfod: Option<DebugInfod>) {
+        self.debuginfod = debuginfod;
+    }
+
+    /// Find the separate debug information file for the ELF file at
+    /// `path`, first locally and then using debuginfod (if enabled).
+    fn find_debug_file(&self, path: &Path, parser: &ElfParser) -> Result<Option<ElfParser>> {
+        if let Some(debug_parser) = find_debug_file(path, parser, &self.debug_dirs)? {
+            return Ok(Some(debug_parser))
+        }
+
+        #[cfg(feature = "debuginfod")]
+        if let Some(debuginfod) = &self.debuginfod {
+            return debuginfod.find_debug_file(parser)
+        }
+
+        Ok(None)
+    }
 
-            if entry.is_valid(&stat) {
-                return Ok(entry.get_backend())
+    /// Find a file with the provided build ID, first locally and then
+    /// using debuginfod (if enabled).
+    pub fn find_by_build_id(&self, build_id: &[u8]) -> Result<Option<PathBuf>> {
+        if let Some((path, _parser)) = find_build_id_file(build_id, &self.debug_dirs) {
+            return Ok(Some(path))
+        }
+
+        #[cfg(feature = "debuginfod")]
+        if let Some(debuginfod) = &self.debuginfod {
+            if let Some(path) = debuginfod.fetch_debuginfo(build_id)? {
+                return Ok(Some(path))
             }
+            return debuginfod.fetch_executable(build_id)
         }
 
-        let entry = ElfCacheEntry::new(file, self.line_number_info, self.debug_info_symbols)?;
+        Ok(None)
+    }
+
+    fn create_entry(&self, path: &Path, file: File) -> Result<ElfCacheEntry> {
+        ElfCacheEntry::new(
+            path,
+            file,
+            self.line_number_info,
+            self.debug_info_symbols,
+            |parser| self.find_debug_file(path, parser),
+        )
+    }
+
+    #[cfg(feature = "lru")]
+    fn find_or_create_backend(&self, path: &Path, file: File) -> Result<ElfBackend> {
+        let stat = fstat(file.as_raw_fd())?;
+        let lookup = |cache: &mut LruCache<PathBuf, ElfCacheEntry>| {
+            cache
+                .get(path)
+                .filter(|entry| entry.is_valid(&stat))
+                .map(ElfCacheEntry::get_backend)
+        };
+
+        if let Some(backend) = lookup(&mut self.cache.lock().unwrap()) {
+            return Ok(backend)
+        }
+
+        // Create the entry without holding the lock, as doing so may
+        // take a while.
+        let entry = self.create_entry(path, file)?;
+
+        let mut cache = self.cache.lock().unwrap();
+        // Another thread may have raced us in creating an entry for the
+        // same file, in which case we use that one.
+        if let Some(backend) = lookup(&mut cache) {
+            return Ok(backend)
+        }
         let backend = entry.get_backend();
-        let _previous = self.cache.put(file_name.to_path_buf(), entry);
+        let _previous = cache.put(path.to_path_buf(), entry);
         Ok(backend)
     }
 
     #[cfg(not(feature = "lru"))]
-    fn find_or_create_backend(&mut self, _file_name: &Path, file: File) -> Result<ElfBackend> {
-        let entry = ElfCacheEntry::new(file, self.line_number_info, self.debug_info_symbols)?;
+    fn find_or_create_backend(&self, path: &Path, file: File) -> Result<ElfBackend> {
+        let entry = self.create_entry(path, file)?;
         let backend = entry.get_backend();
         Ok(backend)
     }
 
-    pub fn find(&mut self, path: &Path) -> Result<ElfBackend> {
+    pub fn find(&self, path: &Path) -> Result<ElfBackend> {
         let file = File::open(path)
             .with_context(|| format!("failed to open ELF file {}", path.display()))?;
         self.find_or_create_backend(path, file)
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct ElfCache {
-    cache: RefCell<_ElfCache>,
-}
-
-impl ElfCache {
-    pub fn new(line_number_info: bool, debug_info_symbols: bool) -> ElfCache {
-        ElfCache {
-            cache: RefCell::new(_ElfCache::new(line_number_info, debug_info_symbols)),
-        }
-    }
-
-    pub fn find(&self, path: &Path) -> Result<ElfBackend> {
-        let mut cache = self.cache.borrow_mut();
-        cache.find(path)
-    }
-}
-
 #[cfg(test)]
 #[cfg(feature = "dwarf")]
 mod tests {
@@ -173,6 +285,9 @@ mod tests {
 
     use test_log::test;
 
+    use crate::elf::types::PT_LOAD;
+    use crate::Error;
+
     #[test]
     fn test_cache() {
         let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
@@ -181,7 +296,7 @@ mod tests {
 
         let src_locations = true;
         let debug_syms = false;
-        let cache = ElfCache::new(src_locations, debug_syms);
+        let cache = ElfCache::new(src_locations, debug_syms, &[]);
         let backend_first = cache.find(Path::new(&bin_name));
         let backend_second = cache.find(Path::new(&bin_name));
         assert!(backend_first.is_ok());
@@ -195,4 +310,65 @@ mod tests {
             ptr::addr_of!(*backend_second.to_dwarf().unwrap().get_parser())
         );
     }
+
+    /// Check that failure to find separate debug information is not
+    /// fatal and that compressed debug sections are recognized as debug
+    /// information.
+    #[test]
+    fn debug_file_lookup() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-no-debug.bin");
+        let file = File::open(&path).unwrap();
+        let entry = ElfCacheEntry::new(&path, file, true, false, |_parser| {
+            Err(Error::with_invalid_data("malformed debug link"))
+        })
+        .unwrap();
+        assert!(entry.get_backend().is_dwarf());
+
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-compressed-debug-zlib-gnu.bin");
+        let file = File::open(&path).unwrap();
+        let _entry = ElfCacheEntry::new(&path, file, true, false, |_parser| {
+            panic!("searched for separate debug information")
+        })
+        .unwrap();
+    }
+
+    /// Check that a separate debug file is only used as the source of
+    /// DWARF data, while everything else is still read from the original
+    /// file.
+    #[cfg(feature = "dwarf")]
+    #[test]
+    fn debug_file_dwarf_only() {
+        let data_dir = Path::new(&env!("CARGO_MANIFEST_DIR")).join("data");
+        let path = data_dir.join("test-stable-addresses-stripped-with-link.bin");
+        let dbg = data_dir.join("test-stable-addresses.dbg");
+        let file = File::open(&path).unwrap();
+        let entry = ElfCacheEntry::new(&path, file, true, false, |_parser| {
+            ElfParser::open(&dbg).map(Some)
+        })
+        .unwrap();
+
+        let backend = entry.get_backend();
+        let dwarf = backend.to_dwarf().unwrap();
+        // The debug file's load segments are at different file offsets,
+        // but we should be working with those of the original file.
+        let load_offset = |parser: &ElfParser| {
+            parser
+                .program_headers()
+                .unwrap()
+                .iter()
+                .find(|phdr| phdr.p_type == PT_LOAD)
+                .map(|phdr| phdr.p_offset)
+        };
+        let parser = ElfParser::open(&path).unwrap();
+        let dbg_parser = ElfParser::open(&dbg).unwrap();
+        assert_ne!(load_offset(&parser), load_offset(&dbg_parser));
+        assert_eq!(load_offset(dwarf.get_parser()), load_offset(&parser));
+
+        let info = dwarf.find_line_info(0x2000100).unwrap().unwrap();
+        assert_eq!(info.line, Some(8));
+    }
 }
@@ -0,0 +1,264 @@
+//! Discovery of separate debug information files.
+//!
+//! Stripped ELF binaries may refer to a companion file containing their
+//! debug information. Such a file can be found based on the binary's
+//! build ID (using the `.build-id/xx/yyyy.debug` layout inside of a debug
+//! directory) or based on the file name stored in its `.gnu_debuglink`
+//! section.
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::log::debug;
+use crate::log::warn;
+use crate::mmap::Mmap;
+use crate::normalize::buildid::BuildIdReader as _;
+use crate::normalize::buildid::DefaultBuildIdReader;
+use crate::util::bytes_to_hex;
+use crate::util::ReadRaw as _;
+use crate::IntoError as _;
+use crate::Result;
+
+use super::ElfParser;
+
+
+/// The directories searched for separate debug information by default.
+pub(crate) const DEFAULT_DEBUG_DIRS: &[&str] = &["/usr/lib/debug", "/lib/debug"];
+
+
+/// Create the lookup table for the CRC-32 variant used by
+/// `.gnu_debuglink` (the one used by zlib as well).
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// Calculate the CRC-32 checksum of the provided data.
+fn crc32(data: &[u8]) -> u32 {
+    let crc = data.iter().fold(!0u32, |crc, byte| {
+        CRC32_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    });
+    !crc
+}
+
+
+/// Read the file name and checksum stored in the `.gnu_debuglink`
+/// section of an ELF file, if any.
+fn read_debug_link(parser: &ElfParser) -> Result<Option<(&OsStr, u32)>> {
+    let idx = if let Some(idx) = parser.find_section(".gnu_debuglink")? {
+        idx
+    } else {
+        return Ok(None)
+    };
+
+    let data = parser.section_data(idx)?;
+    let mut cursor = data;
+    let name = cursor
+        .read_cstr()
+        .ok_or_invalid_data(|| "failed to read .gnu_debuglink file name")?;
+    // The checksum is stored at the next four byte aligned offset after
+    // the file name.
+    let offset = (name.to_bytes_with_nul().len() + 3) & !3;
+    let crc = data
+        .get(offset..)
+        .and_then(|mut data| data.read_u32())
+        .ok_or_invalid_data(|| "failed to read .gnu_debuglink checksum")?;
+    // The checksum is stored in the byte order of the ELF file.
+    let crc = if parser.is_swapped()? {
+        crc.swap_bytes()
+    } else {
+        crc
+    };
+
+    Ok(Some((OsStr::from_bytes(name.to_bytes()), crc)))
+}
+
+
+/// Format a build ID in its hexadecimal `.build-id` path form,
+/// i.e., as `xx/yyyy.debug`.
+fn build_id_path(build_id: &[u8]) -> Option<PathBuf> {
+    let (first, rest) = build_id.split_first()?;
+    if rest.is_empty() {
+        return None
+    }
+
+    let dir = format!("{first:02x}");
+    let file = bytes_to_hex(rest);
+    Some(Path::new(&dir).join(format!("{file}.debug")))
+}
+
+
+/// Try to open the debug file at `path`, checking it with `check`.
+pub(super) fn try_open(
+    path: &Path,
+    check: impl FnOnce(&Mmap, &ElfParser) -> bool,
+) -> Option<ElfParser> {
+    if !path.exists() {
+        return None
+    }
+
+    let mmap = match Mmap::builder().open(path) {
+        Ok(mmap) => mmap,
+        Err(err) => {
+            warn!("failed to open debug file {}: {err}", path.display());
+            return None
+        }
+    };
+    let parser = ElfParser::from_mmap(mmap.clone());
+    if check(&mmap, &parser) {
+        debug!("using debug file {}", path.display());
+        Some(parser)
+    } else {
+        debug!("ignoring non-matching debug file {}", path.display());
+        None
+    }
+}
+
+
+/// Find the file with the provided build ID in the `.build-id`
+/// directory of one of `debug_dirs`.
+pub(crate) fn find_build_id_file(
+    build_id: &[u8],
+    debug_dirs: &[PathBuf],
+) -> Option<(PathBuf, ElfParser)> {
+    let build_id_path = build_id_path(build_id)?;
+    debug_dirs.iter().find_map(|dir| {
+        let path = dir.join(".build-id").join(&build_id_path);
+        let check = |_mmap: &Mmap, parser: &ElfParser| {
+            matches!(
+                DefaultBuildIdReader::read_build_id(parser),
+                Ok(Some(id)) if id == build_id
+            )
+        };
+        try_open(&path, check).map(|parser| (path, parser))
+    })
+}
+
+
+/// Find the separate debug information file for the ELF file at `path`
+/// (represented by `parser`).
+///
+/// `debug_dirs` are searched in order, using the build ID based layout
+/// first and the `.gnu_debuglink` based one second.
+pub(crate) fn find_debug_file(
+    path: &Path,
+    parser: &ElfParser,
+    debug_dirs: &[PathBuf],
+) -> Result<Option<ElfParser>> {
+    if let Some(build_id) = DefaultBuildIdReader::read_build_id(parser)? {
+        if let Some((_path, debug_parser)) = find_build_id_file(&build_id, debug_dirs) {
+            return Ok(Some(debug_parser))
+        }
+    }
+
+    if let Some((name, crc)) = read_debug_link(parser)? {
+        // We want to look in the directory of the actual binary and not,
+        // say, that of a symbolic link to it (e.g., a
+        // `/proc/<pid>/map_files/` entry).
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let bin_dir = path.parent().unwrap_or_else(|| Path::new("/"));
+        let candidates = [bin_dir.join(name), bin_dir.join(".debug").join(name)]
+            .into_iter()
+            .chain(debug_dirs.iter().map(|dir| {
+                let rel_dir = bin_dir.strip_prefix("/").unwrap_or(bin_dir);
+                dir.join(rel_dir).join(name)
+            }));
+
+        for debug_path in candidates {
+            if debug_path == path {
+                continue
+            }
+
+            let check = |mmap: &Mmap, _parser: &ElfParser| crc32(mmap) == crc;
+            if let Some(debug_parser) = try_open(&debug_path, check) {
+                return Ok(Some(debug_parser))
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    use test_log::test;
+
+
+    /// Check that our CRC-32 implementation produces expected results.
+    #[test]
+    fn crc32_calculation() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414fa339
+        );
+    }
+
+    /// Check that we can format build IDs as paths.
+    #[test]
+    fn build_id_path_formatting() {
+        assert_eq!(build_id_path(&[]), None);
+        assert_eq!(build_id_path(&[0x12]), None);
+        assert_eq!(
+            build_id_path(&[0x12, 0xab, 0x0f]),
+            Some(PathBuf::from("12/ab0f.debug"))
+        );
+    }
+
+    /// Check that we can read the `.gnu_debuglink` section of a binary.
+    #[test]
+    fn debug_link_reading() {
+        let bin = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-stripped-with-link.bin");
+        let parser = ElfParser::open(&bin).unwrap();
+        let (name, crc) = read_debug_link(&parser).unwrap().unwrap();
+        assert_eq!(name, "test-stable-addresses.dbg");
+
+        let debug = bin.with_file_name(name);
+        let mmap = Mmap::builder().open(debug).unwrap();
+        assert_eq!(crc32(&mmap), crc);
+
+        let bin = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let parser = ElfParser::open(&bin).unwrap();
+        assert!(read_debug_link(&parser).unwrap().is_none());
+    }
+
+    /// Check that we can find a debug file via `.gnu_debuglink`.
+    #[test]
+    fn debug_file_finding() {
+        let bin = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-stripped-with-link.bin");
+        let parser = ElfParser::open(&bin).unwrap();
+        let debug_parser = find_debug_file(&bin, &parser, &[]).unwrap().unwrap();
+        assert!(debug_parser.find_section(".debug_info").unwrap().is_some());
+    }
+}
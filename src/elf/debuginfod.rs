@@ -0,0 +1,433 @@
+//! A client for fetching debug information from debuginfod servers.
+//!
+//! debuginfod servers provide debug information and executables indexed
+//! by build ID, via the `/buildid/<build-id>/debuginfo` and
+//! `/buildid/<build-id>/executable` endpoints, respectively. Fetched
+//! files are stored in a local cache directory, using the same layout
+//! (`<cache-dir>/<build-id>/debuginfo`), so that they only have to be
+//! downloaded once.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs::create_dir_all;
+use std::fs::remove_file;
+use std::fs::rename;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ureq::Agent;
+use ureq::AgentBuilder;
+use ureq::Error as UreqError;
+
+use crate::log::debug;
+use crate::log::warn;
+use crate::mmap::Mmap;
+use crate::normalize::buildid::BuildIdReader as _;
+use crate::normalize::buildid::DefaultBuildIdReader;
+use crate::util::bytes_to_hex;
+use crate::ErrorExt as _;
+use crate::Result;
+
+use super::debug_link::try_open;
+use super::ElfParser;
+
+
+/// The timeout used for connecting to and reading from servers.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A counter used for making temporary file names unique.
+static TMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+
+/// Open the fetched file at `path`, checking that it has the expected
+/// build ID.
+///
+/// Files not matching `build_id` are removed from the cache, so that
+/// they can be fetched again.
+fn open_verified(path: &Path, build_id: &[u8]) -> Option<ElfParser> {
+    let mut mismatch = false;
+    let parser = try_open(path, |_mmap: &Mmap, parser: &ElfParser| {
+        mismatch = !matches!(
+            DefaultBuildIdReader::read_build_id(parser),
+            Ok(Some(id)) if id == build_id
+        );
+        !mismatch
+    });
+
+    if mismatch {
+        warn!(
+            "removing {} from debuginfod cache: build ID mismatch",
+            path.display()
+        );
+        if let Err(err) = remove_file(path) {
+            warn!("failed to remove {}: {err}", path.display());
+        }
+    }
+    parser
+}
+
+
+/// The name of the directory used for caching fetched files, if none
+/// was provided explicitly.
+fn default_cache_dir() -> PathBuf {
+    let cache_dir = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(env::temp_dir);
+    cache_dir.join("blazesym").join("debuginfod")
+}
+
+
+/// A client for fetching files from a list of debuginfod servers.
+#[derive(Debug)]
+pub(crate) struct DebugInfod {
+    /// The agent used for all HTTP requests.
+    agent: Agent,
+    /// The base URLs of the servers to query, in order.
+    urls: Box<[String]>,
+    /// The directory in which to cache fetched files.
+    cache_dir: PathBuf,
+    /// The cache paths of artifacts that all servers reported as not
+    /// found, so that we don't ask again.
+    misses: Mutex<HashSet<PathBuf>>,
+}
+
+impl DebugInfod {
+    /// Create a new `DebugInfod` client querying the servers at `urls`
+    /// and caching fetched files in `cache_dir`.
+    pub fn new(urls: Vec<String>, cache_dir: Option<PathBuf>) -> Self {
+        let agent = AgentBuilder::new()
+            .timeout_connect(TIMEOUT)
+            .timeout_read(TIMEOUT)
+            .build();
+
+        Self {
+            agent,
+            urls: urls.into_boxed_slice(),
+            cache_dir: cache_dir.unwrap_or_else(default_cache_dir),
+            misses: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Fetch the artifact `artifact` (e.g., `debuginfo`) for the
+    /// provided build ID, returning the path to the locally cached copy.
+    fn fetch(&self, build_id: &[u8], artifact: &str) -> Result<Option<PathBuf>> {
+        let build_id = bytes_to_hex(build_id);
+        let dir = self.cache_dir.join(&build_id);
+        let path = dir.join(artifact);
+        if path.exists() {
+            return Ok(Some(path))
+        }
+
+        if self.misses.lock().unwrap().contains(&path) {
+            return Ok(None)
+        }
+
+        // Only if all servers definitively don't know about the artifact
+        // do we remember it as missing; other failures may be
+        // transient.
+        let mut missing = true;
+        for url in self.urls.iter() {
+            let url = format!(
+                "{}/buildid/{build_id}/{artifact}",
+                url.trim_end_matches('/')
+            );
+            let response = match self.agent.get(&url).call() {
+                Ok(response) => response,
+                Err(UreqError::Status(status, _response)) => {
+                    debug!("{url} returned status {status}");
+                    missing &= status == 404;
+                    continue
+                }
+                Err(err) => {
+                    warn!("failed to fetch {url}: {err}");
+                    missing = false;
+                    continue
+                }
+            };
+
+            let () = create_dir_all(&dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+            // Download into a temporary file first, so that concurrent
+            // users never observe a partially written one. The file
+            // name is unique to this download, even across threads.
+            let tmp_path = dir.join(format!(
+                "{artifact}.{}.{}.tmp",
+                process::id(),
+                TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&tmp_path)
+                .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+            let result = io::copy(&mut response.into_reader(), &mut file);
+            if let Err(err) = result {
+                warn!("failed to download {url}: {err}");
+                let _result = remove_file(&tmp_path);
+                missing = false;
+                continue
+            }
+            let () = rename(&tmp_path, &path)
+                .with_context(|| format!("failed to rename {}", tmp_path.display()))?;
+            debug!("fetched {url} into {}", path.display());
+            return Ok(Some(path))
+        }
+
+        if missing {
+            let _inserted = self.misses.lock().unwrap().insert(path);
+        }
+        Ok(None)
+    }
+
+    /// Fetch the debug information file for the provided build ID.
+    pub fn fetch_debuginfo(&self, build_id: &[u8]) -> Result<Option<PathBuf>> {
+        self.fetch(build_id, "debuginfo")
+    }
+
+    /// Fetch the executable for the provided build ID.
+    pub fn fetch_executable(&self, build_id: &[u8]) -> Result<Option<PathBuf>> {
+        self.fetch(build_id, "executable")
+    }
+
+    /// Find a file containing the debug information for the ELF file
+    /// represented by `parser`.
+    ///
+    /// The server's debug information file is preferred. Because some
+    /// servers only provide the (unstripped) executable, we fall back
+    /// to it if it contains debug information.
+    pub fn find_debug_file(&self, parser: &ElfParser) -> Result<Option<ElfParser>> {
+        let build_id = if let Some(build_id) = DefaultBuildIdReader::read_build_id(parser)? {
+            build_id
+        } else {
+            return Ok(None)
+        };
+
+        if let Some(path) = self.fetch_debuginfo(&build_id)? {
+            if let Some(parser) = open_verified(&path, &build_id) {
+                return Ok(Some(parser))
+            }
+        }
+
+        if let Some(path) = self.fetch_executable(&build_id)? {
+            if let Some(parser) = open_verified(&path, &build_id) {
+                if let Ok(Some(_)) = parser.find_debug_section(".debug_info") {
+                    return Ok(Some(parser))
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::fs::read as read_file;
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::io::Write as _;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+
+    use tempfile::tempdir;
+    use test_log::test;
+
+
+    /// A minimal HTTP server standing in for a debuginfod server.
+    ///
+    /// The server serves the provided files, keyed by request path, and
+    /// responds with 404 for everything else.
+    struct Server {
+        /// The base URL of the server.
+        url: String,
+        /// The number of requests handled so far.
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl Server {
+        fn start(files: HashMap<String, Vec<u8>>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(AtomicUsize::new(0));
+            let count = Arc::clone(&requests);
+
+            let _handle = thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut reader = BufReader::new(&stream);
+                    let mut request = String::new();
+                    let _count = reader.read_line(&mut request).unwrap();
+                    // Skip over all headers.
+                    loop {
+                        let mut line = String::new();
+                        let _count = reader.read_line(&mut line).unwrap();
+                        if line.trim_end().is_empty() {
+                            break
+                        }
+                    }
+
+                    let _count = count.fetch_add(1, Ordering::SeqCst);
+                    let path = request.split_whitespace().nth(1).unwrap_or("");
+                    let () = match files.get(path) {
+                        Some(data) => {
+                            let header = format!(
+                                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                                data.len()
+                            );
+                            let () = stream.write_all(header.as_bytes()).unwrap();
+                            stream.write_all(data).unwrap()
+                        }
+                        None => stream
+                            .write_all(
+                                b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            )
+                            .unwrap(),
+                    };
+                }
+            });
+
+            Self { url, requests }
+        }
+    }
+
+
+    /// Check that we can fetch debug information from a server and that
+    /// we cache fetched files.
+    #[test]
+    fn debuginfo_fetching() {
+        let data = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-dwarf-v5.bin");
+        let build_id = [0xde, 0xad, 0xbe, 0xef];
+        let content = read_file(&data).unwrap();
+        let files = HashMap::from([("/buildid/deadbeef/debuginfo".to_string(), content.clone())]);
+        let server = Server::start(files);
+        let cache_dir = tempdir().unwrap();
+
+        // The first server is not reachable and should just be skipped.
+        let urls = vec!["http://127.0.0.1:1".to_string(), server.url.clone()];
+        let debuginfod = DebugInfod::new(urls, Some(cache_dir.path().to_path_buf()));
+        let path = debuginfod.fetch_debuginfo(&build_id).unwrap().unwrap();
+        assert_eq!(path, cache_dir.path().join("deadbeef").join("debuginfo"));
+        assert_eq!(read_file(&path).unwrap(), content);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+
+        // A second request should be served from the cache.
+        let path = debuginfod.fetch_debuginfo(&build_id).unwrap().unwrap();
+        assert_eq!(read_file(path).unwrap(), content);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+
+        // The server does not know about the executable. Because the
+        // other server was unreachable, that may just be temporary,
+        // though, and we should ask again.
+        let path = debuginfod.fetch_executable(&build_id).unwrap();
+        assert_eq!(path, None);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+        let path = debuginfod.fetch_executable(&build_id).unwrap();
+        assert_eq!(path, None);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 3);
+
+        // If all servers report the executable as not found, we should
+        // remember that instead of asking again.
+        let debuginfod = DebugInfod::new(
+            vec![server.url.clone()],
+            Some(cache_dir.path().to_path_buf()),
+        );
+        let path = debuginfod.fetch_executable(&build_id).unwrap();
+        assert_eq!(path, None);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+        let path = debuginfod.fetch_executable(&build_id).unwrap();
+        assert_eq!(path, None);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+    }
+
+    /// Check that concurrent fetches of the same artifact don't
+    /// interfere with each other.
+    #[test]
+    fn concurrent_fetching() {
+        let data = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-dwarf-v5.bin");
+        let build_id = [0xde, 0xad, 0xbe, 0xef];
+        let content = read_file(&data).unwrap();
+        let files = HashMap::from([("/buildid/deadbeef/debuginfo".to_string(), content.clone())]);
+        let server = Server::start(files);
+        let cache_dir = tempdir().unwrap();
+
+        let debuginfod = Arc::new(DebugInfod::new(
+            vec![server.url.clone()],
+            Some(cache_dir.path().to_path_buf()),
+        ));
+        let handles = (0..8)
+            .map(|_| {
+                let debuginfod = Arc::clone(&debuginfod);
+                thread::spawn(move || debuginfod.fetch_debuginfo(&build_id).unwrap().unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            let path = handle.join().unwrap();
+            assert_eq!(read_file(path).unwrap(), content);
+        }
+    }
+
+    /// Check that we can find the debug information for a stripped
+    /// binary via a server.
+    #[test]
+    fn debug_file_finding() {
+        let data_dir = Path::new(&env!("CARGO_MANIFEST_DIR")).join("data");
+        let stripped = ElfParser::open(&data_dir.join("test-dwarf-v5-stripped.bin")).unwrap();
+        let build_id = DefaultBuildIdReader::read_build_id(&stripped)
+            .unwrap()
+            .unwrap();
+        let content = read_file(data_dir.join("test-dwarf-v5.bin")).unwrap();
+        let path = format!("/buildid/{}/executable", bytes_to_hex(&build_id));
+        let server = Server::start(HashMap::from([(path, content)]));
+        let cache_dir = tempdir().unwrap();
+
+        let debuginfod = DebugInfod::new(vec![server.url], Some(cache_dir.path().to_path_buf()));
+        let parser = debuginfod.find_debug_file(&stripped).unwrap().unwrap();
+        assert!(parser.find_section(".debug_info").unwrap().is_some());
+    }
+
+    /// Check that fetched files not matching the requested build ID are
+    /// removed from the cache.
+    #[test]
+    fn mismatching_build_id() {
+        let data_dir = Path::new(&env!("CARGO_MANIFEST_DIR")).join("data");
+        let stripped = ElfParser::open(&data_dir.join("test-dwarf-v5-stripped.bin")).unwrap();
+        let build_id = DefaultBuildIdReader::read_build_id(&stripped)
+            .unwrap()
+            .unwrap();
+        // Serve a file with a different build ID.
+        let content = read_file(data_dir.join("test-stable-addresses.bin")).unwrap();
+        let path = format!("/buildid/{}/debuginfo", bytes_to_hex(&build_id));
+        let server = Server::start(HashMap::from([(path, content)]));
+        let cache_dir = tempdir().unwrap();
+
+        let debuginfod = DebugInfod::new(
+            vec![server.url.clone()],
+            Some(cache_dir.path().to_path_buf()),
+        );
+        let parser = debuginfod.find_debug_file(&stripped).unwrap();
+        assert!(parser.is_none());
+
+        let path = cache_dir
+            .path()
+            .join(bytes_to_hex(&build_id))
+            .join("debuginfo");
+        assert!(!path.exists());
+    }
+}
@@ -1,5 +1,8 @@
 #[cfg_attr(not(feature = "dwarf"), allow(unused_variables))]
 mod cache;
+mod debug_link;
+#[cfg(feature = "debuginfod")]
+mod debuginfod;
 mod parser;
 #[cfg_attr(not(feature = "dwarf"), allow(unused_variables))]
 mod resolver;
@@ -8,5 +11,9 @@ pub(crate) mod types;
 
 pub(crate) use cache::ElfBackend;
 pub(crate) use cache::ElfCache;
+pub(crate) use debug_link::find_build_id_file;
+pub(crate) use debug_link::DEFAULT_DEBUG_DIRS;
+#[cfg(feature = "debuginfod")]
+pub(crate) use debuginfod::DebugInfod;
 pub(crate) use parser::ElfParser;
 pub(crate) use resolver::ElfResolver;
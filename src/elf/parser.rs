@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
@@ -6,12 +6,16 @@ use std::fs::File;
 use std::mem;
 use std::ops::Deref as _;
 use std::path::Path;
+use std::sync::Mutex;
 
 use crate::inspect::FindAddrOpts;
 use crate::inspect::SymInfo;
 use crate::inspect::SymType;
+use crate::log::debug;
+use crate::log::warn;
 use crate::mmap::Mmap;
 use crate::util::find_match_or_lower_bound_by_key;
+use crate::util::Pod;
 use crate::util::ReadRaw as _;
 use crate::Addr;
 use crate::Error;
@@ -19,15 +23,52 @@ use crate::ErrorExt as _;
 use crate::IntoError as _;
 use crate::Result;
 
+use super::types::ByteSwap;
+use super::types::Elf32_Chdr;
+use super::types::Elf32_Ehdr;
+use super::types::Elf32_Phdr;
+use super::types::Elf32_Rela;
+use super::types::Elf32_Shdr;
+use super::types::Elf32_Sym;
+use super::types::Elf64_Chdr;
 use super::types::Elf64_Ehdr;
 use super::types::Elf64_Phdr;
+use super::types::Elf64_Rela;
 use super::types::Elf64_Shdr;
 use super::types::Elf64_Sym;
+use super::types::EI_CLASS;
+use super::types::EI_DATA;
+use super::types::ELFCLASS32;
+use super::types::ELFCOMPRESS_ZLIB;
+use super::types::ELFCOMPRESS_ZSTD;
+use super::types::ELFDATA2MSB;
+use super::types::ELFDATA_NATIVE;
+use super::types::EM_AARCH64;
+use super::types::EM_RISCV;
+use super::types::EM_X86_64;
+use super::types::ET_CORE;
+use super::types::ET_REL;
+use super::types::R_AARCH64_ABS32;
+use super::types::R_AARCH64_ABS64;
+use super::types::R_RISCV_32;
+use super::types::R_RISCV_64;
+use super::types::R_X86_64_32;
+use super::types::R_X86_64_32S;
+use super::types::R_X86_64_64;
+use super::types::SHF_ALLOC;
+use super::types::SHF_COMPRESSED;
+use super::types::SHN_LORESERVE;
 use super::types::SHN_UNDEF;
+use super::types::SHT_REL;
+use super::types::SHT_RELA;
 #[cfg(test)]
 use super::types::STT_FUNC;
 
 
+/// The maximum size of the ELF file embedded in a `.gnu_debugdata`
+/// section ("MiniDebugInfo") that we are willing to decompress.
+const MAX_MINI_DEBUG_INFO_SIZE: u64 = 256 * 1024 * 1024;
+
 fn symbol_name<'mmap>(strtab: &'mmap [u8], sym: &Elf64_Sym) -> Result<&'mmap str> {
     let name = strtab
         .get(sym.st_name as usize..)
@@ -77,6 +118,144 @@ fn find_sym<'mmap>(
 }
 
 
+/// Decompress zlib compressed data, expected to be `size` bytes in
+/// size when decompressed.
+#[cfg(feature = "zlib")]
+fn decompress_zlib(data: &[u8], size: u64) -> Result<Vec<u8>> {
+    let decompressed = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(data, size as usize)
+        .map_err(|err| {
+            Error::with_invalid_data(format!("failed to decompress zlib data: {:?}", err.status))
+        })?;
+    if decompressed.len() as u64 != size {
+        return Err(Error::with_invalid_data(format!(
+            "zlib decompressed data has unexpected size: {} (expected {size})",
+            decompressed.len()
+        )))
+    }
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "zlib"))]
+fn decompress_zlib(_data: &[u8], _size: u64) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "zlib compressed data is not supported; enable the `zlib` feature",
+    ))
+}
+
+/// Decompress zstd compressed data, expected to be `size` bytes in
+/// size when decompressed.
+#[cfg(feature = "zstd")]
+fn decompress_zstd(data: &[u8], size: u64) -> Result<Vec<u8>> {
+    use std::io::Read as _;
+    use zstd::stream::read::Decoder;
+
+    // `size` stems from the (untrusted) compression header, so we don't
+    // use it for pre-allocating the output buffer. Reading one byte more
+    // than expected allows us to detect excess data.
+    let mut decompressed = Vec::new();
+    let _count = Decoder::with_buffer(data)
+        .map_err(Error::from)
+        .context("failed to create zstd decoder")?
+        .take(size.saturating_add(1))
+        .read_to_end(&mut decompressed)
+        .map_err(Error::from)
+        .context("failed to decompress zstd data")?;
+    if decompressed.len() as u64 != size {
+        return Err(Error::with_invalid_data(format!(
+            "zstd decompressed data has unexpected size: {} (expected {size})",
+            decompressed.len()
+        )))
+    }
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_data: &[u8], _size: u64) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "zstd compressed data is not supported; enable the `zstd` feature",
+    ))
+}
+
+/// Decompress xz compressed data, expected to be at most `max_size`
+/// bytes in size when decompressed.
+#[cfg(feature = "xz")]
+fn decompress_xz(data: &[u8], max_size: u64) -> Result<Vec<u8>> {
+    use std::io::Read as _;
+    use xz2::read::XzDecoder;
+
+    // Reading one byte more than allowed lets us detect excess data.
+    let mut decompressed = Vec::new();
+    let _count = XzDecoder::new(data)
+        .take(max_size.saturating_add(1))
+        .read_to_end(&mut decompressed)
+        .map_err(Error::from)
+        .context("failed to decompress xz data")?;
+    if decompressed.len() as u64 > max_size {
+        return Err(Error::with_invalid_data(format!(
+            "xz decompressed data exceeds maximum size of {max_size} bytes"
+        )))
+    }
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "xz"))]
+fn decompress_xz(_data: &[u8], _max_size: u64) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "xz compressed data is not supported; enable the `xz` feature",
+    ))
+}
+
+
+/// Extend the lifetime of a reference to data owned by a [`Cache`].
+///
+/// # Safety
+/// The referenced data has to be heap allocated and owned by the cache
+/// and it must neither be modified nor freed for as long as the cache
+/// is alive.
+unsafe fn extend_lifetime<'mmap, T>(data: &T) -> &'mmap T
+where
+    T: ?Sized,
+{
+    unsafe { &*(data as *const T) }
+}
+
+/// Read `count` ELF structures of 64 bit type `U` from `data`.
+///
+/// For 64 bit files using the host's byte order, the structures are
+/// referenced in place. Otherwise they are read (in their 32 bit form
+/// `T` for 32 bit files), byte swapped as necessary, converted into
+/// `U`, and stored in `storage`, which the returned slice refers to.
+fn read_elf_slice<'mmap, T, U>(
+    mut data: &'mmap [u8],
+    count: usize,
+    is_32bit: bool,
+    swap: bool,
+    storage: &mut Option<Box<[U]>>,
+) -> Option<&'mmap [U]>
+where
+    T: Pod + ByteSwap,
+    U: Pod + ByteSwap + for<'t> From<&'t T>,
+{
+    let converted = if is_32bit {
+        (0..count)
+            .map(|_| data.read_pod::<T>().map(|t| U::from(&t.byte_swap_if(swap))))
+            .collect::<Option<Box<[U]>>>()?
+    } else if swap {
+        (0..count)
+            .map(|_| data.read_pod::<U>().map(U::byte_swap))
+            .collect::<Option<Box<[U]>>>()?
+    } else {
+        return data.read_pod_slice_ref::<U>(count)
+    };
+
+    // SAFETY: The converted data is owned by the cache and is neither
+    //         modified nor freed while it is alive.
+    let slice = unsafe { extend_lifetime(&*converted) };
+    *storage = Some(converted);
+    Some(slice)
+}
+
+
 struct Cache<'mmap> {
     /// A slice of the raw ELF data that we are about to parse.
     elf_data: &'mmap [u8],
@@ -91,6 +270,33 @@ struct Cache<'mmap> {
     /// The cached ELF string table.
     strtab: Option<&'mmap [u8]>,
     str2symtab: Option<Box<[(&'mmap str, usize)]>>, // strtab offset to symtab in the dictionary order
+    /// Decompressed and/or relocated section data, keyed by section
+    /// index.
+    ///
+    /// Entries are never removed or modified once inserted.
+    decompressed: HashMap<usize, Box<[u8]>>,
+    /// Storage for the ELF header of 32 bit or foreign byte order
+    /// files, converted into its native 64 bit form. `ehdr` refers to
+    /// it.
+    owned_ehdr: Option<Box<Elf64_Ehdr>>,
+    /// Storage for the section headers of 32 bit or foreign byte order
+    /// files, converted into their native 64 bit form. `shdrs` refers
+    /// to it.
+    owned_shdrs: Option<Box<[Elf64_Shdr]>>,
+    /// Storage for the program headers of 32 bit or foreign byte order
+    /// files, converted into their native 64 bit form. `phdrs` refers
+    /// to it.
+    owned_phdrs: Option<Box<[Elf64_Phdr]>>,
+    /// Storage for the symbols of 32 bit or foreign byte order files,
+    /// converted into their native 64 bit form. `symtab` refers to it.
+    owned_syms: Option<Box<[Elf64_Sym]>>,
+    /// The parser for the ELF file embedded in the `.gnu_debugdata`
+    /// section ("MiniDebugInfo"), if any. The outer `Option` indicates
+    /// whether we already looked for it.
+    mini_debug_info: Option<Option<Box<ElfParser>>>,
+    /// Synthetic addresses of the sections of a relocatable object
+    /// file, indexed by section index.
+    section_addrs: Option<Box<[u64]>>,
 }
 
 impl<'mmap> Cache<'mmap> {
@@ -105,7 +311,199 @@ impl<'mmap> Cache<'mmap> {
             symtab: None,
             strtab: None,
             str2symtab: None,
+            decompressed: HashMap::new(),
+            owned_ehdr: None,
+            owned_shdrs: None,
+            owned_phdrs: None,
+            owned_syms: None,
+            mini_debug_info: None,
+            section_addrs: None,
+        }
+    }
+
+    /// Check whether we are dealing with a 32 bit ELF file.
+    fn is_32bit(&mut self) -> Result<bool> {
+        let ehdr = self.ensure_ehdr()?;
+        Ok(ehdr.e_ident[EI_CLASS] == ELFCLASS32)
+    }
+
+    /// Check whether the ELF file uses a byte order different from
+    /// that of the host.
+    fn is_swapped(&mut self) -> Result<bool> {
+        let ehdr = self.ensure_ehdr()?;
+        Ok(ehdr.e_ident[EI_DATA] != ELFDATA_NATIVE)
+    }
+
+    /// Check whether we are dealing with a relocatable object file,
+    /// such as a kernel module.
+    fn is_relocatable(&mut self) -> Result<bool> {
+        let ehdr = self.ensure_ehdr()?;
+        Ok(ehdr.e_type == ET_REL)
+    }
+
+    /// Retrieve the address of the ELF section at index `idx`.
+    ///
+    /// Sections of relocatable object files are not assigned any
+    /// addresses. In order to still be able to tell them apart, we
+    /// assign synthetic addresses to the allocated sections of such a
+    /// file, laying them out one after the other in section header
+    /// order while honoring their alignment requirements.
+    fn section_addr(&mut self, idx: usize) -> Result<u64> {
+        let shdrs = self.ensure_shdrs()?;
+        let section = shdrs
+            .get(idx)
+            .ok_or_invalid_input(|| format!("ELF section index ({idx}) out of bounds"))?;
+
+        if self.is_relocatable()? && section.sh_flags & SHF_ALLOC != 0 {
+            let addrs = self.ensure_section_addrs()?;
+            // SANITY: We have one address per section header and
+            //         checked `idx` against those above.
+            Ok(addrs[idx])
+        } else {
+            Ok(section.sh_addr)
+        }
+    }
+
+    /// Assign synthetic addresses to the allocated sections of a
+    /// relocatable object file.
+    fn ensure_section_addrs(&mut self) -> Result<&[u64]> {
+        if self.section_addrs.is_none() {
+            let shdrs = self.ensure_shdrs()?;
+            let mut next = 0u64;
+            let addrs = shdrs
+                .iter()
+                .map(|shdr| {
+                    if shdr.sh_flags & SHF_ALLOC == 0 {
+                        return Ok(0)
+                    }
+
+                    let align = shdr.sh_addralign.max(1);
+                    let addr = next
+                        .checked_add(align - 1)
+                        .map(|addr| addr / align * align)
+                        .ok_or_invalid_data(|| "ELF section address overflow")?;
+                    // Note that `SHT_NOBITS` sections occupy memory as
+                    // well, even though they don't take up space in the
+                    // file.
+                    next = addr
+                        .checked_add(shdr.sh_size)
+                        .ok_or_invalid_data(|| "ELF section address overflow")?;
+                    Ok(addr)
+                })
+                .collect::<Result<Box<[_]>>>()?;
+            self.section_addrs = Some(addrs);
+        }
+
+        // SANITY: We made sure to set `section_addrs` above.
+        Ok(self.section_addrs.as_deref().unwrap())
+    }
+
+    /// Calculate the address of a symbol, taking into account the
+    /// address of the section it is defined in for relocatable object
+    /// files.
+    fn symbol_addr(&mut self, sym: &Elf64_Sym) -> Result<u64> {
+        if self.is_relocatable()? && sym.st_shndx != SHN_UNDEF && sym.st_shndx < SHN_LORESERVE {
+            let addr = self.section_addr(sym.st_shndx.into())?;
+            Ok(addr.wrapping_add(sym.st_value))
+        } else {
+            Ok(sym.st_value)
+        }
+    }
+
+    /// Apply the relocations in the `SHT_RELA` section at index
+    /// `rela_idx` to `data`.
+    ///
+    /// Only relocations typically found in debug sections, i.e., those
+    /// storing absolute addresses, are supported. Others are ignored,
+    /// with a warning, as the resulting data may be inaccurate.
+    fn apply_relocations(&mut self, rela_idx: usize, data: &mut [u8]) -> Result<()> {
+        let ehdr = self.ensure_ehdr()?;
+        let machine = ehdr.e_machine;
+        let shdrs = self.ensure_shdrs()?;
+        // SANITY: The index was found by searching the section headers.
+        let target_idx = shdrs[rela_idx].sh_info as usize;
+        let target = self.section_name(target_idx)?;
+        if !matches!(machine, EM_X86_64 | EM_AARCH64 | EM_RISCV) {
+            warn!(
+                "relocations for machine {machine} are not supported; ignoring relocations of {target}, which may be inaccurate"
+            );
+            return Ok(())
+        }
+
+        let big_endian = ehdr.e_ident[EI_DATA] == ELFDATA2MSB;
+        let is_32bit = self.is_32bit()?;
+        let swap = self.is_swapped()?;
+        let symtab_idx = shdrs[rela_idx].sh_link as usize;
+
+        let rela_data = self.section_data(rela_idx)?;
+        let (rela_size, sym_size) = if is_32bit {
+            (mem::size_of::<Elf32_Rela>(), mem::size_of::<Elf32_Sym>())
+        } else {
+            (mem::size_of::<Elf64_Rela>(), mem::size_of::<Elf64_Sym>())
+        };
+        let mut rela_storage = None;
+        let relas = read_elf_slice::<Elf32_Rela, Elf64_Rela>(
+            rela_data,
+            rela_data.len() / rela_size,
+            is_32bit,
+            swap,
+            &mut rela_storage,
+        )
+        .ok_or_invalid_data(|| "failed to read relocations")?;
+
+        // Relocations reference symbols by their index, so we can't use
+        // our (sorted) symbol table here.
+        let symtab = self.section_data(symtab_idx)?;
+        let mut sym_storage = None;
+        let syms = read_elf_slice::<Elf32_Sym, _>(
+            symtab,
+            symtab.len() / sym_size,
+            is_32bit,
+            swap,
+            &mut sym_storage,
+        )
+        .ok_or_invalid_data(|| "failed to read symbol table contents")?;
+
+        let mut unsupported = Vec::new();
+        for rela in relas {
+            let size = match (machine, rela.type_()) {
+                (EM_X86_64, R_X86_64_64)
+                | (EM_AARCH64, R_AARCH64_ABS64)
+                | (EM_RISCV, R_RISCV_64) => 8,
+                (EM_X86_64, R_X86_64_32 | R_X86_64_32S)
+                | (EM_AARCH64, R_AARCH64_ABS32)
+                | (EM_RISCV, R_RISCV_32) => 4,
+                (_, typ) => {
+                    if !unsupported.contains(&typ) {
+                        let () = unsupported.push(typ);
+                    }
+                    continue
+                }
+            };
+
+            let sym = syms.get(rela.sym() as usize).ok_or_invalid_data(|| {
+                format!("relocation symbol index ({}) out of bounds", rela.sym())
+            })?;
+            let value = self.symbol_addr(sym)?.wrapping_add(rela.r_addend as u64);
+            let offset = rela.r_offset as usize;
+            let target = data
+                .get_mut(offset..offset.saturating_add(size))
+                .ok_or_invalid_data(|| format!("relocation offset ({offset}) out of bounds"))?;
+
+            match (size, big_endian) {
+                (8, false) => target.copy_from_slice(&value.to_le_bytes()),
+                (8, true) => target.copy_from_slice(&value.to_be_bytes()),
+                (_, false) => target.copy_from_slice(&(value as u32).to_le_bytes()),
+                (_, true) => target.copy_from_slice(&(value as u32).to_be_bytes()),
+            }
         }
+
+        for typ in unsupported {
+            warn!(
+                "ignored relocations of unsupported type {typ} for machine {machine} in {target}, which may be inaccurate"
+            );
+        }
+        Ok(())
     }
 
     /// Retrieve the raw section data for the ELF section at index
@@ -125,15 +523,184 @@ impl<'mmap> Cache<'mmap> {
         Ok(data)
     }
 
+    /// Retrieve the data for the ELF section at index `idx`,
+    /// decompressing it if it is compressed.
+    ///
+    /// Both `SHF_COMPRESSED` sections and legacy GNU style `.zdebug_*`
+    /// sections are supported.
+    fn section_data_decompressed(&mut self, idx: usize) -> Result<&'mmap [u8]> {
+        if let Some(data) = self.decompressed.get(&idx) {
+            // SAFETY: The buffer is owned by the cache and is neither
+            //         modified nor freed while it is alive.
+            return Ok(unsafe { extend_lifetime(&**data) })
+        }
+
+        let shdrs = self.ensure_shdrs()?;
+        let section = shdrs
+            .get(idx)
+            .ok_or_invalid_input(|| format!("ELF section index ({idx}) out of bounds"))?;
+        let mut data = self.section_data(idx)?;
+
+        let decompressed = if section.sh_flags & SHF_COMPRESSED != 0 {
+            let swap = self.is_swapped()?;
+            let (ch_type, ch_size) = if self.is_32bit()? {
+                let chdr = data
+                    .read_pod::<Elf32_Chdr>()
+                    .ok_or_invalid_data(|| "failed to read Elf32_Chdr")?
+                    .byte_swap_if(swap);
+                (chdr.ch_type, chdr.ch_size.into())
+            } else {
+                let chdr = data
+                    .read_pod::<Elf64_Chdr>()
+                    .ok_or_invalid_data(|| "failed to read Elf64_Chdr")?
+                    .byte_swap_if(swap);
+                (chdr.ch_type, chdr.ch_size)
+            };
+            match ch_type {
+                ELFCOMPRESS_ZLIB => Some(decompress_zlib(data, ch_size)?),
+                ELFCOMPRESS_ZSTD => Some(decompress_zstd(data, ch_size)?),
+                typ => {
+                    return Err(Error::with_unsupported(format!(
+                        "ELF section uses unsupported compression format {typ}"
+                    )))
+                }
+            }
+        } else if self.section_name(idx)?.starts_with(".zdebug_") && data.starts_with(b"ZLIB") {
+            // Legacy GNU style compressed sections start with the magic
+            // "ZLIB", followed by the decompressed size as a big endian
+            // 64 bit integer.
+            let size = data
+                .get(4..)
+                .and_then(|mut data| data.read_slice(8))
+                .ok_or_invalid_data(|| "failed to read .zdebug section size")?;
+            // SANITY: `read_slice` returned a slice of eight bytes.
+            let size = u64::from_be_bytes(size.try_into().unwrap());
+            Some(decompress_zlib(&data[12..], size)?)
+        } else {
+            None
+        };
+
+        // Debug sections of relocatable object files (such as kernel
+        // modules) have to have relocations applied before they are
+        // usable.
+        let is_alloc = section.sh_flags & SHF_ALLOC != 0;
+        let decompressed = if self.is_relocatable()? && !is_alloc {
+            let rela_idx = self
+                .ensure_shdrs()?
+                .iter()
+                .position(|shdr| shdr.sh_type == SHT_RELA && shdr.sh_info as usize == idx);
+            if let Some(rela_idx) = rela_idx {
+                let mut buffer = decompressed.unwrap_or_else(|| data.to_vec());
+                let () = self.apply_relocations(rela_idx, &mut buffer)?;
+                Some(buffer)
+            } else {
+                let has_rel = self
+                    .ensure_shdrs()?
+                    .iter()
+                    .any(|shdr| shdr.sh_type == SHT_REL && shdr.sh_info as usize == idx);
+                if has_rel {
+                    warn!(
+                        "SHT_REL relocations are not supported; ignoring relocations of {}, which may be inaccurate",
+                        self.section_name(idx)?
+                    );
+                }
+                decompressed
+            }
+        } else {
+            decompressed
+        };
+
+        let decompressed = if let Some(decompressed) = decompressed {
+            decompressed.into_boxed_slice()
+        } else {
+            return Ok(data)
+        };
+        // SAFETY: The buffer is owned by the cache and is neither
+        //         modified nor freed while it is alive.
+        let data = unsafe { extend_lifetime(&*decompressed) };
+        let _prev = self.decompressed.insert(idx, decompressed);
+        Ok(data)
+    }
+
+    /// Retrieve the parser for the ELF file embedded in the xz
+    /// compressed `.gnu_debugdata` section, if present.
+    ///
+    /// Stripped binaries may carry such an ELF file ("MiniDebugInfo")
+    /// in order to provide a symbol table with all functions.
+    fn ensure_mini_debug_info(&mut self) -> Result<Option<&'mmap ElfParser>> {
+        if let Some(parser) = &self.mini_debug_info {
+            // SAFETY: The parser is owned by the cache and is neither
+            //         modified nor freed while it is alive.
+            return Ok(parser
+                .as_deref()
+                .map(|parser| unsafe { extend_lifetime(parser) }))
+        }
+
+        let parser = match self.find_section(".gnu_debugdata")? {
+            Some(idx) if cfg!(feature = "xz") => {
+                let result = self.section_data(idx).and_then(|data| {
+                    let elf = decompress_xz(data, MAX_MINI_DEBUG_INFO_SIZE)
+                        .context("failed to decompress .gnu_debugdata")?;
+                    Mmap::map_bytes(&elf)
+                });
+                // The symbol tables of the file itself are still usable,
+                // so don't fail because of broken "MiniDebugInfo". We
+                // remember the failure to not retry over and over.
+                match result {
+                    Ok(mmap) => Some(Box::new(ElfParser::from_mmap(mmap))),
+                    Err(err) => {
+                        warn!("failed to load MiniDebugInfo: {err}");
+                        None
+                    }
+                }
+            }
+            Some(_) => {
+                debug!("ignoring .gnu_debugdata section; enable the `xz` feature to use it");
+                None
+            }
+            None => None,
+        };
+
+        let parser = self.mini_debug_info.insert(parser);
+        // SAFETY: The parser is owned by the cache and is neither
+        //         modified nor freed while it is alive.
+        Ok(parser
+            .as_deref()
+            .map(|parser| unsafe { extend_lifetime(parser) }))
+    }
+
     fn ensure_ehdr(&mut self) -> Result<&'mmap Elf64_Ehdr> {
         if let Some(ehdr) = self.ehdr {
             return Ok(ehdr)
         }
 
         let mut elf_data = self.elf_data;
-        let ehdr = elf_data
-            .read_pod_ref::<Elf64_Ehdr>()
-            .ok_or_invalid_data(|| "failed to read Elf64_Ehdr")?;
+        let is_32bit = elf_data.get(EI_CLASS) == Some(&ELFCLASS32);
+        let swap = matches!(elf_data.get(EI_DATA), Some(data) if *data != ELFDATA_NATIVE);
+        let ehdr = if is_32bit || swap {
+            let ehdr = if is_32bit {
+                let ehdr = elf_data
+                    .read_pod::<Elf32_Ehdr>()
+                    .ok_or_invalid_data(|| "failed to read Elf32_Ehdr")?
+                    .byte_swap_if(swap);
+                Elf64_Ehdr::from(&ehdr)
+            } else {
+                elf_data
+                    .read_pod::<Elf64_Ehdr>()
+                    .ok_or_invalid_data(|| "failed to read Elf64_Ehdr")?
+                    .byte_swap()
+            };
+            let ehdr = Box::new(ehdr);
+            // SAFETY: The header is owned by the cache and is neither
+            //         modified nor freed while it is alive.
+            let ehdr_ref = unsafe { extend_lifetime(&*ehdr) };
+            self.owned_ehdr = Some(ehdr);
+            ehdr_ref
+        } else {
+            elf_data
+                .read_pod_ref::<Elf64_Ehdr>()
+                .ok_or_invalid_data(|| "failed to read Elf64_Ehdr")?
+        };
         if !(ehdr.e_ident[0] == 0x7f
             && ehdr.e_ident[1] == b'E'
             && ehdr.e_ident[2] == b'L'
@@ -154,12 +721,20 @@ impl<'mmap> Cache<'mmap> {
         }
 
         let ehdr = self.ensure_ehdr()?;
-        let shdrs = self
+        let data = self
             .elf_data
             .get(ehdr.e_shoff as usize..)
-            .ok_or_invalid_data(|| "Elf64_Ehdr::e_shoff is invalid")?
-            .read_pod_slice_ref::<Elf64_Shdr>(ehdr.e_shnum.into())
-            .ok_or_invalid_data(|| "failed to read Elf64_Shdr")?;
+            .ok_or_invalid_data(|| "Elf64_Ehdr::e_shoff is invalid")?;
+        let is_32bit = self.is_32bit()?;
+        let swap = self.is_swapped()?;
+        let shdrs = read_elf_slice::<Elf32_Shdr, _>(
+            data,
+            ehdr.e_shnum.into(),
+            is_32bit,
+            swap,
+            &mut self.owned_shdrs,
+        )
+        .ok_or_invalid_data(|| "failed to read section headers")?;
         self.shdrs = Some(shdrs);
         Ok(shdrs)
     }
@@ -170,12 +745,20 @@ impl<'mmap> Cache<'mmap> {
         }
 
         let ehdr = self.ensure_ehdr()?;
-        let phdrs = self
+        let data = self
             .elf_data
             .get(ehdr.e_phoff as usize..)
-            .ok_or_invalid_data(|| "Elf64_Ehdr::e_phoff is invalid")?
-            .read_pod_slice_ref::<Elf64_Phdr>(ehdr.e_phnum.into())
-            .ok_or_invalid_data(|| "failed to read Elf64_Phdr")?;
+            .ok_or_invalid_data(|| "Elf64_Ehdr::e_phoff is invalid")?;
+        let is_32bit = self.is_32bit()?;
+        let swap = self.is_swapped()?;
+        let phdrs = read_elf_slice::<Elf32_Phdr, _>(
+            data,
+            ehdr.e_phnum.into(),
+            is_32bit,
+            swap,
+            &mut self.owned_phdrs,
+        )
+        .ok_or_invalid_data(|| "failed to read program headers")?;
         self.phdrs = Some(phdrs);
         Ok(phdrs)
     }
@@ -254,21 +837,50 @@ impl<'mmap> Cache<'mmap> {
             self.symtab = Some(Box::default());
             return Ok(())
         };
-        let mut symtab = self.section_data(idx)?;
+        let symtab = self.section_data(idx)?;
+        let sym_size = if self.is_32bit()? {
+            mem::size_of::<Elf32_Sym>()
+        } else {
+            mem::size_of::<Elf64_Sym>()
+        };
 
-        if symtab.len() % mem::size_of::<Elf64_Sym>() != 0 {
+        if symtab.len() % sym_size != 0 {
             return Err(Error::with_invalid_data(
                 "size of symbol table section is invalid",
             ))
         }
 
-        let count = symtab.len() / mem::size_of::<Elf64_Sym>();
-        let mut symtab = symtab
-            .read_pod_slice_ref::<Elf64_Sym>(count)
-            .ok_or_invalid_data(|| "failed to read symbol table contents")?
-            .iter()
-            .collect::<Vec<&Elf64_Sym>>()
-            .into_boxed_slice();
+        let count = symtab.len() / sym_size;
+        let swap = self.is_swapped()?;
+        let syms = read_elf_slice::<Elf32_Sym, _>(
+            symtab,
+            count,
+            self.is_32bit()?,
+            swap,
+            &mut self.owned_syms,
+        )
+        .ok_or_invalid_data(|| "failed to read symbol table contents")?;
+        let syms = if self.is_relocatable()? {
+            // Symbol values are relative to the section they are defined
+            // in. Make them "absolute" to be able to tell apart symbols
+            // from different sections.
+            let syms = syms
+                .iter()
+                .map(|sym| {
+                    let mut sym = sym.clone();
+                    sym.st_value = self.symbol_addr(&sym)?;
+                    Ok(sym)
+                })
+                .collect::<Result<Box<[_]>>>()?;
+            // SAFETY: The symbols are owned by the cache and are neither
+            //         modified nor freed while it is alive.
+            let slice = unsafe { extend_lifetime(&*syms) };
+            self.owned_syms = Some(syms);
+            slice
+        } else {
+            syms
+        };
+        let mut symtab = syms.iter().collect::<Vec<&Elf64_Sym>>().into_boxed_slice();
         // Order symbols by address and those with equal address descending by
         // size.
         let () = symtab.sort_by(|sym1, sym2| {
@@ -343,7 +955,11 @@ impl Debug for Cache<'_> {
 }
 
 
-/// A parser for ELF64 files.
+/// A parser for ELF files.
+///
+/// Both 32 bit and 64 bit ELF files of either byte order are supported.
+/// The contents of all of them are represented using the 64 bit
+/// structures in host byte order.
 #[derive(Debug)]
 pub(crate) struct ElfParser {
     /// A cache for relevant parts of the ELF file.
@@ -351,7 +967,7 @@ pub(crate) struct ElfParser {
     ///         this member. Rather, they should never outlive `self`.
     ///         Furthermore, this member has to be listed before `mmap`
     ///         to make sure we never end up with a dangling reference.
-    cache: RefCell<Cache<'static>>,
+    cache: Mutex<Cache<'static>>,
     /// The memory mapped file.
     _mmap: Mmap,
 }
@@ -372,7 +988,7 @@ impl ElfParser {
 
         let parser = ElfParser {
             _mmap: mmap,
-            cache: RefCell::new(Cache::new(elf_data)),
+            cache: Mutex::new(Cache::new(elf_data)),
         };
         parser
     }
@@ -390,21 +1006,44 @@ impl ElfParser {
 
     /// Retrieve the data corresponding to the ELF section at index `idx`.
     pub fn section_data(&self, idx: usize) -> Result<&[u8]> {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         cache.section_data(idx)
     }
 
+    /// Retrieve the data corresponding to the ELF section at index `idx`,
+    /// decompressing it if necessary.
+    ///
+    /// Decompressed data is cached for the lifetime of the parser.
+    pub fn section_data_decompressed(&self, idx: usize) -> Result<&[u8]> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.section_data_decompressed(idx)
+    }
+
     /// Find the section of a given name.
     ///
     /// This function return the index of the section if found.
     pub fn find_section(&self, name: &str) -> Result<Option<usize>> {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let index = cache.find_section(name)?;
         Ok(index)
     }
 
+    /// Find the DWARF section of a given name (e.g., `.debug_info`).
+    ///
+    /// Legacy GNU style compressed sections, which use a `.zdebug_`
+    /// prefix instead of `.debug_`, are considered as well.
+    pub fn find_debug_section(&self, name: &str) -> Result<Option<usize>> {
+        let mut index = self.find_section(name)?;
+        if index.is_none() {
+            if let Some(suffix) = name.strip_prefix(".debug_") {
+                index = self.find_section(&format!(".zdebug_{suffix}"))?;
+            }
+        }
+        Ok(index)
+    }
+
     pub fn find_sym(&self, addr: Addr, st_type: u8) -> Result<Option<(&str, Addr)>> {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let strtab = cache.ensure_strtab()?;
         let () = cache.ensure_symtab()?;
         // SANITY: The above `ensure_symtab` ensures we have `symtab`
@@ -419,7 +1058,7 @@ impl ElfParser {
             return Err(Error::with_unsupported("Not implemented"))
         }
 
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let () = cache.ensure_symtab()?;
         let () = cache.ensure_str2symtab()?;
         // SANITY: The above `ensure_symtab` ensures we have `symtab`
@@ -459,28 +1098,71 @@ impl ElfParser {
 
     #[cfg(test)]
     fn get_symbol_name(&self, idx: usize) -> Result<&str> {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let strtab = cache.ensure_strtab()?;
         let sym = cache.symbol(idx)?;
         let name = symbol_name(strtab, sym)?;
         Ok(name)
     }
 
+    /// Retrieve the parser for the ELF file embedded in the
+    /// `.gnu_debugdata` section ("MiniDebugInfo"), if present.
+    pub(crate) fn mini_debug_info(&self) -> Result<Option<&ElfParser>> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.ensure_mini_debug_info()
+    }
+
+    /// Check whether we are dealing with a 32 bit ELF file.
+    pub(crate) fn is_32bit(&self) -> Result<bool> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.is_32bit()
+    }
+
+    /// Check whether we are dealing with a core file.
+    pub(crate) fn is_core(&self) -> Result<bool> {
+        let mut cache = self.cache.lock().unwrap();
+        let ehdr = cache.ensure_ehdr()?;
+        Ok(ehdr.e_type == ET_CORE)
+    }
+
+    /// Check whether the ELF file uses a byte order different from
+    /// that of the host.
+    pub(crate) fn is_swapped(&self) -> Result<bool> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.is_swapped()
+    }
+
+    /// Check whether the ELF file uses big-endian byte order.
+    pub(crate) fn is_big_endian(&self) -> Result<bool> {
+        let mut cache = self.cache.lock().unwrap();
+        let ehdr = cache.ensure_ehdr()?;
+        Ok(ehdr.e_ident[EI_DATA] == ELFDATA2MSB)
+    }
+
+    /// Retrieve the address of the ELF section at index `idx`.
+    ///
+    /// For relocatable object files, allocated sections are considered
+    /// to be located at their file offset.
+    pub(crate) fn section_addr(&self, idx: usize) -> Result<u64> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.section_addr(idx)
+    }
+
     pub(crate) fn section_headers(&self) -> Result<&[Elf64_Shdr]> {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let phdrs = cache.ensure_shdrs()?;
         Ok(phdrs)
     }
 
     pub(crate) fn program_headers(&self) -> Result<&[Elf64_Phdr]> {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let phdrs = cache.ensure_phdrs()?;
         Ok(phdrs)
     }
 
     #[cfg(test)]
     fn pick_symtab_addr(&self) -> (&str, Addr) {
-        let mut cache = self.cache.borrow_mut();
+        let mut cache = self.cache.lock().unwrap();
         let () = cache.ensure_symtab().unwrap();
         let symtab = cache.symtab.as_ref().unwrap();
 
@@ -503,6 +1185,9 @@ mod tests {
     use super::*;
 
     use std::env;
+    use std::fs::read as read_file;
+
+    use crate::ErrorKind;
 
     use test_log::test;
 
@@ -517,6 +1202,42 @@ mod tests {
         assert!(parser.find_section(".shstrtab").is_ok());
     }
 
+    /// Check that we decompress compressed sections and that the result
+    /// matches the uncompressed section.
+    #[test]
+    fn section_decompression() {
+        let data_dir = Path::new(&env!("CARGO_MANIFEST_DIR")).join("data");
+        let parser = ElfParser::open(&data_dir.join("test-stable-addresses.bin")).unwrap();
+        let idx = parser.find_section(".debug_info").unwrap().unwrap();
+        let expected = parser.section_data(idx).unwrap();
+
+        for (file, name) in [
+            (
+                "test-stable-addresses-compressed-debug-zlib.bin",
+                ".debug_info",
+            ),
+            (
+                "test-stable-addresses-compressed-debug-zlib-gnu.bin",
+                ".zdebug_info",
+            ),
+            (
+                "test-stable-addresses-compressed-debug-zstd.bin",
+                ".debug_info",
+            ),
+        ] {
+            let parser = ElfParser::open(&data_dir.join(file)).unwrap();
+            let idx = parser.find_section(name).unwrap().unwrap();
+            let raw = parser.section_data(idx).unwrap();
+            assert_ne!(raw, expected);
+
+            let data = parser.section_data_decompressed(idx).unwrap();
+            assert_eq!(data, expected);
+            // Subsequent calls should be served from the cache.
+            let cached = parser.section_data_decompressed(idx).unwrap();
+            assert_eq!(cached.as_ptr(), data.as_ptr());
+        }
+    }
+
     #[test]
     fn test_elf64_symtab() {
         let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
@@ -574,6 +1295,193 @@ mod tests {
         assert_ne!(syms[0].addr, syms[1].addr);
     }
 
+    /// Check that we validate the decompressed size of zstd data
+    /// without trusting the expected size up front.
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_decompression_size() {
+        let data = b"Hello, world!".repeat(16);
+        let compressed = zstd::bulk::compress(&data, 0).unwrap();
+
+        let decompressed = decompress_zstd(&compressed, data.len() as u64).unwrap();
+        assert_eq!(decompressed, data);
+
+        let err = decompress_zstd(&compressed, u64::MAX).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = decompress_zstd(&compressed, data.len() as u64 - 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Check that we refuse to decompress xz data exceeding the provided
+    /// maximum size.
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_decompression_size() {
+        use std::io::Read as _;
+        use xz2::read::XzEncoder;
+
+        let data = b"Hello, world!".repeat(16);
+        let mut compressed = Vec::new();
+        let _count = XzEncoder::new(data.as_slice(), 6)
+            .read_to_end(&mut compressed)
+            .unwrap();
+
+        let decompressed = decompress_xz(&compressed, data.len() as u64).unwrap();
+        assert_eq!(decompressed, data);
+
+        let err = decompress_xz(&compressed, data.len() as u64 - 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Check that we can look up symbols in the "MiniDebugInfo" of a
+    /// stripped binary.
+    #[cfg(feature = "xz")]
+    #[test]
+    fn mini_debug_info_lookup() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-mini-debug-info.bin");
+        let parser = ElfParser::open(&path).unwrap();
+        assert_eq!(parser.find_sym(0x2000105, STT_FUNC).unwrap(), None);
+
+        let mini_parser = parser.mini_debug_info().unwrap().unwrap();
+        let (name, addr) = mini_parser.find_sym(0x2000105, STT_FUNC).unwrap().unwrap();
+        assert_eq!(name, "factorial");
+        assert_eq!(addr, 0x2000100);
+
+        // Binaries without a `.gnu_debugdata` section don't have
+        // "MiniDebugInfo".
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let parser = ElfParser::open(&path).unwrap();
+        assert!(parser.mini_debug_info().unwrap().is_none());
+    }
+
+    /// Check that corrupted "MiniDebugInfo" does not prevent us from
+    /// using the file's own symbols.
+    #[cfg(feature = "xz")]
+    #[test]
+    fn mini_debug_info_corrupted() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-mini-debug-info.bin");
+        let mut data = read_file(&path).unwrap();
+        let parser = ElfParser::open(&path).unwrap();
+        let idx = parser.find_section(".gnu_debugdata").unwrap().unwrap();
+        let shdr = &parser.section_headers().unwrap()[idx];
+        let start = shdr.sh_offset as usize;
+        let end = start + shdr.sh_size as usize;
+        let () = data[start..end].iter_mut().for_each(|byte| *byte = 0xff);
+
+        let parser = ElfParser::from_mmap(Mmap::map_bytes(&data).unwrap());
+        assert!(parser.mini_debug_info().unwrap().is_none());
+        // The failure should be remembered.
+        assert!(parser.mini_debug_info().unwrap().is_none());
+        assert_eq!(parser.find_sym(0x2000105, STT_FUNC).unwrap(), None);
+    }
+
+    /// Make sure that we can parse 32 bit ELF files.
+    #[test]
+    fn elf32_parsing() {
+        let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-32.bin");
+
+        let parser = ElfParser::open(bin_name.as_ref()).unwrap();
+        assert!(parser.find_section(".debug_info").unwrap().is_some());
+        assert!(!parser.section_headers().unwrap().is_empty());
+        assert!(!parser.program_headers().unwrap().is_empty());
+
+        let (name, addr) = parser.find_sym(0x2000100, STT_FUNC).unwrap().unwrap();
+        assert_eq!(name, "factorial");
+        assert_eq!(addr, 0x2000100);
+
+        let opts = FindAddrOpts::default();
+        let syms = parser.find_addr("factorial", &opts).unwrap();
+        assert_eq!(syms.len(), 1);
+        let sym = &syms[0];
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.addr, 0x2000100);
+        assert_ne!(sym.size, 0);
+    }
+
+    /// Check that we can parse a big-endian ELF file.
+    #[test]
+    fn big_endian_parsing() {
+        let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-big-endian.bin");
+
+        let parser = ElfParser::open(bin_name.as_ref()).unwrap();
+        assert!(parser.is_big_endian().unwrap());
+        assert_eq!(parser.is_swapped().unwrap(), cfg!(target_endian = "little"));
+        assert!(parser.find_section(".debug_info").unwrap().is_some());
+        assert!(!parser.section_headers().unwrap().is_empty());
+        assert!(!parser.program_headers().unwrap().is_empty());
+
+        let (name, addr) = parser.find_sym(0x2000100, STT_FUNC).unwrap().unwrap();
+        assert_eq!(name, "factorial");
+        assert_eq!(addr, 0x2000100);
+
+        let opts = FindAddrOpts::default();
+        let syms = parser.find_addr("factorial", &opts).unwrap();
+        assert_eq!(syms.len(), 1);
+        let sym = &syms[0];
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.addr, 0x2000100);
+        assert_ne!(sym.size, 0);
+    }
+
+    /// Check that we can look up symbols in a relocatable object file,
+    /// with its allocated sections being assigned synthetic,
+    /// non-overlapping addresses.
+    #[test]
+    fn relocatable_parsing() {
+        let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-rel.bin");
+
+        let parser = ElfParser::open(bin_name.as_ref()).unwrap();
+        let factorial = parser.find_section(".text.factorial").unwrap().unwrap();
+        let main = parser.find_section(".text.main").unwrap().unwrap();
+        let shdrs = parser.section_headers().unwrap();
+        let factorial_addr = parser.section_addr(factorial).unwrap() as Addr;
+        let main_addr = parser.section_addr(main).unwrap() as Addr;
+        assert_ne!(factorial_addr, main_addr);
+
+        let mut ranges = shdrs
+            .iter()
+            .enumerate()
+            .filter(|(_idx, shdr)| shdr.sh_flags & SHF_ALLOC != 0)
+            .map(|(idx, shdr)| {
+                let addr = parser.section_addr(idx).unwrap();
+                assert_eq!(addr % shdr.sh_addralign.max(1), 0);
+                addr..addr + shdr.sh_size
+            })
+            .collect::<Vec<_>>();
+        assert!(ranges.len() > 1);
+        let () = ranges.sort_by_key(|range| range.start);
+        for pair in ranges.windows(2) {
+            assert!(pair[0].end <= pair[1].start, "{pair:?}");
+        }
+
+        let (name, addr) = parser
+            .find_sym(factorial_addr + 4, STT_FUNC)
+            .unwrap()
+            .unwrap();
+        assert_eq!(name, "factorial");
+        assert_eq!(addr, factorial_addr);
+
+        let (name, addr) = parser.find_sym(main_addr + 4, STT_FUNC).unwrap().unwrap();
+        assert_eq!(name, "main");
+        assert_eq!(addr, main_addr);
+
+        // Non-allocated sections keep their (zero) address.
+        let debug_info = parser.find_section(".debug_info").unwrap().unwrap();
+        assert_eq!(parser.section_addr(debug_info).unwrap(), 0);
+    }
+
     /// Make sure that we do not report a symbol if there is no conceivable
     /// match.
     #[test]
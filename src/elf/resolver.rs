@@ -6,8 +6,10 @@ use std::path::PathBuf;
 
 use crate::inspect::FindAddrOpts;
 use crate::inspect::SymInfo;
+use crate::log::warn;
 use crate::symbolize::AddrLineInfo;
 use crate::Addr;
+use crate::IntInlinedFn;
 use crate::IntSym;
 use crate::Result;
 use crate::SrcLang;
@@ -40,7 +42,7 @@ impl ElfResolver {
         })
     }
 
-    fn get_parser(&self) -> &ElfParser {
+    pub(crate) fn get_parser(&self) -> &ElfParser {
         match &self.backend {
             #[cfg(feature = "dwarf")]
             ElfBackend::Dwarf(dwarf) => dwarf.get_parser(),
@@ -58,14 +60,36 @@ impl SymResolver for ElfResolver {
     #[cfg_attr(feature = "tracing", crate::log::instrument(fields(addr = format_args!("{addr:#x}"))))]
     fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
         let parser = self.get_parser();
-        if let Some((name, addr)) = parser.find_sym(addr, STT_FUNC)? {
+        let sym = parser.find_sym(addr, STT_FUNC)?;
+        // Stripped binaries may carry additional symbols in the form of
+        // "MiniDebugInfo". Of the two candidates, the one closer to the
+        // address is the better match.
+        let sym = if let Some(mini_parser) = parser.mini_debug_info()? {
+            let mini_sym = mini_parser.find_sym(addr, STT_FUNC).unwrap_or_else(|err| {
+                warn!("failed to look up {addr:#x} in MiniDebugInfo: {err}");
+                None
+            });
+            match (sym, mini_sym) {
+                (Some(sym), Some(mini_sym)) if mini_sym.1 > sym.1 => Some(mini_sym),
+                (sym, mini_sym) => sym.or(mini_sym),
+            }
+        } else {
+            sym
+        };
+
+        if let Some((name, addr)) = sym {
             // ELF does not carry any source code language information.
             let lang = SrcLang::Unknown;
             // We found the address in ELF.
             // TODO: Long term we probably want a different heuristic here, as
             //       there can be valid differences between the two formats
             //       (e.g., DWARF could contain more symbols).
-            return Ok(vec![IntSym { name, addr, lang }])
+            return Ok(vec![IntSym {
+                name,
+                addr,
+                lang,
+                module: None,
+            }])
         }
 
         match &self.backend {
@@ -77,7 +101,19 @@ impl SymResolver for ElfResolver {
 
     fn find_addr(&self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo>> {
         let parser = self.get_parser();
-        let syms = parser.find_addr(name, opts)?;
+        let mut syms = parser.find_addr(name, opts)?;
+        if let Some(mini_parser) = parser.mini_debug_info()? {
+            let mini_syms = mini_parser.find_addr(name, opts).unwrap_or_else(|err| {
+                warn!("failed to look up {name} in MiniDebugInfo: {err}");
+                Vec::new()
+            });
+            for sym in mini_syms {
+                if !syms.iter().any(|known| known.addr == sym.addr) {
+                    let () = syms.push(sym);
+                }
+            }
+        }
+
         if !syms.is_empty() {
             // We found symbols in ELF and DWARF wouldn't add information on
             // top. So just roll with that.
@@ -105,6 +141,20 @@ impl SymResolver for ElfResolver {
         Ok(None)
     }
 
+    #[cfg(feature = "dwarf")]
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        if let ElfBackend::Dwarf(dwarf) = &self.backend {
+            dwarf.find_inlined_fns(addr)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    #[cfg(not(feature = "dwarf"))]
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        Ok(Vec::new())
+    }
+
     /// Find the file offset of the symbol at address `addr`.
     // TODO: See if we could make this a constant time calculation by supplying
     //       the ELF symbol index (and potentially an offset from it) [this will
@@ -141,7 +191,7 @@ mod tests {
     use super::*;
 
     use std::path::Path;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
 
     /// Check that we fail finding an offset for an address not
@@ -152,7 +202,7 @@ mod tests {
             .join("data")
             .join("test-stable-addresses-no-dwarf.bin");
         let elf = ElfParser::open(&path).unwrap();
-        let backend = ElfBackend::Elf(Rc::new(elf));
+        let backend = ElfBackend::Elf(Arc::new(elf));
         let resolver = ElfResolver::with_backend(&path, backend).unwrap();
 
         assert_eq!(resolver.addr_file_off(0x0), None);
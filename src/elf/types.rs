@@ -1,13 +1,64 @@
 const EI_NIDENT: usize = 16;
 
+pub(crate) const EI_CLASS: usize = 4;
+pub(crate) const ELFCLASS32: u8 = 1;
+pub(crate) const ELFCLASS64: u8 = 2;
+
+pub(crate) const EI_DATA: usize = 5;
+pub(crate) const ELFDATA2LSB: u8 = 1;
+pub(crate) const ELFDATA2MSB: u8 = 2;
+
+/// The `EI_DATA` value matching the host's byte order.
+#[cfg(target_endian = "little")]
+pub(crate) const ELFDATA_NATIVE: u8 = ELFDATA2LSB;
+#[cfg(target_endian = "big")]
+pub(crate) const ELFDATA_NATIVE: u8 = ELFDATA2MSB;
+
+
+/// A trait for ELF structures that can have their byte order swapped.
+pub(crate) trait ByteSwap: Sized {
+    /// Swap the byte order of all multi-byte members.
+    fn byte_swap(self) -> Self;
+
+    /// Swap the byte order of all multi-byte members if `swap` is
+    /// `true`.
+    fn byte_swap_if(self, swap: bool) -> Self {
+        if swap {
+            self.byte_swap()
+        } else {
+            self
+        }
+    }
+}
+
+macro_rules! impl_byte_swap {
+    ($ty:ty, $($field:ident),* $(,)?) => {
+        impl ByteSwap for $ty {
+            fn byte_swap(mut self) -> Self {
+                $(
+                    self.$field = self.$field.swap_bytes();
+                )*
+                self
+            }
+        }
+    };
+}
+
+type Elf32_Addr = u32;
+type Elf32_Half = u16;
+type Elf32_Off = u32;
+type Elf32_Word = u32;
+
 type Elf64_Addr = u64;
 type Elf64_Half = u16;
 type Elf64_Off = u64;
 type Elf64_Word = u32;
 type Elf64_Xword = u64;
 
+pub(crate) const ET_REL: u16 = 1;
 pub(crate) const ET_EXEC: u16 = 2;
 pub(crate) const ET_DYN: u16 = 3;
+pub(crate) const ET_CORE: u16 = 4;
 
 #[repr(C)]
 pub(crate) struct Elf64_Ehdr {
@@ -30,7 +81,84 @@ pub(crate) struct Elf64_Ehdr {
 // SAFETY: `Elf64_Ehdr` is valid for any bit pattern.
 unsafe impl crate::util::Pod for Elf64_Ehdr {}
 
+impl_byte_swap!(
+    Elf64_Ehdr,
+    e_type,
+    e_machine,
+    e_version,
+    e_entry,
+    e_phoff,
+    e_shoff,
+    e_flags,
+    e_ehsize,
+    e_phentsize,
+    e_phnum,
+    e_shentsize,
+    e_shnum,
+    e_shstrndx,
+);
+
+#[repr(C)]
+pub(crate) struct Elf32_Ehdr {
+    pub e_ident: [u8; EI_NIDENT], /* ELF "magic number" */
+    pub e_type: Elf32_Half,
+    pub e_machine: Elf32_Half,
+    pub e_version: Elf32_Word,
+    pub e_entry: Elf32_Addr, /* Entry point virtual address */
+    pub e_phoff: Elf32_Off,  /* Program header table file offset */
+    pub e_shoff: Elf32_Off,  /* Section header table file offset */
+    pub e_flags: Elf32_Word,
+    pub e_ehsize: Elf32_Half,
+    pub e_phentsize: Elf32_Half,
+    pub e_phnum: Elf32_Half,
+    pub e_shentsize: Elf32_Half,
+    pub e_shnum: Elf32_Half,
+    pub e_shstrndx: Elf32_Half,
+}
+
+// SAFETY: `Elf32_Ehdr` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf32_Ehdr {}
+
+impl_byte_swap!(
+    Elf32_Ehdr,
+    e_type,
+    e_machine,
+    e_version,
+    e_entry,
+    e_phoff,
+    e_shoff,
+    e_flags,
+    e_ehsize,
+    e_phentsize,
+    e_phnum,
+    e_shentsize,
+    e_shnum,
+    e_shstrndx,
+);
+
+impl From<&Elf32_Ehdr> for Elf64_Ehdr {
+    fn from(ehdr: &Elf32_Ehdr) -> Self {
+        Self {
+            e_ident: ehdr.e_ident,
+            e_type: ehdr.e_type,
+            e_machine: ehdr.e_machine,
+            e_version: ehdr.e_version,
+            e_entry: ehdr.e_entry.into(),
+            e_phoff: ehdr.e_phoff.into(),
+            e_shoff: ehdr.e_shoff.into(),
+            e_flags: ehdr.e_flags,
+            e_ehsize: ehdr.e_ehsize,
+            e_phentsize: ehdr.e_phentsize,
+            e_phnum: ehdr.e_phnum,
+            e_shentsize: ehdr.e_shentsize,
+            e_shnum: ehdr.e_shnum,
+            e_shstrndx: ehdr.e_shstrndx,
+        }
+    }
+}
+
 pub(crate) const PT_LOAD: u32 = 1;
+pub(crate) const PT_NOTE: u32 = 4;
 
 #[repr(C)]
 pub(crate) struct Elf64_Phdr {
@@ -47,7 +175,47 @@ pub(crate) struct Elf64_Phdr {
 // SAFETY: `Elf64_Phdr` is valid for any bit pattern.
 unsafe impl crate::util::Pod for Elf64_Phdr {}
 
+impl_byte_swap!(
+    Elf64_Phdr, p_type, p_flags, p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_align,
+);
+
+#[repr(C)]
+pub(crate) struct Elf32_Phdr {
+    pub p_type: Elf32_Word,
+    pub p_offset: Elf32_Off,  /* Segment file offset */
+    pub p_vaddr: Elf32_Addr,  /* Segment virtual address */
+    pub p_paddr: Elf32_Addr,  /* Segment physical address */
+    pub p_filesz: Elf32_Word, /* Segment size in file */
+    pub p_memsz: Elf32_Word,  /* Segment size in memory */
+    pub p_flags: Elf32_Word,
+    pub p_align: Elf32_Word, /* Segment alignment, file & memory */
+}
+
+// SAFETY: `Elf32_Phdr` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf32_Phdr {}
+
+impl_byte_swap!(
+    Elf32_Phdr, p_type, p_flags, p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_align,
+);
+
+impl From<&Elf32_Phdr> for Elf64_Phdr {
+    fn from(phdr: &Elf32_Phdr) -> Self {
+        Self {
+            p_type: phdr.p_type,
+            p_flags: phdr.p_flags,
+            p_offset: phdr.p_offset.into(),
+            p_vaddr: phdr.p_vaddr.into(),
+            p_paddr: phdr.p_paddr.into(),
+            p_filesz: phdr.p_filesz.into(),
+            p_memsz: phdr.p_memsz.into(),
+            p_align: phdr.p_align.into(),
+        }
+    }
+}
+
 pub(crate) const PF_X: Elf64_Word = 1;
+pub(crate) const PF_W: Elf64_Word = 2;
+pub(crate) const PF_R: Elf64_Word = 4;
 
 #[repr(C)]
 pub(crate) struct Elf64_Shdr {
@@ -66,9 +234,77 @@ pub(crate) struct Elf64_Shdr {
 // SAFETY: `Elf64_Shdr` is valid for any bit pattern.
 unsafe impl crate::util::Pod for Elf64_Shdr {}
 
+impl_byte_swap!(
+    Elf64_Shdr,
+    sh_name,
+    sh_type,
+    sh_flags,
+    sh_addr,
+    sh_offset,
+    sh_size,
+    sh_link,
+    sh_info,
+    sh_addralign,
+    sh_entsize,
+);
+
+#[repr(C)]
+pub(crate) struct Elf32_Shdr {
+    pub sh_name: Elf32_Word,      /* Section name, index in string tbl */
+    pub sh_type: Elf32_Word,      /* Type of section */
+    pub sh_flags: Elf32_Word,     /* Miscellaneous section attributes */
+    pub sh_addr: Elf32_Addr,      /* Section virtual addr at execution */
+    pub sh_offset: Elf32_Off,     /* Section file offset */
+    pub sh_size: Elf32_Word,      /* Size of section in bytes */
+    pub sh_link: Elf32_Word,      /* Index of another section */
+    pub sh_info: Elf32_Word,      /* Additional section information */
+    pub sh_addralign: Elf32_Word, /* Section alignment */
+    pub sh_entsize: Elf32_Word,   /* Entry size if section holds table */
+}
+
+// SAFETY: `Elf32_Shdr` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf32_Shdr {}
+
+impl_byte_swap!(
+    Elf32_Shdr,
+    sh_name,
+    sh_type,
+    sh_flags,
+    sh_addr,
+    sh_offset,
+    sh_size,
+    sh_link,
+    sh_info,
+    sh_addralign,
+    sh_entsize,
+);
+
+impl From<&Elf32_Shdr> for Elf64_Shdr {
+    fn from(shdr: &Elf32_Shdr) -> Self {
+        Self {
+            sh_name: shdr.sh_name,
+            sh_type: shdr.sh_type,
+            sh_flags: shdr.sh_flags.into(),
+            sh_addr: shdr.sh_addr.into(),
+            sh_offset: shdr.sh_offset.into(),
+            sh_size: shdr.sh_size.into(),
+            sh_link: shdr.sh_link,
+            sh_info: shdr.sh_info,
+            sh_addralign: shdr.sh_addralign.into(),
+            sh_entsize: shdr.sh_entsize.into(),
+        }
+    }
+}
+
+pub(crate) const SHF_ALLOC: Elf64_Xword = 0x2;
+pub(crate) const SHF_COMPRESSED: Elf64_Xword = 0x800;
+
 pub(crate) const SHN_UNDEF: u16 = 0;
+pub(crate) const SHN_LORESERVE: u16 = 0xff00;
 
+pub(crate) const SHT_RELA: Elf64_Word = 4;
 pub(crate) const SHT_NOTE: Elf64_Word = 7;
+pub(crate) const SHT_REL: Elf64_Word = 9;
 
 pub(crate) const STT_FUNC: u8 = 2;
 
@@ -93,8 +329,131 @@ impl Elf64_Sym {
 // SAFETY: `Elf64_Sym` is valid for any bit pattern.
 unsafe impl crate::util::Pod for Elf64_Sym {}
 
+impl_byte_swap!(Elf64_Sym, st_name, st_shndx, st_value, st_size,);
+
+#[derive(Clone)]
+#[repr(C)]
+pub(crate) struct Elf32_Sym {
+    pub st_name: Elf32_Word,  /* Symbol name, index in string tbl */
+    pub st_value: Elf32_Addr, /* Value of the symbol */
+    pub st_size: Elf32_Word,  /* Associated symbol size */
+    pub st_info: u8,          /* Type and binding attributes */
+    pub st_other: u8,         /* No defined meaning, 0 */
+    pub st_shndx: Elf32_Half, /* Associated section index */
+}
+
+// SAFETY: `Elf32_Sym` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf32_Sym {}
+
+impl_byte_swap!(Elf32_Sym, st_name, st_shndx, st_value, st_size,);
+
+impl From<&Elf32_Sym> for Elf64_Sym {
+    fn from(sym: &Elf32_Sym) -> Self {
+        Self {
+            st_name: sym.st_name,
+            st_info: sym.st_info,
+            st_other: sym.st_other,
+            st_shndx: sym.st_shndx,
+            st_value: sym.st_value.into(),
+            st_size: sym.st_size.into(),
+        }
+    }
+}
+
+pub(crate) const EM_X86_64: u16 = 62;
+pub(crate) const EM_AARCH64: u16 = 183;
+pub(crate) const EM_RISCV: u16 = 243;
+
+pub(crate) const R_X86_64_64: u32 = 1;
+pub(crate) const R_X86_64_32: u32 = 10;
+pub(crate) const R_X86_64_32S: u32 = 11;
+pub(crate) const R_AARCH64_ABS64: u32 = 257;
+pub(crate) const R_AARCH64_ABS32: u32 = 258;
+pub(crate) const R_RISCV_32: u32 = 1;
+pub(crate) const R_RISCV_64: u32 = 2;
+
+#[repr(C)]
+pub(crate) struct Elf64_Rela {
+    pub r_offset: Elf64_Addr, /* Location at which to apply the action */
+    pub r_info: Elf64_Xword,  /* Index and type of relocation */
+    pub r_addend: i64,        /* Constant addend used to compute value */
+}
+
+impl Elf64_Rela {
+    /// Extract the index of the symbol the relocation refers to.
+    pub fn sym(&self) -> u32 {
+        (self.r_info >> 32) as u32
+    }
+
+    /// Extract the relocation type, typically represented by an R_*
+    /// constant.
+    pub fn type_(&self) -> u32 {
+        (self.r_info & 0xffffffff) as u32
+    }
+}
+
+// SAFETY: `Elf64_Rela` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf64_Rela {}
+
+impl_byte_swap!(Elf64_Rela, r_offset, r_info, r_addend,);
+
+#[repr(C)]
+pub(crate) struct Elf32_Rela {
+    pub r_offset: Elf32_Addr, /* Location at which to apply the action */
+    pub r_info: Elf32_Word,   /* Index and type of relocation */
+    pub r_addend: i32,        /* Constant addend used to compute value */
+}
+
+// SAFETY: `Elf32_Rela` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf32_Rela {}
+
+impl_byte_swap!(Elf32_Rela, r_offset, r_info, r_addend,);
+
+impl From<&Elf32_Rela> for Elf64_Rela {
+    fn from(rela: &Elf32_Rela) -> Self {
+        let sym = u64::from(rela.r_info >> 8);
+        let type_ = u64::from(rela.r_info & 0xff);
+        Self {
+            r_offset: rela.r_offset.into(),
+            r_info: (sym << 32) | type_,
+            r_addend: rela.r_addend.into(),
+        }
+    }
+}
+
+pub(crate) const ELFCOMPRESS_ZLIB: Elf64_Word = 1;
+pub(crate) const ELFCOMPRESS_ZSTD: Elf64_Word = 2;
+
+#[repr(C)]
+pub(crate) struct Elf32_Chdr {
+    pub ch_type: Elf32_Word,      /* Compression format */
+    pub ch_size: Elf32_Word,      /* Uncompressed data size */
+    pub ch_addralign: Elf32_Word, /* Uncompressed data alignment */
+}
+
+// SAFETY: `Elf32_Chdr` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf32_Chdr {}
+
+impl_byte_swap!(Elf32_Chdr, ch_type, ch_size, ch_addralign,);
+
+#[repr(C)]
+pub(crate) struct Elf64_Chdr {
+    pub ch_type: Elf64_Word, /* Compression format */
+    pub ch_reserved: Elf64_Word,
+    pub ch_size: Elf64_Xword,      /* Uncompressed data size */
+    pub ch_addralign: Elf64_Xword, /* Uncompressed data alignment */
+}
+
+// SAFETY: `Elf64_Chdr` is valid for any bit pattern.
+unsafe impl crate::util::Pod for Elf64_Chdr {}
+
+impl_byte_swap!(Elf64_Chdr, ch_type, ch_reserved, ch_size, ch_addralign,);
+
 pub(crate) const NT_GNU_BUILD_ID: Elf64_Word = 3;
 
+/// An ELF note header.
+///
+/// Note headers have the same layout for 32 bit and 64 bit ELF files.
 #[repr(C)]
 pub(crate) struct Elf64_Nhdr {
     pub n_namesz: Elf64_Word,
@@ -104,3 +463,5 @@ pub(crate) struct Elf64_Nhdr {
 
 // SAFETY: `Elf64_Nhdr` is valid for any bit pattern.
 unsafe impl crate::util::Pod for Elf64_Nhdr {}
+
+impl_byte_swap!(Elf64_Nhdr, n_namesz, n_descsz, n_type,);
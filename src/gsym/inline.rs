@@ -0,0 +1,206 @@
+//! Decoding of GSYM inline information.
+//!
+//! Inline information is stored as a tree of [`InlineInfo`] objects in
+//! an [`AddrData`][super::types::AddrData] entry of type
+//! [`INFO_TYPE_INLINE_INFO`][super::types::INFO_TYPE_INLINE_INFO]. The
+//! root of the tree describes the function itself, while each child
+//! describes a function inlined into its parent, along with the
+//! location of the call site in said parent.
+
+use std::ops::Range;
+
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The maximum nesting depth of inlined functions that we support.
+///
+/// Inline information is untrusted input and we parse it recursively,
+/// so we have to bound the depth to not overflow the stack.
+const MAX_INLINE_DEPTH: usize = 128;
+
+/// A node in the tree of functions inlined into a GSYM function.
+#[derive(Debug)]
+pub(super) struct InlineInfo {
+    /// The address ranges covered by the function.
+    pub ranges: Box<[Range<Addr>]>,
+    /// The string table offset of the function's name.
+    pub name: u32,
+    /// The file table index of the call site, with zero meaning
+    /// "unknown".
+    pub call_file: u32,
+    /// The line of the call site, with zero meaning "unknown".
+    pub call_line: u32,
+    /// The functions inlined into this one.
+    pub children: Box<[InlineInfo]>,
+}
+
+impl InlineInfo {
+    /// Parse an `InlineInfo` object, including all its children.
+    ///
+    /// Address ranges are encoded relative to `base_addr`, which is
+    /// the start address of the function for the root object and the
+    /// start of the parent's first range for all others.
+    ///
+    /// An object without any address ranges terminates a list of
+    /// siblings and is reported as `None`.
+    pub(super) fn parse(data: &mut &[u8], base_addr: Addr) -> Result<Option<Self>> {
+        Self::parse_impl(data, base_addr, 0)
+    }
+
+    fn parse_impl(data: &mut &[u8], base_addr: Addr, depth: usize) -> Result<Option<Self>> {
+        fn parse_ranges(data: &mut &[u8], base_addr: Addr) -> Option<Box<[Range<Addr>]>> {
+            let (count, _bytes) = data.read_u128_leb128()?;
+            (0..count)
+                .map(|_| {
+                    let (offset, _bytes) = data.read_u128_leb128()?;
+                    let (size, _bytes) = data.read_u128_leb128()?;
+                    let start = base_addr.checked_add(offset as Addr)?;
+                    let end = start.checked_add(size as Addr)?;
+                    Some(start..end)
+                })
+                .collect()
+        }
+
+        let ranges = parse_ranges(data, base_addr)
+            .ok_or_invalid_data(|| "failed to read GSYM inline info address ranges")?;
+        let first = if let Some(first) = ranges.first() {
+            first.start
+        } else {
+            return Ok(None)
+        };
+
+        let (has_children, name, call_file, call_line) = (|| {
+            let has_children = data.read_u8()? != 0;
+            let name = data.read_u32()?;
+            let (call_file, _bytes) = data.read_u128_leb128()?;
+            let (call_line, _bytes) = data.read_u128_leb128()?;
+            Some((has_children, name, call_file as u32, call_line as u32))
+        })()
+        .ok_or_invalid_data(|| "failed to read GSYM inline info")?;
+
+        let mut children = Vec::new();
+        if has_children {
+            if depth >= MAX_INLINE_DEPTH {
+                return Err(Error::with_invalid_data(format!(
+                    "GSYM inline info exceeds maximum nesting depth of {MAX_INLINE_DEPTH}"
+                )))
+            }
+
+            while let Some(child) = Self::parse_impl(data, first, depth + 1)? {
+                let () = children.push(child);
+            }
+        }
+
+        let slf = Self {
+            ranges,
+            name,
+            call_file,
+            call_line,
+            children: children.into_boxed_slice(),
+        };
+        Ok(Some(slf))
+    }
+
+    /// Find the chain of inlined functions containing `addr`.
+    ///
+    /// Functions are reported from the outermost to the innermost one.
+    /// The object itself, which represents the non-inlined function, is
+    /// not part of the result.
+    pub(super) fn inline_stack(&self, addr: Addr) -> Vec<&InlineInfo> {
+        let mut stack = Vec::new();
+        let mut info = self;
+        while let Some(child) = info
+            .children
+            .iter()
+            .find(|child| child.ranges.iter().any(|range| range.contains(&addr)))
+        {
+            let () = stack.push(child);
+            info = child;
+        }
+        stack
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    /// Check that we can parse a tree of `InlineInfo` objects and find
+    /// the inline stack for an address.
+    #[test]
+    fn inline_info_parsing() {
+        let mut data = Vec::new();
+        // Root: one range [0x1000, 0x1020), with children, no call site.
+        let () = data.extend_from_slice(&[0x01, 0x00, 0x20, 0x01]);
+        let () = data.extend_from_slice(&1u32.to_ne_bytes());
+        let () = data.extend_from_slice(&[0x00, 0x00]);
+        // Child: one range [0x1004, 0x1010), with children, called
+        // from file 1, line 10.
+        let () = data.extend_from_slice(&[0x01, 0x04, 0x0c, 0x01]);
+        let () = data.extend_from_slice(&2u32.to_ne_bytes());
+        let () = data.extend_from_slice(&[0x01, 0x0a]);
+        // Grandchild: one range [0x1004, 0x1008), without children,
+        // called from file 1, line 20.
+        let () = data.extend_from_slice(&[0x01, 0x00, 0x04, 0x00]);
+        let () = data.extend_from_slice(&3u32.to_ne_bytes());
+        let () = data.extend_from_slice(&[0x01, 0x14]);
+        // Terminate the child's and the root's list of children.
+        let () = data.extend_from_slice(&[0x00, 0x00]);
+
+        let mut slice = data.as_slice();
+        let info = InlineInfo::parse(&mut slice, 0x1000).unwrap().unwrap();
+        assert!(slice.is_empty());
+        assert_eq!(info.ranges.len(), 1);
+        assert_eq!(info.ranges[0], 0x1000..0x1020);
+        assert_eq!(info.name, 1);
+        assert_eq!(info.children.len(), 1);
+
+        let stack = info.inline_stack(0x1000);
+        assert!(stack.is_empty());
+
+        let stack = info.inline_stack(0x100c);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack[0].name, 2);
+        assert_eq!(stack[0].call_file, 1);
+        assert_eq!(stack[0].call_line, 10);
+
+        let stack = info.inline_stack(0x1005);
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack[0].name, 2);
+        assert_eq!(stack[1].name, 3);
+        assert_eq!(stack[1].call_line, 20);
+    }
+
+    /// Make sure that we fail gracefully on truncated data.
+    #[test]
+    fn inline_info_truncated() {
+        let data = [0x01, 0x00, 0x20, 0x01];
+        let err = InlineInfo::parse(&mut data.as_slice(), 0x1000).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Check that we reject excessively deeply nested inline
+    /// information instead of overflowing the stack.
+    #[test]
+    fn inline_info_deep_nesting() {
+        // An object with one range [0x1000, 0x1020), with children,
+        // without a call site.
+        let mut object = vec![0x01, 0x00, 0x20, 0x01];
+        let () = object.extend_from_slice(&1u32.to_ne_bytes());
+        let () = object.extend_from_slice(&[0x00, 0x00]);
+        let data = object.repeat(100_000);
+
+        let err = InlineInfo::parse(&mut data.as_slice(), 0x1000).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -10,12 +10,14 @@ use crate::inspect::SymInfo;
 use crate::mmap::Mmap;
 use crate::symbolize::AddrLineInfo;
 use crate::Addr;
+use crate::IntInlinedFn;
 use crate::IntSym;
 use crate::IntoError as _;
 use crate::Result;
 use crate::SrcLang;
 use crate::SymResolver;
 
+use super::inline::InlineInfo;
 use super::linetab::run_op;
 use super::linetab::LineTableHeader;
 use super::linetab::LineTableRow;
@@ -101,6 +103,7 @@ impl SymResolver for GsymResolver<'_> {
                 name,
                 addr: found,
                 lang,
+                module: None,
             };
 
             Ok(vec![sym])
@@ -205,6 +208,103 @@ impl SymResolver for GsymResolver<'_> {
         Ok(addr_info)
     }
 
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        let idx = if let Some(idx) = self.ctx.find_addr(addr) {
+            idx
+        } else {
+            return Ok(Vec::new())
+        };
+        let symaddr = self
+            .ctx
+            .addr_at(idx)
+            .ok_or_invalid_data(|| format!("failed to read address table entry {idx}"))?;
+        if addr < symaddr {
+            return Ok(Vec::new())
+        }
+        let addrinfo = self
+            .ctx
+            .addr_info(idx)
+            .ok_or_invalid_data(|| format!("failed to read address information entry {idx}"))?;
+        if addr >= (symaddr + addrinfo.size as Addr) {
+            return Ok(Vec::new())
+        }
+
+        let inline_data = parse_address_data(addrinfo.data)
+            .find(|addr_ent| addr_ent.typ == INFO_TYPE_INLINE_INFO)
+            .map(|addr_ent| addr_ent.data);
+        let mut data = if let Some(data) = inline_data {
+            data
+        } else {
+            return Ok(Vec::new())
+        };
+
+        let info = if let Some(info) = InlineInfo::parse(&mut data, symaddr)? {
+            info
+        } else {
+            return Ok(Vec::new())
+        };
+
+        info.inline_stack(addr)
+            .into_iter()
+            .map(|inlined| {
+                let name = self
+                    .ctx
+                    .get_str(inlined.name as usize)
+                    .and_then(|s| s.to_str())
+                    .ok_or_invalid_data(|| {
+                        format!(
+                            "failed to read string table entry at offset {}",
+                            inlined.name
+                        )
+                    })?;
+
+                let call_site = if inlined.call_file != 0 {
+                    let finfo = self
+                        .ctx
+                        .file_info(inlined.call_file as usize)
+                        .ok_or_invalid_data(|| {
+                            format!("failed to read file table entry {}", inlined.call_file)
+                        })?;
+                    let dir = self
+                        .ctx
+                        .get_str(finfo.directory as usize)
+                        .ok_or_invalid_data(|| {
+                            format!(
+                                "failed to read string table entry at offset {}",
+                                finfo.directory
+                            )
+                        })?;
+                    let file = self
+                        .ctx
+                        .get_str(finfo.filename as usize)
+                        .ok_or_invalid_data(|| {
+                            format!(
+                                "failed to read string table entry at offset {}",
+                                finfo.filename
+                            )
+                        })?;
+                    Some(AddrLineInfo {
+                        dir: Path::new(dir),
+                        file,
+                        line: (inlined.call_line != 0).then_some(inlined.call_line),
+                        column: None,
+                    })
+                } else {
+                    None
+                };
+
+                let inlined = IntInlinedFn {
+                    name,
+                    call_site,
+                    // Gsym does not carry any source code language
+                    // information.
+                    lang: SrcLang::Unknown,
+                };
+                Ok(inlined)
+            })
+            .collect()
+    }
+
     fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
         // Unavailable
         None
@@ -264,4 +364,32 @@ mod tests {
         assert_eq!(info.line, Some(8));
         assert_eq!(info.file, "test-stable-addresses.c");
     }
+
+    /// Check that we can find the functions inlined at an address.
+    #[test]
+    fn find_inlined_fns() {
+        let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.gsym");
+        let resolver = GsymResolver::new(test_gsym).unwrap();
+
+        // `factorial` does not have any inlined functions.
+        let inlined = resolver.find_inlined_fns(0x2000100).unwrap();
+        assert!(inlined.is_empty());
+
+        let inlined = resolver.find_inlined_fns(0x2000208).unwrap();
+        assert_eq!(inlined.len(), 2);
+
+        let outer = &inlined[0];
+        assert_eq!(outer.name, "factorial_inline_wrapper");
+        let call_site = outer.call_site.as_ref().unwrap();
+        assert_eq!(call_site.file, "test-stable-addresses.c");
+        assert_eq!(call_site.line, Some(53));
+
+        let inner = &inlined[1];
+        assert_eq!(inner.name, "factorial_2nd_layer_inline_wrapper");
+        let call_site = inner.call_site.as_ref().unwrap();
+        assert_eq!(call_site.file, "test-stable-addresses.c");
+        assert_eq!(call_site.line, Some(48));
+    }
 }
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[cfg(feature = "dwarf")]
 use crate::dwarf::DwarfResolver;
@@ -51,18 +51,18 @@ impl Inspector {
                     let debug_line_info = true;
                     let debug_info_symbols = true;
                     let dwarf = DwarfResolver::open(path, debug_line_info, debug_info_symbols)?;
-                    let backend = ElfBackend::Dwarf(Rc::new(dwarf));
+                    let backend = ElfBackend::Dwarf(Arc::new(dwarf));
                     backend
                 } else {
                     let elf = ElfParser::open(path)?;
-                    let backend = ElfBackend::Elf(Rc::new(elf));
+                    let backend = ElfBackend::Elf(Arc::new(elf));
                     backend
                 };
 
                 #[cfg(not(feature = "dwarf"))]
                 let backend = {
                     let elf = ElfParser::open(path)?;
-                    let backend = ElfBackend::Elf(Rc::new(elf));
+                    let backend = ElfBackend::Elf(Arc::new(elf));
                     backend
                 };
 
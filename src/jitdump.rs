@@ -0,0 +1,699 @@
+//! Support for perf jitdump files.
+//!
+//! Just-in-time compilers (e.g., the JVM, V8, or .NET) can describe the
+//! code they generate in a jitdump file, by convention named
+//! `jit-<pid>.dump`. Contrary to perf maps, jitdump files record the
+//! history of code: when code got loaded, when it got moved to a
+//! different address, as well as debug information mapping code to
+//! source code lines. In order for perf to find the file, the JIT maps
+//! it into its address space, which is what we use for discovering it.
+//!
+//! A jitdump file starts with a file header, followed by a sequence of
+//! records, each introduced by a record header. All values are in the
+//! byte order of the process that created the file, which can be
+//! inferred from the magic number.
+//!
+//! See <https://git.kernel.org/pub/scm/linux/kernel/git/torvalds/linux.git/tree/tools/perf/Documentation/jitdump-specification.txt>
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::read as read_file;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::inspect::FindAddrOpts;
+use crate::inspect::SymInfo;
+use crate::inspect::SymType;
+use crate::log::debug;
+use crate::maps::MapsEntry;
+use crate::maps::PathName;
+use crate::symbolize::AddrLineInfo;
+use crate::util::split_path;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntInlinedFn;
+use crate::IntSym;
+use crate::IntoError as _;
+use crate::Pid;
+use crate::Result;
+use crate::SrcLang;
+use crate::SymResolver;
+
+
+/// The magic number identifying a jitdump file ("JiTD").
+const JITDUMP_MAGIC: u32 = 0x4A695444;
+
+/// The size of the record header, in bytes.
+const RECORD_HEADER_SIZE: usize = 16;
+
+const JIT_CODE_LOAD: u32 = 0;
+const JIT_CODE_MOVE: u32 = 1;
+const JIT_CODE_DEBUG_INFO: u32 = 2;
+
+
+/// Check whether a `MapsEntry` maps the jitdump file of the process
+/// with the provided ID, returning the path to use for opening it.
+pub(crate) fn jitdump_entry_path(entry: &MapsEntry, pid: Pid) -> Option<&Path> {
+    match &entry.path_name {
+        Some(PathName::Path(path)) => {
+            let file_name = format!("jit-{}.dump", pid.resolve());
+            (path.symbolic_path.file_name()? == file_name.as_str()).then(|| {
+                // Should the file be inaccessible, loading it fails
+                // later on and gets reported then.
+                path.local_path()
+                    .map(|(path, _access)| path)
+                    .unwrap_or(&path.maps_file)
+            })
+        }
+        _ => None,
+    }
+}
+
+
+/// A reader of values in the jitdump file's byte order.
+struct Reader<'dat> {
+    data: &'dat [u8],
+    /// Whether values need their bytes swapped to match the host's
+    /// byte order.
+    swap: bool,
+}
+
+impl<'dat> Reader<'dat> {
+    fn read_u32(&mut self) -> Option<u32> {
+        let value = self.data.read_u32()?;
+        Some(if self.swap { value.swap_bytes() } else { value })
+    }
+
+    fn read_u64(&mut self) -> Option<u64> {
+        let value = self.data.read_u64()?;
+        Some(if self.swap { value.swap_bytes() } else { value })
+    }
+
+    fn read_cstr(&mut self) -> Option<&'dat CStr> {
+        self.data.read_cstr()
+    }
+}
+
+
+/// A mapping of a code location to a source code line.
+#[derive(Debug)]
+struct LineEntry {
+    /// The offset of the described code from the start of the code
+    /// blob.
+    offset: u64,
+    /// The line number.
+    line: u32,
+    /// The index of the source file in `JitDump::files`.
+    file: usize,
+}
+
+
+/// A blob of code loaded by the JIT.
+#[derive(Debug)]
+struct Code {
+    /// The name of the function the code belongs to.
+    name: String,
+    /// Line information for the code, sorted by offset.
+    lines: Vec<LineEntry>,
+}
+
+
+/// The placement of a code blob at an address for a period of time.
+#[derive(Debug)]
+struct Placement {
+    /// The address the code got placed at.
+    addr: Addr,
+    /// The size of the code, in bytes.
+    size: u64,
+    /// The time stamp at which the code got placed at `addr`.
+    start: u64,
+    /// The time stamp at which the code got moved away, if any.
+    end: Option<u64>,
+    /// The index of the code in `JitDump::codes`.
+    code: usize,
+}
+
+impl Placement {
+    #[inline]
+    fn contains(&self, addr: Addr) -> bool {
+        (self.addr..self.addr.saturating_add(self.size as Addr)).contains(&addr)
+    }
+
+    /// Check whether the code was placed at `addr` at the provided
+    /// time, with `None` meaning the most recent state.
+    #[inline]
+    fn is_live(&self, time: Option<u64>) -> bool {
+        match time {
+            None => self.end.is_none(),
+            Some(time) => self.start <= time && self.end.map_or(true, |end| time < end),
+        }
+    }
+}
+
+
+/// The parsed contents of a jitdump file.
+#[derive(Debug, Default)]
+struct JitDump {
+    /// Source file names, as referenced by line entries.
+    files: Vec<String>,
+    /// All code blobs loaded.
+    codes: Vec<Code>,
+    /// All placements of code blobs, in the order they happened.
+    placements: Vec<Placement>,
+}
+
+impl JitDump {
+    /// Parse the jitdump file contained in `data`.
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut data = data;
+        let magic = data
+            .read_u32()
+            .ok_or_invalid_data(|| "failed to read jitdump magic")?;
+        let swap = match magic {
+            JITDUMP_MAGIC => false,
+            _ if magic.swap_bytes() == JITDUMP_MAGIC => true,
+            _ => {
+                return Err(Error::with_invalid_data(format!(
+                    "encountered invalid jitdump magic {magic:#x}"
+                )))
+            }
+        };
+
+        let mut reader = Reader { data, swap };
+        let _version = reader.read_u32();
+        let header_size = reader
+            .read_u32()
+            .ok_or_invalid_data(|| "failed to read jitdump header")?
+            as usize;
+        // The header size includes the magic we already read.
+        let _rest = reader
+            .data
+            .read_slice(header_size.saturating_sub(3 * 4))
+            .ok_or_invalid_data(|| "jitdump header is truncated")?;
+
+        let mut dump = JitDump::default();
+        let mut parser = Parser::default();
+        let mut offset = header_size;
+        while !reader.data.is_empty() {
+            let mut header = Reader {
+                data: reader.data,
+                swap,
+            };
+            let (id, size, timestamp) =
+                match (header.read_u32(), header.read_u32(), header.read_u64()) {
+                    (Some(id), Some(size), Some(timestamp)) => (id, size as usize, timestamp),
+                    _ => {
+                        // The JIT may still be writing the file.
+                        debug!("ignoring truncated jitdump record header at offset {offset}");
+                        break
+                    }
+                };
+            if size < RECORD_HEADER_SIZE {
+                return Err(Error::with_invalid_data(format!(
+                    "encountered jitdump record with invalid size {size} at offset {offset}"
+                )))
+            }
+
+            let record = if let Some(record) = reader.data.read_slice(size) {
+                record
+            } else {
+                debug!("ignoring truncated jitdump record at offset {offset}");
+                break
+            };
+            let mut body = Reader {
+                data: &record[RECORD_HEADER_SIZE..],
+                swap,
+            };
+            let () = parser
+                .parse_record(&mut dump, id, timestamp, &mut body)
+                .ok_or_invalid_data(|| {
+                    format!("encountered malformed jitdump record {id} at offset {offset}")
+                })?;
+            offset += size;
+        }
+        Ok(dump)
+    }
+
+    /// Find the placement covering `addr` at the provided time.
+    ///
+    /// If several placements qualify, e.g., because code got
+    /// regenerated at the same address, the most recent one wins.
+    fn find_placement(&self, addr: Addr, time: Option<u64>) -> Option<&Placement> {
+        self.placements
+            .iter()
+            .filter(|placement| placement.contains(addr) && placement.is_live(time))
+            .max_by_key(|placement| placement.start)
+    }
+}
+
+
+/// State required while parsing the records of a jitdump file.
+#[derive(Default)]
+struct Parser {
+    /// Line information not yet attributed to loaded code, by code
+    /// address. Debug information records precede the load records of
+    /// the code they describe.
+    pending_lines: HashMap<Addr, Vec<LineEntry>>,
+    /// Indexes into `JitDump::files`, by file name.
+    file_idxs: HashMap<String, usize>,
+    /// Indexes into `JitDump::codes`, by JIT assigned code index.
+    code_idxs: HashMap<u64, usize>,
+}
+
+impl Parser {
+    fn parse_record(
+        &mut self,
+        dump: &mut JitDump,
+        id: u32,
+        timestamp: u64,
+        body: &mut Reader<'_>,
+    ) -> Option<()> {
+        match id {
+            JIT_CODE_LOAD => {
+                let _pid = body.read_u32()?;
+                let _tid = body.read_u32()?;
+                let _vma = body.read_u64()?;
+                let code_addr = body.read_u64()? as Addr;
+                let code_size = body.read_u64()?;
+                let code_index = body.read_u64()?;
+                let name = body.read_cstr()?.to_string_lossy().into_owned();
+
+                let lines = self.pending_lines.remove(&code_addr).unwrap_or_default();
+                let code = dump.codes.len();
+                let () = dump.codes.push(Code { name, lines });
+                let _prev = self.code_idxs.insert(code_index, code);
+                let () = dump.placements.push(Placement {
+                    addr: code_addr,
+                    size: code_size,
+                    start: timestamp,
+                    end: None,
+                    code,
+                });
+            }
+            JIT_CODE_MOVE => {
+                let _pid = body.read_u32()?;
+                let _tid = body.read_u32()?;
+                let _vma = body.read_u64()?;
+                let old_code_addr = body.read_u64()? as Addr;
+                let new_code_addr = body.read_u64()? as Addr;
+                let code_size = body.read_u64()?;
+                let code_index = body.read_u64()?;
+
+                let code = if let Some(code) = self.code_idxs.get(&code_index) {
+                    *code
+                } else {
+                    debug!("ignoring move of unknown jitdump code {code_index}");
+                    return Some(())
+                };
+
+                if let Some(placement) = dump.placements.iter_mut().find(|placement| {
+                    placement.code == code
+                        && placement.addr == old_code_addr
+                        && placement.end.is_none()
+                }) {
+                    placement.end = Some(timestamp);
+                }
+                let () = dump.placements.push(Placement {
+                    addr: new_code_addr,
+                    size: code_size,
+                    start: timestamp,
+                    end: None,
+                    code,
+                });
+            }
+            JIT_CODE_DEBUG_INFO => {
+                let code_addr = body.read_u64()? as Addr;
+                let count = body.read_u64()?;
+
+                let mut lines = Vec::new();
+                let mut prev_file = None;
+                for _ in 0..count {
+                    let addr = body.read_u64()? as Addr;
+                    let line = body.read_u32()?;
+                    let _discrim = body.read_u32()?;
+                    let name = body.read_cstr()?;
+                    // A file name consisting of 0xff only refers to the
+                    // file name of the previous entry.
+                    let file = if name.to_bytes() == b"\xff" {
+                        prev_file?
+                    } else {
+                        let name = name.to_string_lossy();
+                        if let Some(idx) = self.file_idxs.get(name.as_ref()) {
+                            *idx
+                        } else {
+                            let idx = dump.files.len();
+                            let () = dump.files.push(name.to_string());
+                            let _prev = self.file_idxs.insert(name.into_owned(), idx);
+                            idx
+                        }
+                    };
+                    prev_file = Some(file);
+
+                    let () = lines.push(LineEntry {
+                        offset: addr.checked_sub(code_addr)? as u64,
+                        line,
+                        file,
+                    });
+                }
+
+                let () = lines.sort_by_key(|entry| entry.offset);
+                let _prev = self.pending_lines.insert(code_addr, lines);
+            }
+            // We don't care about any other records (code close and
+            // unwinding information).
+            _ => (),
+        }
+        Some(())
+    }
+}
+
+
+/// The symbol resolver for jitdump files.
+pub(crate) struct JitDumpResolver {
+    /// The parsed jitdump file.
+    dump: JitDump,
+    /// The time stamp at which to look up code, with `None` meaning
+    /// the most recent state.
+    time: Option<u64>,
+    /// The path to the jitdump file.
+    path: PathBuf,
+}
+
+impl JitDumpResolver {
+    /// Load the jitdump file at `path`, resolving addresses as they
+    /// were valid at the provided time.
+    pub fn from_file(path: &Path, time: Option<u64>) -> Result<Self> {
+        let data = read_file(path)
+            .with_context(|| format!("failed to read jitdump file {}", path.display()))?;
+        let dump = JitDump::parse(&data)
+            .with_context(|| format!("failed to parse jitdump file {}", path.display()))?;
+
+        let slf = Self {
+            dump,
+            time,
+            path: path.to_path_buf(),
+        };
+        Ok(slf)
+    }
+}
+
+impl SymResolver for JitDumpResolver {
+    fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
+        let syms = self
+            .dump
+            .find_placement(addr, self.time)
+            .map(|placement| IntSym {
+                name: &self.dump.codes[placement.code].name,
+                addr: placement.addr,
+                // jitdump files don't carry any source code language
+                // information.
+                lang: SrcLang::Unknown,
+                module: None,
+            })
+            .into_iter()
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_addr(&self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo>> {
+        if let SymType::Variable = opts.sym_type {
+            return Ok(Vec::new())
+        }
+
+        let syms = self
+            .dump
+            .placements
+            .iter()
+            .filter(|placement| {
+                placement.is_live(self.time) && self.dump.codes[placement.code].name == name
+            })
+            .map(|placement| SymInfo {
+                name: name.to_string(),
+                addr: placement.addr,
+                size: placement.size as usize,
+                sym_type: SymType::Function,
+                file_offset: 0,
+                obj_file_name: None,
+            })
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_line_info(&self, addr: Addr) -> Result<Option<AddrLineInfo<'_>>> {
+        let placement = if let Some(placement) = self.dump.find_placement(addr, self.time) {
+            placement
+        } else {
+            return Ok(None)
+        };
+
+        let lines = &self.dump.codes[placement.code].lines;
+        let offset = (addr - placement.addr) as u64;
+        let idx = lines.partition_point(|entry| entry.offset <= offset);
+        let entry = if let Some(entry) = idx.checked_sub(1).and_then(|idx| lines.get(idx)) {
+            entry
+        } else {
+            return Ok(None)
+        };
+
+        let (dir, file) = split_path(&self.dump.files[entry.file]);
+        let info = AddrLineInfo {
+            dir,
+            file,
+            line: (entry.line != 0).then_some(entry.line),
+            column: None,
+        };
+        Ok(Some(info))
+    }
+
+    fn find_inlined_fns(&self, _addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        Ok(Vec::new())
+    }
+
+    fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
+        None
+    }
+}
+
+impl Debug for JitDumpResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "JitDump {}", self.path.display())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+
+    /// A helper for creating jitdump file contents.
+    struct Builder {
+        data: Vec<u8>,
+        swap: bool,
+    }
+
+    impl Builder {
+        fn new(swap: bool) -> Self {
+            let mut slf = Self {
+                data: Vec::new(),
+                swap,
+            };
+            slf.u32(JITDUMP_MAGIC);
+            slf.u32(1);
+            slf.u32(40);
+            slf.u32(62);
+            slf.u32(0);
+            slf.u32(1234);
+            slf.u64(0);
+            slf.u64(0);
+            slf
+        }
+
+        fn u32(&mut self, value: u32) {
+            let value = if self.swap { value.swap_bytes() } else { value };
+            self.data.extend_from_slice(&value.to_ne_bytes());
+        }
+
+        fn u64(&mut self, value: u64) {
+            let value = if self.swap { value.swap_bytes() } else { value };
+            self.data.extend_from_slice(&value.to_ne_bytes());
+        }
+
+        fn record(&mut self, id: u32, timestamp: u64, body: impl FnOnce(&mut Builder)) {
+            let start = self.data.len();
+            self.u32(id);
+            self.u32(0);
+            self.u64(timestamp);
+            body(self);
+            let size = (self.data.len() - start) as u32;
+            let size = if self.swap { size.swap_bytes() } else { size };
+            self.data[start + 4..start + 8].copy_from_slice(&size.to_ne_bytes());
+        }
+
+        fn load(&mut self, timestamp: u64, addr: u64, size: u64, index: u64, name: &str) {
+            self.record(JIT_CODE_LOAD, timestamp, |b| {
+                b.u32(1234);
+                b.u32(1234);
+                b.u64(addr);
+                b.u64(addr);
+                b.u64(size);
+                b.u64(index);
+                b.data.extend_from_slice(name.as_bytes());
+                b.data.push(0);
+                b.data.extend(std::iter::repeat(0xcc).take(size as usize));
+            })
+        }
+
+        fn mv(&mut self, timestamp: u64, old: u64, new: u64, size: u64, index: u64) {
+            self.record(JIT_CODE_MOVE, timestamp, |b| {
+                b.u32(1234);
+                b.u32(1234);
+                b.u64(new);
+                b.u64(old);
+                b.u64(new);
+                b.u64(size);
+                b.u64(index);
+            })
+        }
+
+        fn debug_info(&mut self, timestamp: u64, addr: u64, entries: &[(u64, u32, &[u8])]) {
+            self.record(JIT_CODE_DEBUG_INFO, timestamp, |b| {
+                b.u64(addr);
+                b.u64(entries.len() as u64);
+                for (entry_addr, line, file) in entries {
+                    b.u64(*entry_addr);
+                    b.u32(*line);
+                    b.u32(0);
+                    b.data.extend_from_slice(file);
+                    b.data.push(0);
+                }
+            })
+        }
+    }
+
+
+    /// Check that we can parse a jitdump file and symbolize addresses
+    /// with line information, irrespective of its byte order.
+    #[test]
+    fn jitdump_parsing() {
+        fn test(swap: bool) {
+            let mut builder = Builder::new(swap);
+            builder.debug_info(
+                10,
+                0x1000,
+                &[
+                    (0x1010, 7, b"/src/foo.js"),
+                    (0x1000, 5, b"/src/foo.js"),
+                    (0x1020, 9, b"\xff"),
+                ],
+            );
+            builder.load(11, 0x1000, 0x40, 1, "foo");
+            builder.load(12, 0x2000, 0x10, 2, "bar");
+            // A trailing truncated record should be ignored.
+            builder.data.extend_from_slice(&[0, 0, 0]);
+
+            let dump = JitDump::parse(&builder.data).unwrap();
+            let resolver = JitDumpResolver {
+                dump,
+                time: None,
+                path: PathBuf::from("jit-1234.dump"),
+            };
+
+            let syms = resolver.find_syms(0x1018).unwrap();
+            assert_eq!(syms.len(), 1);
+            assert_eq!(syms[0].name, "foo");
+            assert_eq!(syms[0].addr, 0x1000);
+
+            let info = resolver.find_line_info(0x1018).unwrap().unwrap();
+            assert_eq!(info.dir, Path::new("/src"));
+            assert_eq!(info.file, "foo.js");
+            assert_eq!(info.line, Some(7));
+
+            let info = resolver.find_line_info(0x103f).unwrap().unwrap();
+            assert_eq!(info.file, "foo.js");
+            assert_eq!(info.line, Some(9));
+
+            let syms = resolver.find_syms(0x2008).unwrap();
+            assert_eq!(syms[0].name, "bar");
+            assert!(resolver.find_line_info(0x2008).unwrap().is_none());
+
+            assert!(resolver.find_syms(0x1040).unwrap().is_empty());
+        }
+
+        test(false);
+        test(true);
+    }
+
+    /// Check that we take into account the time at which code got moved
+    /// when resolving addresses.
+    #[test]
+    fn jitdump_code_move() {
+        let mut builder = Builder::new(false);
+        builder.debug_info(
+            10,
+            0x1000,
+            &[(0x1000, 5, b"/src/foo.js"), (0x1008, 6, b"\xff")],
+        );
+        builder.load(10, 0x1000, 0x10, 1, "foo");
+        builder.mv(20, 0x1000, 0x3000, 0x10, 1);
+        builder.load(30, 0x1000, 0x20, 2, "baz");
+
+        let dump = JitDump::parse(&builder.data).unwrap();
+        let mut resolver = JitDumpResolver {
+            dump,
+            time: None,
+            path: PathBuf::from("jit-1234.dump"),
+        };
+
+        let name = |resolver: &JitDumpResolver, addr| {
+            resolver
+                .find_syms(addr)
+                .unwrap()
+                .first()
+                .map(|sym| sym.name.to_string())
+        };
+
+        // By default, the most recent state is used.
+        assert_eq!(name(&resolver, 0x1004).as_deref(), Some("baz"));
+        assert_eq!(name(&resolver, 0x300c).as_deref(), Some("foo"));
+        // Line information should have moved along with the code.
+        let info = resolver.find_line_info(0x300c).unwrap().unwrap();
+        assert_eq!(info.line, Some(6));
+
+        resolver.time = Some(15);
+        assert_eq!(name(&resolver, 0x1004).as_deref(), Some("foo"));
+        assert_eq!(name(&resolver, 0x300c), None);
+
+        resolver.time = Some(25);
+        assert_eq!(name(&resolver, 0x1004), None);
+        assert_eq!(name(&resolver, 0x300c).as_deref(), Some("foo"));
+
+        resolver.time = Some(5);
+        assert_eq!(name(&resolver, 0x1004), None);
+
+        let opts = FindAddrOpts::default();
+        resolver.time = None;
+        let infos = resolver.find_addr("foo", &opts).unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].addr, 0x3000);
+    }
+
+    /// Check that we reject files that aren't jitdump files.
+    #[test]
+    fn invalid_jitdump() {
+        let err = JitDump::parse(b"\x7fELF\x02\x01\x01\x00").unwrap_err();
+        assert!(err.to_string().contains("magic"), "{err}");
+
+        let mut builder = Builder::new(false);
+        builder.record(JIT_CODE_LOAD, 1, |b| b.u32(1));
+        let err = JitDump::parse(&builder.data).unwrap_err();
+        assert!(err.to_string().contains("malformed"), "{err}");
+    }
+}
@@ -1,32 +1,203 @@
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::fs::read as read_file;
 use std::path::Path;
-use std::rc::Rc;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::bpf::BpfProgResolver;
+use crate::elf::find_build_id_file;
+use crate::elf::types::NT_GNU_BUILD_ID;
+use crate::elf::ElfParser;
 use crate::elf::ElfResolver;
+use crate::elf::DEFAULT_DEBUG_DIRS;
 use crate::inspect::FindAddrOpts;
 use crate::inspect::SymInfo;
+use crate::kmod::ModuleResolver;
 use crate::ksym::KSymResolver;
+use crate::log::debug;
+use crate::normalize::buildid::BuildIdReader as _;
+use crate::normalize::buildid::DefaultBuildIdReader;
 use crate::symbolize::AddrLineInfo;
+use crate::util::bytes_to_hex;
+use crate::util::uname_release;
+use crate::util::ReadRaw as _;
 use crate::Addr;
 use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntInlinedFn;
 use crate::IntSym;
 use crate::Result;
 use crate::SymResolver;
 
 
+/// The file exposing the ELF notes of the running kernel.
+pub(crate) const SYS_KERNEL_NOTES: &str = "/sys/kernel/notes";
+
+/// The names of symbols marking the start of the kernel's text, in
+/// order of preference.
+const TEXT_START_SYMS: [&str; 2] = ["_stext", "_text"];
+
+
+/// Round `size` up to the alignment of ELF note entries.
+#[inline]
+fn note_align(size: usize) -> Option<usize> {
+    Some(size.checked_add(3)? & !3)
+}
+
+/// Find the GNU build ID in a sequence of raw ELF notes, such as the
+/// ones the kernel exposes in `/sys/kernel/notes`.
+fn parse_build_id_notes(mut notes: &[u8]) -> Option<Vec<u8>> {
+    while !notes.is_empty() {
+        let namesz = notes.read_u32()? as usize;
+        let descsz = notes.read_u32()? as usize;
+        let type_ = notes.read_u32()?;
+        let name = notes.read_slice(note_align(namesz)?)?;
+        let desc = notes.read_slice(note_align(descsz)?)?;
+
+        if type_ == NT_GNU_BUILD_ID && name.get(..namesz) == Some(b"GNU\0") {
+            return Some(desc[..descsz].to_vec())
+        }
+    }
+    None
+}
+
+/// Read the build ID of the running kernel from the provided notes
+/// file (typically [`SYS_KERNEL_NOTES`]).
+pub(crate) fn read_kernel_build_id(notes: &Path) -> Result<Option<Vec<u8>>> {
+    let data = read_file(notes)
+        .with_context(|| format!("failed to read kernel notes from {}", notes.display()))?;
+    Ok(parse_build_id_notes(&data))
+}
+
+/// Check that the kernel image at `path`, as parsed by `parser`, has
+/// the provided build ID.
+///
+/// Images without a build ID cannot be verified and are accepted.
+pub(crate) fn verify_kernel_image(path: &Path, parser: &ElfParser, build_id: &[u8]) -> Result<()> {
+    match DefaultBuildIdReader::read_build_id(parser)? {
+        Some(image_id) if image_id != build_id => Err(Error::with_invalid_data(format!(
+            "kernel image {} (build ID {}) does not match the running kernel (build ID {})",
+            path.display(),
+            bytes_to_hex(&image_id),
+            bytes_to_hex(build_id),
+        ))),
+        Some(_) => Ok(()),
+        None => {
+            debug!(
+                "kernel image {} has no build ID; unable to verify it",
+                path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Find the image of the running kernel in one of the conventional
+/// locations.
+///
+/// If the running kernel's build ID is known, the build ID based
+/// layout of the default debug directories is searched as well and
+/// images with a different build ID are skipped.
+pub(crate) fn find_kernel_image(build_id: Option<&[u8]>) -> Result<Option<PathBuf>> {
+    if let Some(build_id) = build_id {
+        let debug_dirs = DEFAULT_DEBUG_DIRS
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        if let Some((path, _parser)) = find_build_id_file(build_id, &debug_dirs) {
+            return Ok(Some(path))
+        }
+    }
+
+    let release = uname_release()?.to_str().unwrap().to_string();
+    let candidates = [
+        Path::new("/boot").join(format!("vmlinux-{release}")),
+        Path::new("/lib/modules").join(&release).join("vmlinux"),
+        Path::new("/lib/modules")
+            .join(&release)
+            .join("build/vmlinux"),
+        Path::new("/usr/lib/debug/boot").join(format!("vmlinux-{release}")),
+        Path::new("/usr/lib/debug/boot").join(format!("vmlinux-{release}.debug")),
+        Path::new("/usr/lib/debug/lib/modules")
+            .join(&release)
+            .join("vmlinux"),
+    ];
+
+    for path in candidates {
+        if !path.exists() {
+            continue
+        }
+
+        let build_id = if let Some(build_id) = build_id {
+            build_id
+        } else {
+            return Ok(Some(path))
+        };
+
+        let result =
+            ElfParser::open(&path).and_then(|parser| verify_kernel_image(&path, &parser, build_id));
+        match result {
+            Ok(()) => return Ok(Some(path)),
+            Err(err) => debug!("{err}; ignoring..."),
+        }
+    }
+    Ok(None)
+}
+
+/// Determine the KASLR offset of the kernel described by
+/// `ksym_resolver`, i.e., the difference between its addresses and
+/// those in the kernel image parsed by `image`.
+///
+/// The offset is inferred from the address of the symbol marking the
+/// start of the kernel's text. `None` is returned if that symbol is
+/// not present in either source.
+pub(crate) fn find_kaslr_offset(
+    ksym_resolver: &KSymResolver,
+    image: &ElfParser,
+) -> Result<Option<Addr>> {
+    let opts = FindAddrOpts::default();
+    for name in TEXT_START_SYMS {
+        let runtime = ksym_resolver.find_addr(name, &opts)?;
+        let image = image.find_addr(name, &opts)?;
+        if let (Some(runtime), Some(image)) = (runtime.first(), image.first()) {
+            return Ok(Some(runtime.addr.wrapping_sub(image.addr)))
+        }
+    }
+    Ok(None)
+}
+
+
 pub(crate) struct KernelResolver {
-    pub ksym_resolver: Option<Rc<KSymResolver>>,
+    pub ksym_resolver: Option<Arc<KSymResolver>>,
     pub elf_resolver: Option<ElfResolver>,
+    /// Resolvers for loaded kernel modules.
+    pub modules: Vec<ModuleResolver>,
+    /// Resolvers for loaded BPF programs.
+    pub bpf_progs: Vec<BpfProgResolver>,
+    /// The KASLR offset of the addresses to symbolize, relative to the
+    /// kernel image.
+    pub kaslr_offset: Addr,
+    /// The KASLR offset of the running kernel, which kallsyms, module,
+    /// and BPF program addresses are relative to.
+    pub runtime_offset: Addr,
 }
 
 impl KernelResolver {
     pub fn new(
-        ksym_resolver: Option<Rc<KSymResolver>>,
+        ksym_resolver: Option<Arc<KSymResolver>>,
         elf_resolver: Option<ElfResolver>,
+        modules: Vec<ModuleResolver>,
+        bpf_progs: Vec<BpfProgResolver>,
+        kaslr_offset: Addr,
+        runtime_offset: Addr,
     ) -> Result<KernelResolver> {
-        if ksym_resolver.is_none() && elf_resolver.is_none() {
+        if ksym_resolver.is_none()
+            && elf_resolver.is_none()
+            && modules.is_empty()
+            && bpf_progs.is_empty()
+        {
             return Err(Error::with_not_found(
                     "failed to create kernel resolver: neither ksym resolver nor kernel image ELF resolver are present",
             ))
@@ -35,16 +206,87 @@ impl KernelResolver {
         Ok(KernelResolver {
             ksym_resolver,
             elf_resolver,
+            modules,
+            bpf_progs,
+            kaslr_offset,
+            runtime_offset,
         })
     }
+
+    /// Translate an address to symbolize into the address space of the
+    /// running kernel.
+    #[inline]
+    fn runtime_addr(&self, addr: Addr) -> Addr {
+        addr.wrapping_sub(self.kaslr_offset)
+            .wrapping_add(self.runtime_offset)
+    }
+
+    /// Translate an address to symbolize into the address space of the
+    /// kernel image.
+    #[inline]
+    fn image_addr(&self, addr: Addr) -> Addr {
+        addr.wrapping_sub(self.kaslr_offset)
+    }
+
+    /// Translate symbols found in the address space of the running
+    /// kernel back into that of the addresses to symbolize.
+    fn translate_runtime_syms<'slf>(&self, syms: Vec<IntSym<'slf>>) -> Vec<IntSym<'slf>> {
+        syms.into_iter()
+            .map(|sym| IntSym {
+                addr: sym
+                    .addr
+                    .wrapping_sub(self.runtime_offset)
+                    .wrapping_add(self.kaslr_offset),
+                ..sym
+            })
+            .collect()
+    }
+
+    /// Find the resolver of the kernel module containing `addr`, if
+    /// any.
+    fn find_module(&self, addr: Addr) -> Option<&ModuleResolver> {
+        self.modules.iter().find(|module| module.contains(addr))
+    }
+
+    /// Find the resolver of the BPF program containing `addr`, if any.
+    fn find_bpf_prog(&self, addr: Addr) -> Option<&BpfProgResolver> {
+        self.bpf_progs.iter().find(|prog| prog.contains(addr))
+    }
 }
 
 impl SymResolver for KernelResolver {
     fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
+        let runtime_addr = self.runtime_addr(addr);
+        if let Some(module) = self.find_module(runtime_addr) {
+            let syms = module.find_syms(runtime_addr)?;
+            if !syms.is_empty() {
+                return Ok(self.translate_runtime_syms(syms))
+            }
+        }
+
+        if let Some(prog) = self.find_bpf_prog(runtime_addr) {
+            let syms = prog.find_syms(runtime_addr)?;
+            if !syms.is_empty() {
+                return Ok(self.translate_runtime_syms(syms))
+            }
+        }
+
         if let Some(ksym_resolver) = self.ksym_resolver.as_ref() {
-            ksym_resolver.find_syms(addr)
+            ksym_resolver
+                .find_syms(runtime_addr)
+                .map(|syms| self.translate_runtime_syms(syms))
+        } else if let Some(elf_resolver) = self.elf_resolver.as_ref() {
+            let syms = elf_resolver
+                .find_syms(self.image_addr(addr))?
+                .into_iter()
+                .map(|sym| IntSym {
+                    addr: sym.addr.wrapping_add(self.kaslr_offset),
+                    ..sym
+                })
+                .collect();
+            Ok(syms)
         } else {
-            self.elf_resolver.as_ref().unwrap().find_syms(addr)
+            Ok(Vec::new())
         }
     }
 
@@ -53,13 +295,29 @@ impl SymResolver for KernelResolver {
     }
 
     fn find_line_info(&self, addr: Addr) -> Result<Option<AddrLineInfo>> {
-        if let Some(resolver) = self.elf_resolver.as_ref() {
-            resolver.find_line_info(addr)
+        let runtime_addr = self.runtime_addr(addr);
+        if let Some(module) = self.find_module(runtime_addr) {
+            module.find_line_info(runtime_addr)
+        } else if let Some(prog) = self.find_bpf_prog(runtime_addr) {
+            prog.find_line_info(runtime_addr)
+        } else if let Some(resolver) = self.elf_resolver.as_ref() {
+            resolver.find_line_info(self.image_addr(addr))
         } else {
             Ok(None)
         }
     }
 
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        let runtime_addr = self.runtime_addr(addr);
+        if let Some(module) = self.find_module(runtime_addr) {
+            module.find_inlined_fns(runtime_addr)
+        } else if let Some(resolver) = self.elf_resolver.as_ref() {
+            resolver.find_inlined_fns(self.image_addr(addr))
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
     fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
         None
     }
@@ -83,3 +341,68 @@ impl Debug for KernelResolver {
         )
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+
+    /// Check that we can find the build ID in raw ELF notes.
+    #[test]
+    fn build_id_notes_parsing() {
+        fn note(name: &[u8], type_: u32, desc: &[u8]) -> Vec<u8> {
+            let mut data = Vec::new();
+            let () = data.extend_from_slice(&(name.len() as u32).to_ne_bytes());
+            let () = data.extend_from_slice(&(desc.len() as u32).to_ne_bytes());
+            let () = data.extend_from_slice(&type_.to_ne_bytes());
+            let () = data.extend_from_slice(name);
+            let () = data.resize(note_align(data.len()).unwrap(), 0);
+            let () = data.extend_from_slice(desc);
+            let () = data.resize(note_align(data.len()).unwrap(), 0);
+            data
+        }
+
+        let mut notes = note(b"Linux\0", 6, b"\x01");
+        let () = notes.extend(note(b"Xen\0", NT_GNU_BUILD_ID, b"\xff\xff"));
+        let () = notes.extend(note(b"GNU\0", NT_GNU_BUILD_ID, b"\xde\xad\xbe\xef\x42"));
+        assert_eq!(
+            parse_build_id_notes(&notes),
+            Some(b"\xde\xad\xbe\xef\x42".to_vec())
+        );
+
+        assert_eq!(parse_build_id_notes(&notes[..notes.len() - 4]), None);
+        assert_eq!(parse_build_id_notes(&[]), None);
+    }
+
+    /// Check that we reject kernel images with a mismatching build ID.
+    #[test]
+    fn kernel_image_verification() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("libtest-so.so");
+        let parser = ElfParser::open(&path).unwrap();
+        let build_id = DefaultBuildIdReader::read_build_id(&parser)
+            .unwrap()
+            .unwrap();
+
+        let () = verify_kernel_image(&path, &parser, &build_id).unwrap();
+
+        let err = verify_kernel_image(&path, &parser, &[0xde, 0xad]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("does not match the running kernel"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("(build ID dead)"), "{err}");
+
+        // Images without build ID cannot be verified.
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-kernel.bin");
+        let parser = ElfParser::open(&path).unwrap();
+        let () = verify_kernel_image(&path, &parser, &build_id).unwrap();
+    }
+}
@@ -0,0 +1,459 @@
+//! Support for loadable kernel modules.
+//!
+//! The kernel lists loaded modules along with their base address and
+//! size in `/proc/modules`. More precise information about where each
+//! of a module's sections got loaded to is available in
+//! `/sys/module/<module>/sections/`. Using the latter, we map kernel
+//! addresses back into the module's `.ko` file, which may contain
+//! symbols and debug information.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::read_dir;
+use std::fs::read_to_string;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::elf::types::SHF_ALLOC;
+use crate::elf::ElfResolver;
+use crate::inspect::FindAddrOpts;
+use crate::inspect::SymInfo;
+use crate::log::debug;
+use crate::symbolize::AddrLineInfo;
+use crate::Addr;
+use crate::ErrorExt as _;
+use crate::IntInlinedFn;
+use crate::IntSym;
+use crate::IntoError as _;
+use crate::Result;
+use crate::SymResolver;
+
+
+/// The path to the list of loaded kernel modules.
+pub(crate) const PROC_MODULES: &str = "/proc/modules";
+/// The directory containing information about loaded kernel modules.
+pub(crate) const SYS_MODULE: &str = "/sys/module";
+
+
+/// Parse a hexadecimal number, optionally prefixed with `0x`.
+fn parse_hex(s: &str) -> Option<Addr> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    Addr::from_str_radix(s, 16).ok()
+}
+
+
+/// A kernel module, as listed in `/proc/modules`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct LoadedModule {
+    /// The name of the module.
+    pub name: String,
+    /// The address range covered by the module.
+    pub range: Range<Addr>,
+}
+
+impl LoadedModule {
+    /// Parse a single line of `/proc/modules`.
+    ///
+    /// Lines have the form `name size refcount deps state addr [taint]`.
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let name = fields.next()?.to_string();
+        let size = fields.next()?.parse::<Addr>().ok()?;
+        let addr = parse_hex(fields.nth(3)?)?;
+
+        let module = Self {
+            name,
+            range: addr..addr.saturating_add(size),
+        };
+        Some(module)
+    }
+}
+
+
+/// Parse a file in the format of `/proc/modules`.
+///
+/// Modules without a known address (e.g., because the kernel hides
+/// addresses from unprivileged users) are skipped.
+pub(crate) fn parse_modules(path: &Path) -> Result<Vec<LoadedModule>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+
+    let mut modules = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue
+        }
+
+        let module = LoadedModule::parse(&line).ok_or_invalid_data(|| {
+            format!(
+                "encountered malformed entry in line {} of {}: {line}",
+                idx + 1,
+                path.display()
+            )
+        })?;
+        if module.range.start != 0 {
+            let () = modules.push(module);
+        }
+    }
+    Ok(modules)
+}
+
+
+/// Read the load addresses of a module's sections from `dir`, which is
+/// expected to be in the format of `/sys/module/<module>/sections/`.
+///
+/// Sections without a known address are skipped.
+pub(crate) fn read_section_addrs(dir: &Path) -> Result<Vec<(String, Addr)>> {
+    let mut sections = Vec::new();
+    for entry in read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = if let Some(name) = name.to_str() {
+            name
+        } else {
+            continue
+        };
+
+        let path = entry.path();
+        let content =
+            read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let addr = parse_hex(content.trim()).ok_or_invalid_data(|| {
+            format!("encountered invalid section address in {}", path.display())
+        })?;
+        if addr != 0 {
+            let () = sections.push((name.to_string(), addr));
+        }
+    }
+    Ok(sections)
+}
+
+
+/// Find all kernel module (`.ko`) files in `dir` and its
+/// subdirectories, keyed by module name.
+///
+/// Module names use underscores where file names may use dashes, so
+/// the former are used throughout.
+pub(crate) fn find_module_files(dir: &Path) -> HashMap<String, PathBuf> {
+    fn find(dir: &Path, files: &mut HashMap<String, PathBuf>) {
+        let entries = match read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                debug!("failed to read directory {}: {err}", dir.display());
+                return
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(typ) if typ.is_dir() => find(&path, files),
+                Ok(_) => {
+                    if let Some(name) = path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(|name| name.strip_suffix(".ko"))
+                    {
+                        let _prev = files
+                            .entry(name.replace('-', "_"))
+                            .or_insert_with(|| path.clone());
+                    }
+                }
+                Err(_) => (),
+            }
+        }
+    }
+
+    let mut files = HashMap::new();
+    let () = find(dir, &mut files);
+    files
+}
+
+
+/// The mapping of a loaded section to its location in the `.ko` file.
+#[derive(Debug)]
+struct SectionMapping {
+    /// The address range the section was loaded to.
+    load: Range<Addr>,
+    /// The address of the section in the `.ko` file.
+    file_addr: Addr,
+}
+
+
+/// The symbol resolver for a loaded kernel module.
+///
+/// Addresses handed to this resolver are kernel addresses. They are
+/// translated into addresses in the module's `.ko` file based on the
+/// load addresses of its sections.
+pub(crate) struct ModuleResolver {
+    /// The name of the module.
+    name: String,
+    /// The mappings of the module's sections, sorted by load address.
+    sections: Vec<SectionMapping>,
+    /// The resolver for the module's `.ko` file.
+    resolver: ElfResolver,
+}
+
+impl ModuleResolver {
+    /// Create a new `ModuleResolver` for the module `name`, which has
+    /// its sections loaded to `section_addrs`.
+    pub fn new(
+        name: String,
+        section_addrs: &[(String, Addr)],
+        resolver: ElfResolver,
+    ) -> Result<Self> {
+        let parser = resolver.get_parser();
+        let shdrs = parser.section_headers()?;
+
+        let mut sections = Vec::new();
+        for (section, addr) in section_addrs {
+            let idx = if let Some(idx) = parser.find_section(section)? {
+                idx
+            } else {
+                continue
+            };
+            // SANITY: `find_section` returned a valid section index.
+            let shdr = &shdrs[idx];
+            if shdr.sh_flags & SHF_ALLOC == 0 || shdr.sh_size == 0 {
+                continue
+            }
+
+            let () = sections.push(SectionMapping {
+                load: *addr..addr.saturating_add(shdr.sh_size as Addr),
+                file_addr: parser.section_addr(idx)? as Addr,
+            });
+        }
+        let () = sections.sort_by_key(|mapping| mapping.load.start);
+
+        let slf = Self {
+            name,
+            sections,
+            resolver,
+        };
+        Ok(slf)
+    }
+
+    /// Check whether `addr` belongs to one of the module's sections.
+    pub fn contains(&self, addr: Addr) -> bool {
+        self.file_addr(addr).is_some()
+    }
+
+    /// Translate a kernel address into an address in the `.ko` file.
+    fn file_addr(&self, addr: Addr) -> Option<Addr> {
+        let idx = self
+            .sections
+            .partition_point(|mapping| mapping.load.start <= addr);
+        let mapping = self.sections.get(idx.checked_sub(1)?)?;
+        mapping
+            .load
+            .contains(&addr)
+            .then(|| mapping.file_addr + (addr - mapping.load.start))
+    }
+
+    /// Translate an address in the `.ko` file into a kernel address.
+    fn kernel_addr(&self, file_addr: Addr) -> Option<Addr> {
+        self.sections.iter().find_map(|mapping| {
+            let size = mapping.load.end - mapping.load.start;
+            (mapping.file_addr..mapping.file_addr + size)
+                .contains(&file_addr)
+                .then(|| mapping.load.start + (file_addr - mapping.file_addr))
+        })
+    }
+}
+
+impl SymResolver for ModuleResolver {
+    fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
+        let file_addr = if let Some(file_addr) = self.file_addr(addr) {
+            file_addr
+        } else {
+            return Ok(Vec::new())
+        };
+
+        let syms = self
+            .resolver
+            .find_syms(file_addr)?
+            .into_iter()
+            .map(|sym| IntSym {
+                // The symbol starts at the same offset before the
+                // address in either address space.
+                addr: addr - (file_addr - sym.addr),
+                module: Some(&self.name),
+                ..sym
+            })
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_addr(&self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo>> {
+        let syms = self
+            .resolver
+            .find_addr(name, opts)?
+            .into_iter()
+            .filter_map(|sym| {
+                let addr = self.kernel_addr(sym.addr)?;
+                Some(SymInfo { addr, ..sym })
+            })
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_line_info(&self, addr: Addr) -> Result<Option<AddrLineInfo<'_>>> {
+        if let Some(file_addr) = self.file_addr(addr) {
+            self.resolver.find_line_info(file_addr)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        if let Some(file_addr) = self.file_addr(addr) {
+            self.resolver.find_inlined_fns(file_addr)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
+        None
+    }
+}
+
+impl Debug for ModuleResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "ModuleResolver {} {}",
+            self.name,
+            self.resolver.file_name().display()
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs::create_dir_all;
+    use std::fs::write;
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+    use test_log::test;
+
+    use crate::dwarf::DwarfResolver;
+    use crate::elf::ElfBackend;
+
+
+    /// Check that we can parse `/proc/modules` contents.
+    #[test]
+    fn proc_modules_parsing() {
+        let dir = tempdir().unwrap();
+        let modules = dir.path().join("modules");
+        let () = write(
+            &modules,
+            "nf_tables 344064 0 - Live 0xffffffffc0b41000\n\
+             hidden 4096 0 - Live 0x0000000000000000\n\
+             kvm_intel 479232 0 - Live 0xffffffffc0a2d000 (E)\n",
+        )
+        .unwrap();
+
+        let modules = parse_modules(&modules).unwrap();
+        assert_eq!(
+            modules,
+            vec![
+                LoadedModule {
+                    name: "nf_tables".to_string(),
+                    range: 0xffffffffc0b41000..0xffffffffc0b41000 + 344064,
+                },
+                LoadedModule {
+                    name: "kvm_intel".to_string(),
+                    range: 0xffffffffc0a2d000..0xffffffffc0a2d000 + 479232,
+                },
+            ]
+        );
+
+        let () = write(dir.path().join("bogus"), "nf_tables 344064\n").unwrap();
+        let err = parse_modules(&dir.path().join("bogus")).unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+    }
+
+    /// Check that we can read section load addresses and find module
+    /// files.
+    #[test]
+    fn module_discovery() {
+        let dir = tempdir().unwrap();
+        let sections = dir.path().join("sections");
+        let () = create_dir_all(&sections).unwrap();
+        let () = write(sections.join(".text"), "0xffffffffc0b41000\n").unwrap();
+        let () = write(sections.join(".data"), "0x0000000000000000\n").unwrap();
+
+        let addrs = read_section_addrs(&sections).unwrap();
+        assert_eq!(addrs, vec![(".text".to_string(), 0xffffffffc0b41000)]);
+
+        let modules = dir.path().join("kernel").join("net");
+        let () = create_dir_all(&modules).unwrap();
+        let () = write(modules.join("nf-tables.ko"), "").unwrap();
+        let () = write(modules.join("nf_tables.c"), "").unwrap();
+
+        let files = find_module_files(dir.path());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files.get("nf_tables"), Some(&modules.join("nf-tables.ko")));
+    }
+
+    /// Check that we can symbolize addresses in a kernel module based
+    /// on the load addresses of its sections.
+    #[test]
+    fn module_symbolization() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-rel.bin");
+        let dwarf = DwarfResolver::open(&path, true, false).unwrap();
+        let backend = ElfBackend::Dwarf(Arc::new(dwarf));
+        let resolver = ElfResolver::with_backend(&path, backend).unwrap();
+
+        let sections = [
+            (".text".to_string(), 0xffffffffc0001000),
+            (".text.factorial".to_string(), 0xffffffffc0002000),
+            (".text.main".to_string(), 0xffffffffc0003000),
+        ];
+        let resolver = ModuleResolver::new("test".to_string(), &sections, resolver).unwrap();
+
+        assert!(resolver.contains(0xffffffffc0002004));
+        assert!(!resolver.contains(0xffffffffc0004000));
+
+        let syms = resolver.find_syms(0xffffffffc0002004).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "factorial");
+        assert_eq!(syms[0].addr, 0xffffffffc0002000);
+        assert_eq!(syms[0].module, Some("test"));
+
+        let info = resolver
+            .find_line_info(0xffffffffc0002004)
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.file, "test-stable-addresses.c");
+        assert_eq!(info.line, Some(8));
+
+        let syms = resolver.find_syms(0xffffffffc0003000).unwrap();
+        assert_eq!(syms[0].name, "main");
+        let info = resolver
+            .find_line_info(0xffffffffc0003008)
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.line, Some(34));
+
+        let opts = FindAddrOpts::default();
+        let infos = resolver.find_addr("factorial", &opts).unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].addr, 0xffffffffc0002000);
+
+        assert!(resolver.find_syms(0xffffffffc0004000).unwrap().is_empty());
+    }
+}
@@ -1,4 +1,3 @@
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
@@ -8,13 +7,16 @@ use std::io::BufRead;
 use std::io::BufReader;
 use std::path::Path;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
 
 use crate::inspect::FindAddrOpts;
 use crate::inspect::SymInfo;
 use crate::inspect::SymType;
 use crate::symbolize::AddrLineInfo;
 use crate::Addr;
+use crate::IntInlinedFn;
 use crate::IntSym;
 use crate::Result;
 use crate::SrcLang;
@@ -27,17 +29,20 @@ const DFL_KSYM_CAP: usize = 200000;
 pub struct Ksym {
     pub addr: Addr,
     pub name: String,
+    /// The name of the kernel module the symbol belongs to, if any.
+    pub module: Option<String>,
 }
 
 impl<'ksym> From<&'ksym Ksym> for IntSym<'ksym> {
     fn from(other: &'ksym Ksym) -> Self {
-        let Ksym { name, addr } = other;
+        let Ksym { name, addr, module } = other;
         IntSym {
             name,
             addr: *addr,
             // Kernel symbols don't carry any source code language
             // information.
             lang: SrcLang::Unknown,
+            module: module.as_deref(),
         }
     }
 }
@@ -48,7 +53,7 @@ impl<'ksym> From<&'ksym Ksym> for IntSym<'ksym> {
 /// a copy from other devices.
 pub struct KSymResolver {
     syms: Vec<Ksym>,
-    sym_to_addr: RefCell<HashMap<&'static str, Addr>>,
+    sym_to_addr: Mutex<HashMap<&'static str, Addr>>,
     file_name: PathBuf,
 }
 
@@ -75,7 +80,14 @@ impl KSymResolver {
                     continue
                 }
                 let name = String::from(func);
-                syms.push(Ksym { addr, name });
+                // Symbols of loadable modules have the module name
+                // appended, in the form `[module]`.
+                let module = tokens
+                    .get(3)
+                    .and_then(|module| module.strip_prefix('['))
+                    .and_then(|module| module.strip_suffix(']'))
+                    .map(String::from);
+                syms.push(Ksym { addr, name, module });
             }
 
             line.truncate(0);
@@ -85,22 +97,23 @@ impl KSymResolver {
 
         let slf = Self {
             syms,
-            sym_to_addr: RefCell::default(),
+            sym_to_addr: Mutex::default(),
             file_name: filename,
         };
         Ok(slf)
     }
 
-    fn ensure_sym_to_addr(&self) {
-        if self.sym_to_addr.borrow().len() > 0 {
-            return
+    fn ensure_sym_to_addr(&self) -> MutexGuard<'_, HashMap<&'static str, Addr>> {
+        let mut sym_to_addr = self.sym_to_addr.lock().unwrap();
+        if !sym_to_addr.is_empty() {
+            return sym_to_addr
         }
-        let mut sym_to_addr = self.sym_to_addr.borrow_mut();
-        for Ksym { name, addr } in self.syms.iter() {
+        for Ksym { name, addr, .. } in self.syms.iter() {
             // Performance & lifetime hacking
             let name_static = unsafe { &*(name as *const String) };
             sym_to_addr.insert(name_static, *addr);
         }
+        sym_to_addr
     }
 
     pub fn find_addresses_ksym(&self, addr: Addr) -> impl Iterator<Item = &Ksym> {
@@ -155,9 +168,7 @@ impl SymResolver for KSymResolver {
         if let SymType::Variable = opts.sym_type {
             return Ok(Vec::new())
         }
-        let () = self.ensure_sym_to_addr();
-
-        let sym_to_addr = self.sym_to_addr.borrow();
+        let sym_to_addr = self.ensure_sym_to_addr();
         if let Some(addr) = sym_to_addr.get(name) {
             Ok(vec![SymInfo {
                 name: name.to_string(),
@@ -176,6 +187,10 @@ impl SymResolver for KSymResolver {
         Ok(None)
     }
 
+    fn find_inlined_fns(&self, _addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        Ok(Vec::new())
+    }
+
     fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
         None
     }
@@ -193,25 +208,25 @@ impl Debug for KSymResolver {
 /// It returns the same instance if path is the same.
 #[derive(Debug)]
 pub struct KSymCache {
-    resolvers: RefCell<HashMap<PathBuf, Rc<KSymResolver>>>,
+    resolvers: Mutex<HashMap<PathBuf, Arc<KSymResolver>>>,
 }
 
 impl KSymCache {
     pub fn new() -> KSymCache {
         KSymCache {
-            resolvers: RefCell::new(HashMap::new()),
+            resolvers: Mutex::new(HashMap::new()),
         }
     }
 
     /// Find an instance of KSymResolver from the cache or create a new one.
-    pub fn get_resolver(&self, path: &Path) -> Result<Rc<KSymResolver>> {
-        let mut resolvers = self.resolvers.borrow_mut();
+    pub fn get_resolver(&self, path: &Path) -> Result<Arc<KSymResolver>> {
+        let mut resolvers = self.resolvers.lock().unwrap();
         if let Some(resolver) = resolvers.get(path) {
             return Ok(resolver.clone())
         }
 
         let resolver = KSymResolver::load_file_name(path.to_path_buf())?;
-        let resolver = Rc::new(resolver);
+        let resolver = Arc::new(resolver);
         resolvers.insert(path.to_path_buf(), resolver.clone());
         Ok(resolver)
     }
@@ -222,6 +237,7 @@ mod tests {
     use super::*;
 
     use std::cmp::Ordering;
+    use std::thread;
 
     use test_log::test;
 
@@ -284,6 +300,25 @@ mod tests {
         assert!(found.iter().any(|x| x.addr == addr));
     }
 
+    /// Check that we retain the module name of symbols belonging to
+    /// loadable kernel modules.
+    #[test]
+    fn ksym_module_names() {
+        let kallsyms = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("kallsyms");
+        let resolver = KSymResolver::load_file_name(kallsyms).unwrap();
+
+        let syms = resolver.find_syms(0xbf00ae80).unwrap();
+        assert!(!syms.is_empty());
+        let sym = syms.iter().find(|sym| sym.name == "scanarg").unwrap();
+        assert_eq!(sym.module, Some("binfmt_misc"));
+
+        let syms = resolver.find_syms(0xc01ba2a8).unwrap();
+        assert!(syms.iter().any(|sym| sym.name == "do_sys_open"));
+        assert!(syms.iter().all(|sym| sym.module.is_none()));
+    }
+
     #[test]
     fn ksym_cache() {
         let kallsyms = Path::new(&env!("CARGO_MANIFEST_DIR"))
@@ -296,6 +331,46 @@ mod tests {
         assert!(resolver1.is_ok());
     }
 
+    /// Check that a `KSymCache` can be used from multiple threads
+    /// concurrently and hands out the same resolver to all of them.
+    #[test]
+    fn ksym_cache_multi_threaded() {
+        let kallsyms = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("kallsyms");
+        let cache = Arc::new(KSymCache::new());
+
+        let handles = (0..4)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let kallsyms = kallsyms.clone();
+                thread::spawn(move || {
+                    let opts = FindAddrOpts {
+                        offset_in_file: false,
+                        obj_file_name: false,
+                        sym_type: SymType::Function,
+                    };
+                    let resolver = cache.get_resolver(&kallsyms).unwrap();
+                    let syms = resolver.find_addr("do_sys_open", &opts).unwrap();
+                    assert_eq!(syms.len(), 1);
+                    assert_eq!(syms[0].addr, 0xc01ba2a8);
+
+                    let syms = resolver.find_syms(0xc01ba2a8).unwrap();
+                    assert!(syms.iter().any(|sym| sym.name == "do_sys_open"));
+                    resolver
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let resolvers = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        assert!(resolvers
+            .windows(2)
+            .all(|pair| Arc::ptr_eq(&pair[0], &pair[1])));
+    }
+
     #[test]
     fn find_addresses_ksym() {
         let resolver = KSymResolver {
@@ -303,21 +378,25 @@ mod tests {
                 Ksym {
                     addr: 0x123,
                     name: "1".to_string(),
+                    module: None,
                 },
                 Ksym {
                     addr: 0x123,
                     name: "1.5".to_string(),
+                    module: None,
                 },
                 Ksym {
                     addr: 0x1234,
                     name: "2".to_string(),
+                    module: None,
                 },
                 Ksym {
                     addr: 0x12345,
                     name: "3".to_string(),
+                    module: None,
                 },
             ],
-            sym_to_addr: RefCell::default(),
+            sym_to_addr: Mutex::default(),
             file_name: PathBuf::new(),
         };
 
@@ -373,9 +452,10 @@ mod tests {
                 .map(|x| Ksym {
                     addr: 1,
                     name: x.to_string(),
+                    module: None,
                 })
                 .collect(),
-            sym_to_addr: RefCell::default(),
+            sym_to_addr: Mutex::default(),
             file_name: PathBuf::new(),
         };
 
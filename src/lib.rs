@@ -30,29 +30,38 @@
 #[cfg(feature = "nightly")]
 extern crate test;
 
+mod bpf;
+mod breakpad;
 pub mod c_api;
+mod coredump;
 #[cfg(feature = "dwarf")]
 mod dwarf;
 mod elf;
 mod error;
 mod gsym;
 pub mod inspect;
+mod jitdump;
 mod kernel;
+mod kmod;
 mod ksym;
 mod maps;
 mod mmap;
 pub mod normalize;
+mod perf_map;
 mod resolver;
 pub mod symbolize;
 mod util;
+mod vdso;
 mod zip;
 
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::num::NonZeroU32;
+use std::process;
 use std::result;
 
+use resolver::IntInlinedFn;
 use resolver::IntSym;
 use resolver::SrcLang;
 use resolver::SymResolver;
@@ -92,6 +101,16 @@ pub enum Pid {
     Pid(NonZeroU32),
 }
 
+impl Pid {
+    /// Retrieve the numeric ID of the referenced process.
+    pub(crate) fn resolve(self) -> u32 {
+        match self {
+            Self::Slf => process::id(),
+            Self::Pid(pid) => pid.get(),
+        }
+    }
+}
+
 impl Display for Pid {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
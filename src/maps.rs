@@ -8,8 +8,13 @@ use std::io::Error;
 use std::io::ErrorKind;
 use std::io::Read;
 use std::ops::Range;
+use std::os::unix::io::AsRawFd as _;
+use std::path::Path;
 use std::path::PathBuf;
 
+use crate::log::debug;
+use crate::normalize::MapsFileAccess;
+use crate::util::fstat;
 use crate::Addr;
 use crate::ErrorExt as _;
 use crate::IntoError as _;
@@ -17,7 +22,20 @@ use crate::Pid;
 use crate::Result;
 
 
-#[derive(Debug, Eq, Hash, PartialEq)]
+/// The path to the file backing a proc maps entry via the process' root
+/// directory, along with the identity of the file that got mapped.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct RootFile {
+    /// The path of the file via a `/proc/<xxx>/root/` component.
+    pub path: PathBuf,
+    /// The device containing the mapped file.
+    pub dev: libc::dev_t,
+    /// The inode of the mapped file.
+    pub inode: libc::ino_t,
+}
+
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub(crate) struct EntryPath {
     /// The path of the file backing the maps entry via a
     /// `/proc/<xxx>/map_files/` component.
@@ -31,6 +49,108 @@ pub(crate) struct EntryPath {
     /// parsed. This path has been sanitized and no longer contains any
     /// `(deleted)` suffixes.
     pub symbolic_path: PathBuf,
+    /// The path to the file via the process' root directory, used as a
+    /// fallback if `maps_file` is inaccessible.
+    ///
+    /// Accessing `/proc/<xxx>/map_files/` requires `CAP_SYS_ADMIN`,
+    /// which we may not have. The root directory, on the other hand,
+    /// only requires ptrace access to the process and it also works for
+    /// processes in a different mount namespace (e.g., inside a
+    /// container). Deleted files can't be accessed this way.
+    pub root_file: Option<RootFile>,
+    /// How the file is accessed on the local system, once
+    /// [resolved][EntryPath::resolve].
+    pub access: Option<MapsFileAccess>,
+}
+
+impl EntryPath {
+    /// Create an `EntryPath` for the provided paths.
+    pub(crate) fn new(
+        maps_file: PathBuf,
+        symbolic_path: PathBuf,
+        root_file: Option<RootFile>,
+    ) -> Self {
+        Self {
+            maps_file,
+            symbolic_path,
+            root_file,
+            access: None,
+        }
+    }
+
+    /// Determine how the file backing the proc maps entry can be
+    /// accessed on the local system.
+    ///
+    /// `maps_file` is used if accessible, with `root_file` as the
+    /// fallback. In the latter case we check that the file still is the
+    /// one that got mapped, as it may have been replaced since.
+    fn find_access(&self) -> Result<MapsFileAccess> {
+        let err = match self.maps_file.metadata() {
+            Ok(_) => return Ok(MapsFileAccess::MapFiles),
+            Err(err) => err,
+        };
+
+        let root_file = if let Some(root_file) = &self.root_file {
+            root_file
+        } else {
+            return Ok(MapsFileAccess::MapFiles)
+        };
+
+        debug!(
+            "failed to access {}: {err}; falling back to {}",
+            self.maps_file.display(),
+            root_file.path.display()
+        );
+
+        let file = File::open(&root_file.path).with_context(|| {
+            format!(
+                "failed to access {} via {} ({err}) or {}",
+                self.symbolic_path.display(),
+                self.maps_file.display(),
+                root_file.path.display()
+            )
+        })?;
+        let stat = fstat(file.as_raw_fd())?;
+        if stat.st_dev != root_file.dev || stat.st_ino != root_file.inode {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "file {} is not the one mapped into the process (device or inode mismatch)",
+                    root_file.path.display()
+                ),
+            )
+            .into())
+        }
+        Ok(MapsFileAccess::ProcRoot)
+    }
+
+    /// Determine how the file backing the proc maps entry can be
+    /// accessed on the local system and remember the result for
+    /// subsequent [`EntryPath::local_path`] calls.
+    pub(crate) fn resolve(&mut self) -> Result<()> {
+        if self.access.is_none() {
+            self.access = Some(self.find_access()?);
+        }
+        Ok(())
+    }
+
+    /// Find the path through which the file backing the proc maps entry
+    /// can be accessed on the local system, along with the strategy
+    /// used for accessing it.
+    ///
+    /// Unless the entry has been [resolved][EntryPath::resolve] already,
+    /// this function has to check the file's accessibility.
+    pub(crate) fn local_path(&self) -> Result<(&Path, MapsFileAccess)> {
+        let access = match self.access {
+            Some(access) => access,
+            None => self.find_access()?,
+        };
+        let path = match (access, &self.root_file) {
+            (MapsFileAccess::ProcRoot, Some(root_file)) => &root_file.path,
+            _ => &self.maps_file,
+        };
+        Ok((path, access))
+    }
 }
 
 
@@ -72,6 +192,7 @@ pub(crate) struct MapsEntry {
 
 
 /// An already filtered `MapsEntry` that is guaranteed to contain a path.
+#[derive(Clone)]
 pub(crate) struct PathMapsEntry {
     /// The virtual address range covered by this entry.
     pub range: Range<Addr>,
@@ -149,27 +270,55 @@ fn parse_maps_line<'line>(line: &'line str, pid: Pid) -> Result<MapsEntry> {
         )
     })?;
 
-    let (_dev, line) = split_once(line, "device component")?;
+    let (dev_str, line) = split_once(line, "device component")?;
     // Note that by design, a path may not be present and so we may not be able
     // to successfully split.
-    let path_str = split_once(line, "inode component")
-        .map(|(_inode, line)| line.trim())
-        .unwrap_or("");
+    let (inode_str, path_str) = split_once(line, "inode component")
+        .map(|(inode, line)| (inode, line.trim()))
+        .unwrap_or((line, ""));
 
     let path_name = match path_str.as_bytes() {
         [] => None,
         [b'/', ..] => {
-            let symbolic_path =
-                PathBuf::from(path_str.strip_suffix(" (deleted)").unwrap_or(path_str));
+            let deleted_path = path_str.strip_suffix(" (deleted)");
+            let symbolic_path = PathBuf::from(deleted_path.unwrap_or(path_str));
             // TODO: May have to resolve the symbolic link in case of
             //       `Pid::Slf` here for remote symbolization use cases.
             let maps_file = PathBuf::from(format!(
                 "/proc/{pid}/map_files/{loaded_addr:x}-{end_addr:x}"
             ));
-            Some(PathName::Path(EntryPath {
+            let root_file = if deleted_path.is_none() {
+                let dev = dev_str
+                    .split_once(':')
+                    .and_then(|(major, minor)| {
+                        let major = u32::from_str_radix(major, 16).ok()?;
+                        let minor = u32::from_str_radix(minor, 16).ok()?;
+                        Some(libc::makedev(major, minor))
+                    })
+                    .ok_or_invalid_data(|| {
+                        format!(
+                            "encountered malformed device component in proc maps line: {full_line}"
+                        )
+                    })?;
+                let inode = inode_str.parse().map_err(|err| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("encountered malformed inode component in proc maps line: {full_line}: {err}"),
+                    )
+                })?;
+                Some(RootFile {
+                    path: PathBuf::from(format!("/proc/{pid}/root{path_str}")),
+                    dev,
+                    inode,
+                })
+            } else {
+                None
+            };
+            Some(PathName::Path(EntryPath::new(
                 maps_file,
                 symbolic_path,
-            }))
+                root_file,
+            )))
         }
         // This variant would typically capture components such as `[vdso]` or
         // `[heap]`, but we can't rely on square brackets being present
@@ -247,6 +396,22 @@ pub(crate) fn parse(pid: Pid) -> Result<impl Iterator<Item = Result<MapsEntry>>>
     Ok(iter)
 }
 
+/// Check whether a `MapsEntry` describes anonymous executable memory,
+/// as typically used for just-in-time compiled code.
+pub(crate) fn is_anon_exec(entry: &MapsEntry) -> bool {
+    if (entry.mode & 0b0010) != 0b0010 {
+        return false
+    }
+
+    match &entry.path_name {
+        None => true,
+        // Named anonymous mappings show up as `[anon:<name>]`.
+        Some(PathName::Component(component)) => component.starts_with("[anon"),
+        Some(PathName::Path(..)) => false,
+    }
+}
+
+
 /// A helper function checking whether a `MapsEntry` has relevance to
 /// symbolization efforts and converting it accordingly.
 pub(crate) fn filter_map_relevant(entry: MapsEntry) -> Option<PathMapsEntry> {
@@ -290,10 +455,11 @@ mod tests {
             range: 0x1000..0x1337,
             mode: 0b10,
             offset: 0x5000,
-            path: EntryPath {
-                maps_file: PathBuf::from("/proc/1234/maps_files/559cf1bdf000-559cf1be0000"),
-                symbolic_path: PathBuf::from("/lib64/libc.so.6"),
-            },
+            path: EntryPath::new(
+                PathBuf::from("/proc/1234/maps_files/559cf1bdf000-559cf1be0000"),
+                PathBuf::from("/lib64/libc.so.6"),
+                None,
+            ),
         };
 
         let dbg = format!("{entry:?}");
@@ -377,6 +543,20 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
                 .maps_file,
             Path::new("/proc/self/map_files/400000-401000")
         );
+        assert_eq!(
+            entry
+                .path_name
+                .as_ref()
+                .unwrap()
+                .as_path()
+                .unwrap()
+                .root_file,
+            Some(RootFile {
+                path: PathBuf::from("/proc/self/root/tmp/test/test"),
+                dev: libc::makedev(0, 0x29),
+                inode: 47459,
+            })
+        );
 
         let entry = parse_maps_line(lines.lines().nth(7).unwrap(), Pid::Slf).unwrap();
         assert_eq!(entry.range.start, 0x55f4a95cb000);
@@ -416,6 +596,56 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
                 .maps_file,
             Path::new("/proc/self/map_files/7f2321e00000-7f2321e37000")
         );
+        // Deleted files can't be accessed via the root directory.
+        assert_eq!(
+            entry
+                .path_name
+                .as_ref()
+                .unwrap()
+                .as_path()
+                .unwrap()
+                .root_file,
+            None
+        );
+    }
+
+    /// Check that we fall back to accessing files via the process' root
+    /// directory if `map_files` is inaccessible.
+    #[test]
+    fn entry_path_root_fallback() {
+        let mut path = parse(Pid::Slf)
+            .unwrap()
+            .map(Result::unwrap)
+            .find_map(|entry| match entry.path_name {
+                Some(PathName::Path(path)) if path.root_file.is_some() => Some(path),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            path.local_path().unwrap(),
+            (path.maps_file.as_path(), MapsFileAccess::MapFiles)
+        );
+
+        // Pretend that `map_files` is inaccessible.
+        path.maps_file = PathBuf::from("/proc/self/map_files/does-not-exist");
+        let root_path = path.root_file.as_ref().unwrap().path.clone();
+        assert_eq!(
+            path.local_path().unwrap(),
+            (root_path.as_path(), MapsFileAccess::ProcRoot)
+        );
+
+        // Once resolved, the result should be reused.
+        let () = path.resolve().unwrap();
+        assert_eq!(path.access, Some(MapsFileAccess::ProcRoot));
+
+        // A file other than the mapped one must not be used.
+        path.access = None;
+        path.root_file.as_mut().unwrap().inode += 1;
+        let err = path.local_path().unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::NotFound, "{err}");
+        let err = path.resolve().unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::NotFound, "{err}");
+        assert_eq!(path.access, None);
     }
 
     /// Check that we error out as expected on malformed proc maps lines.
@@ -433,6 +663,8 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
             "7fa7bb75a000-7fa7bb75c000 r--p",
             "7fa7bb75a000-7fa7bb75c000 r--p 00000000",
             "7fa7bb75a000-7fa7bb75c000 r--p 000zz000 00:20",
+            "7fa7bb75a000-7fa7bb75c000 r--p 00000000 00-20 12023220 /usr/lib64/libc.so.6",
+            "7fa7bb75a000-7fa7bb75c000 r--p 00000000 00:20 120zz220 /usr/lib64/libc.so.6",
         ];
 
         let () = lines.iter().for_each(|line| {
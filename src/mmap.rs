@@ -4,9 +4,10 @@ use std::ops::Deref;
 use std::ops::Range;
 use std::os::unix::io::AsRawFd;
 use std::path::Path;
+use std::ptr;
 use std::ptr::null_mut;
-use std::rc::Rc;
 use std::slice;
+use std::sync::Arc;
 
 use crate::Error;
 use crate::ErrorExt as _;
@@ -67,7 +68,45 @@ impl Builder {
 
         let mapping = Mapping { ptr, len };
         let mmap = Mmap {
-            mapping: Rc::new(mapping),
+            mapping: Arc::new(mapping),
+            view: 0..len,
+        };
+        Ok(mmap)
+    }
+
+    /// Create an anonymous memory mapping containing a copy of `data`.
+    pub fn map_bytes(self, data: &[u8]) -> Result<Mmap> {
+        let len = data.len();
+
+        // SAFETY: `mmap` with the provided arguments is always safe to call.
+        let ptr = unsafe {
+            libc::mmap(
+                null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+
+        if ptr == libc::MAP_FAILED {
+            return Err(Error::from(io::Error::last_os_error()))
+        }
+
+        let mapping = Mapping { ptr, len };
+        // SAFETY: The mapping is writable and `len` bytes in size and
+        //         it cannot overlap with `data`.
+        let () = unsafe { ptr::copy_nonoverlapping(data.as_ptr(), ptr.cast(), len) };
+
+        // SAFETY: `ptr` and `len` describe a valid mapping.
+        let rc = unsafe { libc::mprotect(ptr, len, self.protection) };
+        if rc != 0 {
+            return Err(Error::from(io::Error::last_os_error()))
+        }
+
+        let mmap = Mmap {
+            mapping: Arc::new(mapping),
             view: 0..len,
         };
         Ok(mmap)
@@ -81,6 +120,13 @@ pub(crate) struct Mapping {
     len: usize,
 }
 
+// SAFETY: `Mapping` represents a memory region that is only ever read
+//         from and that stays valid until the object is dropped, so it
+//         can be sent to and shared with other threads.
+unsafe impl Send for Mapping {}
+// SAFETY: See above.
+unsafe impl Sync for Mapping {}
+
 impl Deref for Mapping {
     type Target = [u8];
 
@@ -104,7 +150,7 @@ impl Drop for Mapping {
 #[derive(Clone, Debug)]
 pub(crate) struct Mmap {
     /// The actual memory mapping.
-    mapping: Rc<Mapping>,
+    mapping: Arc<Mapping>,
     /// The view on the memory mapping that this object represents.
     view: Range<usize>,
 }
@@ -120,6 +166,11 @@ impl Mmap {
         Self::builder().map(file)
     }
 
+    /// Create an anonymous memory mapping containing a copy of `data`.
+    pub fn map_bytes(data: &[u8]) -> Result<Self> {
+        Self::builder().map_bytes(data)
+    }
+
     /// Create a new `Mmap` object (sharing the same underlying memory mapping
     /// as the current one) that restricts its view to the provided `range`.
     /// Adjustment happens relative to the current view.
@@ -181,6 +232,17 @@ mod tests {
         );
     }
 
+    /// Check that we can create a memory mapping from a byte slice.
+    #[test]
+    fn mmap_bytes() {
+        let s = b"abcdefghijklmnopqrstuvwxyz";
+        let mmap = Mmap::map_bytes(s).unwrap();
+        assert_eq!(mmap.deref(), s);
+
+        let mmap = mmap.constrain(2..4).unwrap();
+        assert_eq!(mmap.deref(), b"cd");
+    }
+
     /// Check that we can properly restrict the view of a `Mmap`.
     #[test]
     fn view_constraining() {
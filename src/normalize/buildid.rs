@@ -2,6 +2,7 @@ use std::fs::File;
 use std::path::Path;
 
 use crate::elf;
+use crate::elf::types::ByteSwap as _;
 use crate::elf::types::Elf64_Nhdr;
 use crate::elf::ElfParser;
 use crate::log::warn;
@@ -25,8 +26,9 @@ fn read_build_id_from_notes(parser: &ElfParser) -> Result<Option<Vec<u8>>> {
             //         be found.
             let mut bytes = parser.section_data(idx).unwrap();
             let header = bytes
-                .read_pod_ref::<Elf64_Nhdr>()
-                .ok_or_invalid_data(|| "failed to read build ID section header")?;
+                .read_pod::<Elf64_Nhdr>()
+                .ok_or_invalid_data(|| "failed to read build ID section header")?
+                .byte_swap_if(parser.is_swapped()?);
             if header.n_type == elf::types::NT_GNU_BUILD_ID {
                 // Type check is assumed to suffice, but we still need
                 // to skip the name bytes.
@@ -65,8 +67,9 @@ fn read_build_id_from_section_name(parser: &ElfParser) -> Result<Option<Vec<u8>>
         //         found.
         let mut bytes = parser.section_data(idx).unwrap();
         let header = bytes
-            .read_pod_ref::<Elf64_Nhdr>()
-            .ok_or_invalid_data(|| "failed to read build ID section header")?;
+            .read_pod::<Elf64_Nhdr>()
+            .ok_or_invalid_data(|| "failed to read build ID section header")?
+            .byte_swap_if(parser.is_swapped()?);
         let name = bytes
             .read_slice(header.n_namesz as _)
             .and_then(|mut name| name.read_cstr())
@@ -86,13 +89,13 @@ fn read_build_id_from_section_name(parser: &ElfParser) -> Result<Option<Vec<u8>>
     }
 }
 
-pub(super) trait BuildIdReader: 'static {
+pub(crate) trait BuildIdReader: 'static {
     fn read_build_id_from_elf(path: &Path) -> Result<Option<Vec<u8>>>;
     fn read_build_id(parser: &ElfParser) -> Result<Option<Vec<u8>>>;
 }
 
 
-pub(super) struct DefaultBuildIdReader;
+pub(crate) struct DefaultBuildIdReader;
 
 impl BuildIdReader for DefaultBuildIdReader {
     /// Attempt to read an ELF binary's build ID.
@@ -187,6 +190,28 @@ mod tests {
         test(read_build_id_from_notes);
     }
 
+    /// Check that we can read the build ID of a 32 bit ELF binary.
+    #[test]
+    fn build_id_reading_elf32() {
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("libtest-so-32.so");
+        let build_id = read_elf_build_id(&elf).unwrap().unwrap();
+        // The file contains a sha1 build ID, which is always 20 bytes in length.
+        assert_eq!(build_id.len(), 20, "'{build_id:?}'");
+    }
+
+    /// Check that we can read the build ID of a big-endian ELF binary.
+    #[test]
+    fn build_id_reading_big_endian() {
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-big-endian.bin");
+        let build_id = read_elf_build_id(&elf).unwrap().unwrap();
+        // The file contains a sha1 build ID, which is always 20 bytes in length.
+        assert_eq!(build_id.len(), 20, "'{build_id:?}'");
+    }
+
     /// Check that we can read a binary's build ID.
     #[test]
     fn build_id_reading() {
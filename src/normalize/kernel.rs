@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use crate::elf::ElfParser;
+use crate::kernel::find_kaslr_offset;
+use crate::kernel::verify_kernel_image;
+use crate::ksym::KSymResolver;
+use crate::Addr;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// Kernel addresses with the kernel's KASLR offset removed.
+///
+/// Normalized kernel addresses are addresses as they appear in the
+/// kernel image (e.g., `vmlinux`). As such, they can be symbolized on a
+/// different system, given the kernel image, by setting
+/// [`symbolize::Kernel::kaslr_offset`][crate::symbolize::Kernel::kaslr_offset]
+/// to `Some(0)`.
+#[derive(Clone, Debug)]
+pub struct NormalizedKernelAddrs {
+    /// The normalized addresses, in the same order as the addresses
+    /// they correspond to were provided in.
+    pub addrs: Vec<Addr>,
+    /// The KASLR offset that got removed from the addresses.
+    pub kaslr_offset: u64,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub(crate) _non_exhaustive: (),
+}
+
+
+/// Normalize kernel addresses by removing the KASLR offset of the
+/// kernel described by `kallsyms`, relative to `kernel_image`.
+///
+/// If provided, the kernel image is verified to have `build_id`.
+pub(crate) fn normalize_kernel_addrs_impl(
+    addrs: &[Addr],
+    kallsyms: &Path,
+    kernel_image: &Path,
+    build_id: Option<&[u8]>,
+) -> Result<NormalizedKernelAddrs> {
+    let ksym_resolver = KSymResolver::load_file_name(kallsyms.to_path_buf())
+        .with_context(|| format!("failed to load kallsyms from {}", kallsyms.display()))?;
+    let parser = ElfParser::open(kernel_image)
+        .with_context(|| format!("failed to open kernel image {}", kernel_image.display()))?;
+    if let Some(build_id) = build_id {
+        let () = verify_kernel_image(kernel_image, &parser, build_id)?;
+    }
+    let kaslr_offset = find_kaslr_offset(&ksym_resolver, &parser)?.ok_or_invalid_data(|| {
+        format!(
+            "failed to determine KASLR offset of {} relative to {}",
+            kallsyms.display(),
+            kernel_image.display()
+        )
+    })?;
+
+    let addrs = addrs
+        .iter()
+        .map(|addr| addr.wrapping_sub(kaslr_offset))
+        .collect();
+    let normalized = NormalizedKernelAddrs {
+        addrs,
+        kaslr_offset: kaslr_offset as u64,
+        _non_exhaustive: (),
+    };
+    Ok(normalized)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::write;
+
+    use tempfile::tempdir;
+    use test_log::test;
+
+
+    /// Check that we can normalize kernel addresses.
+    #[test]
+    fn kernel_address_normalization() {
+        let dir = tempdir().unwrap();
+        let kallsyms = dir.path().join("kallsyms");
+        // The kernel image has `_stext` at 0x2000000.
+        let () = write(
+            &kallsyms,
+            b"0000000012000000 T _stext\n0000000012000100 T factorial\n",
+        )
+        .unwrap();
+        let image = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses-kernel.bin");
+
+        let normalized =
+            normalize_kernel_addrs_impl(&[0x12000105, 0x12000000], &kallsyms, &image, None)
+                .unwrap();
+        assert_eq!(normalized.kaslr_offset, 0x10000000);
+        assert_eq!(normalized.addrs, vec![0x2000105, 0x2000000]);
+
+        // Without `_stext` we cannot determine the offset.
+        let () = write(&kallsyms, b"0000000012000100 T factorial\n").unwrap();
+        let err = normalize_kernel_addrs_impl(&[0x12000105], &kallsyms, &image, None).unwrap_err();
+        assert!(
+            err.to_string().contains("failed to determine KASLR offset"),
+            "{err}"
+        );
+    }
+}
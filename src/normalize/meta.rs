@@ -5,6 +5,19 @@ use std::path::PathBuf;
 type BuildId = Vec<u8>;
 
 
+/// The way in which a file mapped into a process was accessed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum MapsFileAccess {
+    /// The file was accessed via `/proc/<pid>/map_files/`.
+    MapFiles,
+    /// The file was accessed via the process' root directory,
+    /// `/proc/<pid>/root/`. This strategy is used if `map_files` is
+    /// inaccessible, e.g., for lack of `CAP_SYS_ADMIN`.
+    ProcRoot,
+}
+
+
 /// Meta information about an ELF file inside an APK.
 ///
 /// The corresponding normalized address is normalized to the ELF file,
@@ -41,6 +54,8 @@ pub struct ApkElf {
     pub elf_path: PathBuf,
     /// The ELF file's build ID, if available.
     pub elf_build_id: Option<BuildId>,
+    /// How the APK was accessed during normalization, if known.
+    pub access: Option<MapsFileAccess>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub(crate) _non_exhaustive: (),
@@ -54,6 +69,23 @@ pub struct Elf {
     pub path: PathBuf,
     /// The ELF file's build ID, if available.
     pub build_id: Option<BuildId>,
+    /// How the ELF file was accessed during normalization, if known.
+    pub access: Option<MapsFileAccess>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub(crate) _non_exhaustive: (),
+}
+
+
+/// Meta information about the vDSO.
+///
+/// The vDSO is not backed by a file. The corresponding normalized
+/// address is relative to the vDSO's ELF image, as it can be read from
+/// the process' memory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vdso {
+    /// The vDSO's build ID, if available.
+    pub build_id: Option<BuildId>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub(crate) _non_exhaustive: (),
@@ -83,6 +115,7 @@ impl From<Unknown> for UserAddrMeta {
 pub enum UserAddrMeta {
     ApkElf(ApkElf),
     Elf(Elf),
+    Vdso(Vdso),
     Unknown(Unknown),
 }
 
@@ -103,6 +136,14 @@ impl UserAddrMeta {
         }
     }
 
+    /// Retrieve the [`Vdso`] of this enum, if this variant is active.
+    pub fn vdso(&self) -> Option<&Vdso> {
+        match self {
+            Self::Vdso(vdso) => Some(vdso),
+            _ => None,
+        }
+    }
+
     /// Retrieve the [`Unknown`] of this enum, if this variant is active.
     pub fn unknown(&self) -> Option<&Unknown> {
         match self {
@@ -126,6 +167,7 @@ mod tests {
             apk_path: PathBuf::from("/tmp/archive.apk"),
             elf_path: PathBuf::from("object.so"),
             elf_build_id: None,
+            access: None,
             _non_exhaustive: (),
         });
         assert!(meta.apk_elf().is_some());
@@ -135,10 +177,21 @@ mod tests {
         let meta = UserAddrMeta::Elf(Elf {
             path: PathBuf::from("/tmp/executable.bin"),
             build_id: None,
+            access: None,
             _non_exhaustive: (),
         });
         assert!(meta.apk_elf().is_none());
         assert!(meta.elf().is_some());
+        assert!(meta.vdso().is_none());
+        assert!(meta.unknown().is_none());
+
+        let meta = UserAddrMeta::Vdso(Vdso {
+            build_id: None,
+            _non_exhaustive: (),
+        });
+        assert!(meta.apk_elf().is_none());
+        assert!(meta.elf().is_none());
+        assert!(meta.vdso().is_some());
         assert!(meta.unknown().is_none());
 
         let meta = UserAddrMeta::Unknown(Unknown {
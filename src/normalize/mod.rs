@@ -31,19 +31,25 @@
 //! ```
 
 pub(crate) mod buildid;
+mod kernel;
 mod meta;
 mod normalizer;
 mod user;
 
+pub use kernel::NormalizedKernelAddrs;
 pub use meta::ApkElf;
 pub use meta::Elf;
+pub use meta::MapsFileAccess;
 pub use meta::Unknown;
 pub use meta::UserAddrMeta;
+pub use meta::Vdso;
 pub use normalizer::Normalizer;
 pub use user::NormalizedUserAddrs;
 
 pub(crate) use user::create_apk_elf_path;
+pub(crate) use user::is_apk;
 pub(crate) use user::normalize_apk_addr;
 pub(crate) use user::normalize_elf_addr;
 pub(crate) use user::normalize_sorted_user_addrs_with_entries;
+pub(crate) use user::normalize_vdso_addr;
 pub(crate) use user::Handler;
@@ -1,8 +1,18 @@
+use std::path::Path;
+
+use crate::kernel::find_kernel_image;
+use crate::kernel::read_kernel_build_id;
+use crate::kernel::SYS_KERNEL_NOTES;
+use crate::ksym::KALLSYMS;
+use crate::log::debug;
 use crate::util;
 use crate::Addr;
+use crate::Error;
 use crate::Pid;
 use crate::Result;
 
+use super::kernel::normalize_kernel_addrs_impl;
+use super::kernel::NormalizedKernelAddrs;
 use super::user::normalize_user_addrs_sorted_impl;
 use super::user::NormalizedUserAddrs;
 
@@ -92,6 +102,43 @@ impl Normalizer {
             |sorted_addrs| normalize_user_addrs_sorted_impl(sorted_addrs, pid),
         )
     }
+
+    /// Normalize kernel addresses.
+    ///
+    /// Normalization removes the KASLR offset of the running kernel from
+    /// `addrs`, turning them into addresses as they appear in the
+    /// kernel image. The offset is determined by comparing the address
+    /// of the `_stext` symbol in `/proc/kallsyms` to that in the kernel
+    /// image. If `kernel_image` is `None`, the image of the running
+    /// kernel is searched for in the conventional locations, such as
+    /// `"/boot/"` and `"/lib/modules/<release>/"`. The image is
+    /// required to have the running kernel's build ID, if known.
+    ///
+    /// Normalized addresses are reported in the exact same order in
+    /// which the non-normalized ones were provided.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip(self)))]
+    pub fn normalize_kernel_addrs(
+        &self,
+        addrs: &[Addr],
+        kernel_image: Option<&Path>,
+    ) -> Result<NormalizedKernelAddrs> {
+        let build_id = read_kernel_build_id(Path::new(SYS_KERNEL_NOTES))
+            .map_err(|err| debug!("{err}; unable to verify kernel image"))
+            .unwrap_or_default();
+        let kernel_image = if let Some(kernel_image) = kernel_image {
+            kernel_image.to_path_buf()
+        } else {
+            find_kernel_image(build_id.as_deref())?.ok_or_else(|| {
+                Error::with_not_found("failed to find image of the running kernel")
+            })?
+        };
+        normalize_kernel_addrs_impl(
+            addrs,
+            Path::new(KALLSYMS),
+            &kernel_image,
+            build_id.as_deref(),
+        )
+    }
 }
 
 
@@ -241,8 +288,10 @@ mod tests {
                     .unwrap(),
             ),
             path: so_path,
+            access: meta.elf().unwrap().access,
             _non_exhaustive: (),
         };
+        assert!(expected_elf.access.is_some());
         assert_eq!(meta, &UserAddrMeta::Elf(expected_elf));
     }
 
@@ -310,8 +359,10 @@ mod tests {
                         .unwrap()
                         .unwrap(),
                 ),
+                access: meta.apk_elf().unwrap().access,
                 _non_exhaustive: (),
             };
+            assert!(expected.access.is_some());
             assert_eq!(meta, &UserAddrMeta::ApkElf(expected));
         }
 
@@ -3,6 +3,7 @@ use std::ffi::OsStr;
 use std::io::Error;
 use std::io::ErrorKind;
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -11,6 +12,9 @@ use crate::elf::ElfParser;
 use crate::error::IntoError as _;
 use crate::maps;
 use crate::maps::PathMapsEntry;
+use crate::vdso::is_vdso;
+use crate::vdso::read_vdso;
+use crate::vdso::VDSO_MAPS_COMPONENT;
 use crate::zip;
 use crate::Addr;
 use crate::ErrorExt as _;
@@ -25,6 +29,7 @@ use super::meta::ApkElf;
 use super::meta::Elf;
 use super::meta::Unknown;
 use super::meta::UserAddrMeta;
+use super::meta::Vdso;
 use super::normalizer::NormalizedAddrs;
 
 
@@ -51,6 +56,16 @@ pub(crate) fn create_apk_elf_path(apk: &Path, elf: &Path) -> Result<PathBuf> {
 }
 
 
+/// Check whether the file at `path` is an APK (or zip archive), based
+/// on its extension.
+pub(crate) fn is_apk(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("apk") | Some("zip")
+    )
+}
+
+
 fn normalize_elf_offset_with_parser(offset: u64, parser: &ElfParser) -> Result<Option<Addr>> {
     let phdrs = parser.program_headers()?;
     let addr = phdrs.iter().find_map(|phdr| {
@@ -68,9 +83,11 @@ fn normalize_elf_offset_with_parser(offset: u64, parser: &ElfParser) -> Result<O
 
 /// Make a [`UserAddrMeta::Elf`] variant.
 fn make_elf_meta(entry: &PathMapsEntry, get_build_id: &BuildIdFn) -> Result<UserAddrMeta> {
+    let (path, access) = entry.path.local_path()?;
     let elf = Elf {
         path: entry.path.symbolic_path.to_path_buf(),
-        build_id: get_build_id(&entry.path.maps_file)?,
+        build_id: get_build_id(path)?,
+        access: Some(access),
         _non_exhaustive: (),
     };
     let meta = UserAddrMeta::Elf(elf);
@@ -85,10 +102,12 @@ fn make_apk_elf_meta(
     elf_parser: &ElfParser,
     get_build_id: &ElfBuildIdFn,
 ) -> Result<UserAddrMeta> {
+    let (_path, access) = entry.path.local_path()?;
     let apk = ApkElf {
         elf_build_id: get_build_id(elf_parser)?,
         apk_path: entry.path.symbolic_path.to_path_buf(),
         elf_path,
+        access: Some(access),
         _non_exhaustive: (),
     };
     let meta = UserAddrMeta::ApkElf(apk);
@@ -96,12 +115,24 @@ fn make_apk_elf_meta(
 }
 
 
+/// Make a [`UserAddrMeta::Vdso`] variant.
+fn make_vdso_meta(parser: &ElfParser, get_build_id: &ElfBuildIdFn) -> Result<UserAddrMeta> {
+    let vdso = Vdso {
+        build_id: get_build_id(parser)?,
+        _non_exhaustive: (),
+    };
+    let meta = UserAddrMeta::Vdso(vdso);
+    Ok(meta)
+}
+
+
 /// Normalize a virtual address belonging to an ELF file represented by the
 /// provided [`PathMapsEntry`].
 pub(crate) fn normalize_elf_addr(virt_addr: Addr, entry: &PathMapsEntry) -> Result<Addr> {
     let file_off = virt_addr as u64 - entry.range.start as u64 + entry.offset;
-    let parser = ElfParser::open(&entry.path.maps_file)
-        .with_context(|| format!("failed to open map file {}", entry.path.maps_file.display()))?;
+    let (path, _access) = entry.path.local_path()?;
+    let parser = ElfParser::open(path)
+        .with_context(|| format!("failed to open map file {}", path.display()))?;
     let addr = normalize_elf_offset_with_parser(file_off, &parser)?.ok_or_invalid_input(|| {
         format!(
             "failed to find ELF segment in {} that contains file offset {:#x}",
@@ -122,7 +153,8 @@ pub(crate) fn normalize_apk_addr(
 ) -> Result<(Addr, PathBuf, ElfParser)> {
     let file_off = virt_addr - entry.range.start + entry.offset as usize;
     // An APK is nothing but a fancy zip archive.
-    let apk = zip::Archive::open(&entry.path.maps_file)?;
+    let (path, _access) = entry.path.local_path()?;
+    let apk = zip::Archive::open(path)?;
 
     // Find the APK entry covering the calculated file offset.
     for apk_entry in apk.entries() {
@@ -130,15 +162,19 @@ pub(crate) fn normalize_apk_addr(
         let bounds = apk_entry.data_offset..apk_entry.data_offset + apk_entry.data.len();
 
         if bounds.contains(&file_off) {
-            let mmap = apk
-                .mmap()
-                .constrain(bounds.clone())
-                .ok_or_invalid_input(|| {
+            // Only stored entries can be mapped into a process directly.
+            if apk_entry.compression != zip::COMPRESSION_STORED {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
                     format!(
-                        "invalid APK entry data bounds ({bounds:?}) in {}",
-                        entry.path.symbolic_path.display()
-                    )
-                })?;
+                        "APK entry {} in {} containing file offset {file_off:#x} is compressed",
+                        apk_entry.path.display(),
+                        entry.path.symbolic_path.display(),
+                    ),
+                )
+                .into())
+            }
+            let mmap = apk.read_entry(&apk_entry)?;
             let parser = ElfParser::from_mmap(mmap);
             let elf_off = file_off - apk_entry.data_offset;
             if let Some(addr) = normalize_elf_offset_with_parser(elf_off as u64, &parser)? {
@@ -160,6 +196,22 @@ pub(crate) fn normalize_apk_addr(
 }
 
 
+/// Normalize a virtual address belonging to the vDSO mapped at `range`,
+/// using the provided parser for it.
+pub(crate) fn normalize_vdso_addr(
+    virt_addr: Addr,
+    range: &Range<Addr>,
+    parser: &ElfParser,
+) -> Result<Addr> {
+    let offset = virt_addr - range.start;
+    let addr =
+        normalize_elf_offset_with_parser(offset as u64, parser)?.ok_or_invalid_input(|| {
+            format!("failed to find vDSO segment that contains offset {offset:#x}")
+        })?;
+    Ok(addr)
+}
+
+
 /// A type representing normalized user addresses.
 pub type NormalizedUserAddrs = NormalizedAddrs<UserAddrMeta>;
 
@@ -221,10 +273,35 @@ pub(crate) trait Handler {
 
     /// Handle an address residing in the provided [`PathMapsEntry`].
     fn handle_entry_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()>;
+
+    /// Handle an address residing in the vDSO, which is mapped at
+    /// `range`.
+    fn handle_vdso_addr(&mut self, addr: Addr, range: &Range<Addr>) -> Result<()>;
+}
+
+
+/// A proc maps entry of relevance to normalization.
+enum RelevantEntry {
+    /// An entry backed by a file.
+    Path(PathMapsEntry),
+    /// The vDSO, mapped at the given range.
+    Vdso(Range<Addr>),
+}
+
+impl RelevantEntry {
+    /// Retrieve the virtual address range covered by this entry.
+    fn range(&self) -> &Range<Addr> {
+        match self {
+            Self::Path(entry) => &entry.range,
+            Self::Vdso(range) => range,
+        }
+    }
 }
 
 
 struct NormalizationHandler<R> {
+    /// The process in which addresses are being normalized.
+    pid: Pid,
     /// The normalized user addresses we are building up.
     normalized: NormalizedUserAddrs,
     /// Lookup table from path (as used in each proc maps entry) to index into
@@ -233,20 +310,24 @@ struct NormalizationHandler<R> {
     /// The index of the `Unknown` entry in `meta_lookup`, used for all unknown
     /// addresses.
     unknown_idx: Option<usize>,
+    /// The parser for the process' vDSO, once it has been read.
+    vdso: Option<ElfParser>,
     #[doc(hidden)]
     _phanton: PhantomData<R>,
 }
 
 impl<R> NormalizationHandler<R> {
     /// Instantiate a new `NormalizationHandler` object.
-    fn new(addr_count: usize) -> Self {
+    fn new(pid: Pid, addr_count: usize) -> Self {
         Self {
+            pid,
             normalized: NormalizedUserAddrs {
                 addrs: Vec::with_capacity(addr_count),
                 meta: Vec::new(),
             },
             meta_lookup: HashMap::<PathBuf, usize>::new(),
             unknown_idx: None,
+            vdso: None,
             _phanton: PhantomData,
         }
     }
@@ -296,16 +377,28 @@ where
     }
 
     fn handle_entry_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()> {
-        let ext = entry
-            .path
-            .symbolic_path
-            .extension()
-            .unwrap_or_else(|| OsStr::new(""));
-        match ext.to_str() {
-            Some("apk") | Some("zip") => self.normalize_and_add_apk_addr(addr, entry),
-            _ => self.normalize_and_add_elf_addr(addr, entry),
+        if is_apk(&entry.path.symbolic_path) {
+            self.normalize_and_add_apk_addr(addr, entry)
+        } else {
+            self.normalize_and_add_elf_addr(addr, entry)
         }
     }
+
+    fn handle_vdso_addr(&mut self, addr: Addr, range: &Range<Addr>) -> Result<()> {
+        let parser = if let Some(parser) = &self.vdso {
+            parser
+        } else {
+            self.vdso.insert(read_vdso(self.pid, range)?)
+        };
+        let norm_addr = normalize_vdso_addr(addr, range, parser)?;
+        let () = self.normalized.add_normalized_addr(
+            norm_addr,
+            Path::new(VDSO_MAPS_COMPONENT),
+            &mut self.meta_lookup,
+            || make_vdso_meta(parser, &R::read_build_id),
+        )?;
+        Ok(())
+    }
 }
 
 
@@ -320,7 +413,8 @@ where
     H: Handler,
 {
     let mut entries = entries.filter_map(|result| match result {
-        Ok(entry) => maps::filter_map_relevant(entry).map(Ok),
+        Ok(entry) if is_vdso(&entry) => Some(Ok(RelevantEntry::Vdso(entry.range))),
+        Ok(entry) => maps::filter_map_relevant(entry).map(|entry| Ok(RelevantEntry::Path(entry))),
         Err(err) => Some(Err(err)),
     });
 
@@ -345,7 +439,7 @@ where
         }
         prev_addr = addr;
 
-        while addr >= entry.range.end {
+        while addr >= entry.range().end {
             entry = if let Some(entry) = entries.next() {
                 entry?
             } else {
@@ -363,12 +457,20 @@ where
         // that means that we cannot find a suitable entry. This could
         // happen, for example, if an ELF object was unmapped between
         // address capture and normalization.
-        if addr < entry.range.start {
+        if addr < entry.range().start {
             let () = handler.handle_unknown_addr(addr)?;
             continue 'main
         }
 
-        let () = handler.handle_entry_addr(addr, &entry)?;
+        let () = match &mut entry {
+            RelevantEntry::Path(entry) => {
+                // Figure out how to access the file only once per entry,
+                // not for every address in it.
+                let () = entry.path.resolve()?;
+                handler.handle_entry_addr(addr, entry)?
+            }
+            RelevantEntry::Vdso(range) => handler.handle_vdso_addr(addr, range)?,
+        };
     }
 
     Ok(handler)
@@ -396,7 +498,7 @@ where
 {
     let addrs_cnt = addrs.len();
     let entries = maps::parse(pid)?;
-    let handler = NormalizationHandler::<DefaultBuildIdReader>::new(addrs_cnt);
+    let handler = NormalizationHandler::<DefaultBuildIdReader>::new(pid, addrs_cnt);
     let handler = normalize_sorted_user_addrs_with_entries(addrs, entries, handler)?;
     debug_assert_eq!(handler.normalized.addrs.len(), addrs_cnt);
     Ok(handler.normalized)
@@ -407,8 +509,12 @@ where
 mod tests {
     use super::*;
 
+    use std::env;
+
     use test_log::test;
 
+    use crate::elf::types::PT_LOAD;
+
 
     /// Check that we can create a path to an ELF inside an APK as expected.
     #[test]
@@ -419,6 +525,24 @@ mod tests {
         assert_eq!(path, Path::new("/root/test.apk!/subdir/libc.so"));
     }
 
+    /// Check that we can normalize file offsets in a 32 bit ELF file.
+    #[test]
+    fn elf32_offset_normalization() {
+        let so = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("libtest-so-32.so");
+        let parser = ElfParser::open(&so).unwrap();
+        let phdrs = parser.program_headers().unwrap();
+        assert!(!phdrs.is_empty());
+
+        for phdr in phdrs.iter().filter(|phdr| phdr.p_type == PT_LOAD) {
+            let addr = normalize_elf_offset_with_parser(phdr.p_offset + 4, &parser)
+                .unwrap()
+                .unwrap();
+            assert_eq!(addr, phdr.p_vaddr as Addr + 4);
+        }
+    }
+
     /// Check that we correctly handle normalization of an address not
     /// in any executable segment.
     #[test]
@@ -466,7 +590,7 @@ mod tests {
             let entries = maps::parse_file(maps.as_bytes(), pid);
             let addrs = [unknown_addr as Addr];
 
-            let handler = NormalizationHandler::<NoBuildIdReader>::new(addrs.len());
+            let handler = NormalizationHandler::<NoBuildIdReader>::new(pid, addrs.len());
             let norm_addrs = normalize_sorted_user_addrs_with_entries(
                 addrs.as_slice().iter().copied(),
                 entries,
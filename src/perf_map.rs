@@ -0,0 +1,271 @@
+//! Support for perf map files.
+//!
+//! Just-in-time compilers (e.g., V8, the JVM with `perf-map-agent`, or
+//! LuaJIT) place generated code in anonymous memory mappings, for which
+//! no symbol information is available otherwise. Many of them can be
+//! asked to describe this code in a "perf map" file, by convention
+//! located at `/tmp/perf-<pid>.map`. Each line of such a file describes
+//! a single symbol:
+//! ```text
+//! START SIZE symbolname
+//! ```
+//! `START` and `SIZE` are hexadecimal numbers (optionally with a `0x`
+//! prefix) and the symbol name may contain spaces.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::inspect::FindAddrOpts;
+use crate::inspect::SymInfo;
+use crate::inspect::SymType;
+use crate::symbolize::AddrLineInfo;
+use crate::Addr;
+use crate::ErrorExt as _;
+use crate::IntInlinedFn;
+use crate::IntSym;
+use crate::IntoError as _;
+use crate::Pid;
+use crate::Result;
+use crate::SrcLang;
+use crate::SymResolver;
+
+
+/// Retrieve the conventional path of the perf map file for the process
+/// with the provided ID.
+pub(crate) fn perf_map_path(pid: Pid) -> PathBuf {
+    PathBuf::from(format!("/tmp/perf-{}.map", pid.resolve()))
+}
+
+
+/// Parse a hexadecimal number, optionally prefixed with `0x`.
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(s, 16).ok()
+}
+
+
+/// A symbol described by a perf map file.
+#[derive(Debug)]
+struct PerfMapSym {
+    /// The symbol's start address.
+    addr: Addr,
+    /// The size of the symbol's code, in bytes.
+    size: u64,
+    /// The name of the symbol.
+    name: String,
+}
+
+impl PerfMapSym {
+    /// Parse a single line of a perf map file.
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(3, ' ');
+        let addr = parse_hex(fields.next()?)? as Addr;
+        let size = parse_hex(fields.next()?)?;
+        let name = fields.next()?.to_string();
+
+        let sym = Self { addr, size, name };
+        Some(sym)
+    }
+
+    #[inline]
+    fn contains(&self, addr: Addr) -> bool {
+        (self.addr..self.addr.saturating_add(self.size as Addr)).contains(&addr)
+    }
+}
+
+
+/// The symbol resolver for perf map files.
+pub(crate) struct PerfMapResolver {
+    /// All symbols, sorted by address.
+    syms: Vec<PerfMapSym>,
+    /// The path to the perf map file.
+    path: PathBuf,
+}
+
+impl PerfMapResolver {
+    /// Load the perf map file at `path`.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open perf map file {}", path.display()))?;
+        let syms = Self::parse(file)
+            .with_context(|| format!("failed to parse perf map file {}", path.display()))?;
+
+        let slf = Self {
+            syms,
+            path: path.to_path_buf(),
+        };
+        Ok(slf)
+    }
+
+    fn parse<R>(reader: R) -> Result<Vec<PerfMapSym>>
+    where
+        R: Read,
+    {
+        let mut syms = Vec::new();
+        for (idx, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue
+            }
+
+            let sym = PerfMapSym::parse(line).ok_or_invalid_data(|| {
+                format!("encountered malformed entry in line {}: {line}", idx + 1)
+            })?;
+            let () = syms.push(sym);
+        }
+
+        // Code may get regenerated at the same address. We use a stable
+        // sort so that the most recent entry is the last one with a given
+        // address.
+        let () = syms.sort_by_key(|sym| sym.addr);
+        Ok(syms)
+    }
+}
+
+impl SymResolver for PerfMapResolver {
+    fn find_syms(&self, addr: Addr) -> Result<Vec<IntSym<'_>>> {
+        let idx = self.syms.partition_point(|sym| sym.addr <= addr);
+        let sym = idx
+            .checked_sub(1)
+            .and_then(|idx| self.syms.get(idx))
+            .filter(|sym| sym.contains(addr));
+
+        let syms = sym
+            .map(|sym| IntSym {
+                name: &sym.name,
+                addr: sym.addr,
+                // Perf maps don't carry any source code language
+                // information.
+                lang: SrcLang::Unknown,
+                module: None,
+            })
+            .into_iter()
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_addr(&self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo>> {
+        if let SymType::Variable = opts.sym_type {
+            return Ok(Vec::new())
+        }
+
+        let syms = self
+            .syms
+            .iter()
+            .filter(|sym| sym.name == name)
+            .map(|sym| SymInfo {
+                name: sym.name.clone(),
+                addr: sym.addr,
+                size: sym.size as usize,
+                sym_type: SymType::Function,
+                file_offset: 0,
+                obj_file_name: None,
+            })
+            .collect();
+        Ok(syms)
+    }
+
+    fn find_line_info(&self, _addr: Addr) -> Result<Option<AddrLineInfo<'_>>> {
+        Ok(None)
+    }
+
+    fn find_inlined_fns(&self, _addr: Addr) -> Result<Vec<IntInlinedFn<'_>>> {
+        Ok(Vec::new())
+    }
+
+    fn addr_file_off(&self, _addr: Addr) -> Option<u64> {
+        None
+    }
+}
+
+impl Debug for PerfMapResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "PerfMap {}", self.path.display())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write as _;
+    use std::process;
+
+    use tempfile::NamedTempFile;
+    use test_log::test;
+
+
+    /// Check that we can parse a perf map file and look up symbols in
+    /// it.
+    #[test]
+    fn perf_map_parsing() {
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file
+            .write_all(
+                b"7f0000001000 100 LazyCompile:~foo /app/foo.js:1\n\
+                  0x7f0000001200 0x20 bar\n\
+                  \n\
+                  7f0000001000 80 LazyCompile:*foo /app/foo.js:1\n",
+            )
+            .unwrap();
+
+        let resolver = PerfMapResolver::from_file(file.path()).unwrap();
+        assert_eq!(resolver.syms.len(), 3);
+
+        // The more recent entry at the same address should win.
+        let syms = resolver.find_syms(0x7f0000001010).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "LazyCompile:*foo /app/foo.js:1");
+        assert_eq!(syms[0].addr, 0x7f0000001000);
+
+        let syms = resolver.find_syms(0x7f000000121f).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].name, "bar");
+
+        let syms = resolver.find_syms(0x7f0000001220).unwrap();
+        assert!(syms.is_empty());
+        let syms = resolver.find_syms(0x7f0000000fff).unwrap();
+        assert!(syms.is_empty());
+
+        let opts = FindAddrOpts::default();
+        let infos = resolver.find_addr("bar", &opts).unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].addr, 0x7f0000001200);
+        assert_eq!(infos[0].size, 0x20);
+    }
+
+    /// Check that we report malformed perf map entries.
+    #[test]
+    fn malformed_perf_map_parsing() {
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(b"1000 10 foo\nxyz 10 bar\n").unwrap();
+
+        let err = PerfMapResolver::from_file(file.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+    }
+
+    /// Check that we construct the conventional perf map path correctly.
+    #[test]
+    fn perf_map_path_construction() {
+        let path = perf_map_path(Pid::from(1234));
+        assert_eq!(path, Path::new("/tmp/perf-1234.map"));
+
+        let path = perf_map_path(Pid::Slf);
+        assert_eq!(
+            path,
+            PathBuf::from(format!("/tmp/perf-{}.map", process::id()))
+        );
+    }
+}
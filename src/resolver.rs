@@ -28,6 +28,20 @@ pub(crate) struct IntSym<'src> {
     pub(crate) addr: Addr,
     /// The source code language from which the symbol originates.
     pub(crate) lang: SrcLang,
+    /// The name of the kernel module the symbol belongs to, if any.
+    pub(crate) module: Option<&'src str>,
+}
+
+
+/// Our internal representation of an inlined function.
+pub(crate) struct IntInlinedFn<'src> {
+    /// The name of the inlined function.
+    pub(crate) name: &'src str,
+    /// The source code location of the call site, i.e., the location in
+    /// the caller at which the function got inlined.
+    pub(crate) call_site: Option<AddrLineInfo<'src>>,
+    /// The source code language from which the function originates.
+    pub(crate) lang: SrcLang,
 }
 
 
@@ -46,6 +60,9 @@ where
     fn find_addr(&self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo>>;
     /// Find the file name and the line number of an address.
     fn find_line_info(&self, addr: Addr) -> Result<Option<AddrLineInfo>>;
+    /// Find the functions inlined at an address, ordered from the
+    /// outermost to the innermost one.
+    fn find_inlined_fns(&self, addr: Addr) -> Result<Vec<IntInlinedFn<'_>>>;
     /// Translate an address (virtual) in a process to the file offset
     /// in the object file.
     fn addr_file_off(&self, addr: Addr) -> Option<u64>;
@@ -74,6 +74,10 @@ mod symbolizer;
 use std::ffi::OsStr;
 use std::path::Path;
 
+pub use source::Breakpad;
+pub use source::BreakpadData;
+pub use source::BreakpadFile;
+pub use source::CoreFile;
 pub use source::Elf;
 pub use source::Gsym;
 pub use source::GsymData;
@@ -82,6 +86,7 @@ pub use source::Kernel;
 pub use source::Process;
 pub use source::Source;
 pub use symbolizer::Builder;
+pub use symbolizer::InlinedFn;
 pub use symbolizer::Sym;
 pub use symbolizer::Symbolizer;
 
@@ -66,9 +66,48 @@ pub struct Kernel {
     ///
     /// This should be the path of a kernel image.  For example,
     /// `"/boot/vmlinux-xxxx"`.  A `None` value will find the
-    /// kernel image of the running kernel in `"/boot/"` or
+    /// kernel image of the running kernel by its build ID in the
+    /// debug directories or in conventional locations such as
+    /// `"/boot/"`, `"/lib/modules/<release>/"`, or
     /// `"/usr/lib/debug/boot/"`.
+    ///
+    /// When symbolizing addresses of the running kernel, the image is
+    /// required to have the build ID reported in
+    /// `"/sys/kernel/notes"`, if any.
     pub kernel_image: Option<PathBuf>,
+    /// The directory to search for kernel module (`.ko`) files.
+    ///
+    /// Addresses residing in loaded kernel modules are symbolized using
+    /// the corresponding module file found in this directory or any of
+    /// its subdirectories, if any. A `None` value will search
+    /// `"/lib/modules/<release>/"` of the running kernel.
+    ///
+    /// Because the list of loaded modules is retrieved from the running
+    /// kernel, module addresses are only symbolized this way if the
+    /// addresses are assumed to stem from it, i.e., if `kaslr_offset` is
+    /// not provided and `kallsyms` is unset or refers to
+    /// `/proc/kallsyms`, or if `modules_dir` is set explicitly.
+    pub modules_dir: Option<PathBuf>,
+    /// Whether to symbolize addresses in JITed BPF programs using the
+    /// function and line information the kernel keeps for them.
+    ///
+    /// This information is only available for programs loaded with BTF
+    /// and retrieving it requires the `CAP_SYS_ADMIN` capability. If it
+    /// is not usable, BPF program addresses are symbolized using
+    /// kallsyms, which only reports `bpf_prog_<tag>_<name>` symbols.
+    pub bpf_prog_info: bool,
+    /// The KASLR offset of the addresses to symbolize, i.e., the
+    /// difference between them and the corresponding addresses in the
+    /// kernel image.
+    ///
+    /// If `None`, the offset of the running kernel is inferred by
+    /// comparing the address of the `_stext` symbol in kallsyms to that
+    /// in the kernel image. If set, the addresses are not assumed to
+    /// stem from the running kernel and `"/proc/kallsyms"` is only
+    /// used if explicitly provided via [`kallsyms`][Kernel::kallsyms].
+    /// Use `Some(0)` for addresses normalized by
+    /// [`Normalizer::normalize_kernel_addrs`][crate::normalize::Normalizer::normalize_kernel_addrs].
+    pub kaslr_offset: Option<u64>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub(crate) _non_exhaustive: (),
@@ -90,6 +129,27 @@ impl From<Kernel> for Source<'static> {
 pub struct Process {
     /// The referenced process' ID.
     pub pid: Pid,
+    /// The path to a perf map file describing just-in-time compiled
+    /// code in the process.
+    ///
+    /// Addresses in anonymous executable memory, which typically
+    /// contains such code, are symbolized using this file. If `None`,
+    /// the conventional `/tmp/perf-<pid>.map` file is used, if present.
+    pub perf_map: Option<PathBuf>,
+    /// The path to a jitdump file describing just-in-time compiled
+    /// code in the process.
+    ///
+    /// Addresses in anonymous executable memory are symbolized using
+    /// this file, including source code line information, before
+    /// falling back to the perf map. If `None`, the `jit-<pid>.dump`
+    /// file mapped into the process is used, if any.
+    pub jitdump: Option<PathBuf>,
+    /// The time stamp, in the clock domain of the jitdump file, at
+    /// which the symbolized addresses were valid.
+    ///
+    /// Code described by a jitdump file may get moved over time. If
+    /// `None`, the most recent location of each piece of code is used.
+    pub jitdump_time: Option<u64>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub(crate) _non_exhaustive: (),
@@ -100,6 +160,9 @@ impl Process {
     pub fn new(pid: Pid) -> Self {
         Self {
             pid,
+            perf_map: None,
+            jitdump: None,
+            jitdump_time: None,
             _non_exhaustive: (),
         }
     }
@@ -109,6 +172,9 @@ impl Debug for Process {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         let Process {
             pid,
+            perf_map: _,
+            jitdump: _,
+            jitdump_time: _,
             _non_exhaustive: (),
         } = self;
 
@@ -126,6 +192,59 @@ impl From<Process> for Source<'static> {
 }
 
 
+/// Configuration for symbolizing addresses of a process captured in an
+/// ELF core file.
+///
+/// The process' memory map is reconstructed from the core file's
+/// `NT_FILE` note. The corresponding addresses supplied to
+/// [`Symbolizer::symbolize`] are expected to be absolute addresses as
+/// valid within the process at the time the core file was created.
+#[derive(Clone)]
+pub struct CoreFile {
+    /// The path to the core file.
+    pub path: PathBuf,
+    /// A directory under which to look up the files mapped by the
+    /// process, e.g., a copy of the root file system of the system the
+    /// core file was created on.
+    ///
+    /// If `None`, mapped files are looked up at the paths recorded in
+    /// the core file.
+    pub sysroot: Option<PathBuf>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub(crate) _non_exhaustive: (),
+}
+
+impl CoreFile {
+    /// Create a new [`CoreFile`] object, referencing the provided path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            sysroot: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl Debug for CoreFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let CoreFile {
+            path,
+            sysroot: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(CoreFile)).field(path).finish()
+    }
+}
+
+impl From<CoreFile> for Source<'static> {
+    fn from(core_file: CoreFile) -> Self {
+        Source::CoreFile(core_file)
+    }
+}
+
+
 #[derive(Clone, Debug)]
 pub enum Gsym<'dat> {
     /// "Raw" Gsym data.
@@ -188,10 +307,74 @@ impl From<GsymFile> for Source<'static> {
 }
 
 
+#[derive(Clone, Debug)]
+pub enum Breakpad<'dat> {
+    /// "Raw" Breakpad symbol file data.
+    Data(BreakpadData<'dat>),
+    /// A Breakpad symbol file.
+    File(BreakpadFile),
+}
+
+/// Breakpad symbol file data.
+#[derive(Clone, Debug)]
+pub struct BreakpadData<'dat> {
+    /// The "raw" Breakpad symbol file data.
+    pub data: &'dat [u8],
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub(crate) _non_exhaustive: (),
+}
+
+impl<'dat> BreakpadData<'dat> {
+    /// Create a new [`BreakpadData`] object, referencing the provided
+    /// data.
+    pub fn new(data: &'dat [u8]) -> Self {
+        Self {
+            data,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl<'dat> From<BreakpadData<'dat>> for Source<'dat> {
+    fn from(breakpad: BreakpadData<'dat>) -> Self {
+        Source::Breakpad(Breakpad::Data(breakpad))
+    }
+}
+
+
+/// A Breakpad symbol file (typically with a `.sym` extension).
+#[derive(Clone, Debug)]
+pub struct BreakpadFile {
+    /// The path to the Breakpad symbol file.
+    pub path: PathBuf,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub(crate) _non_exhaustive: (),
+}
+
+impl BreakpadFile {
+    /// Create a new [`BreakpadFile`] object, referencing the provided
+    /// path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<BreakpadFile> for Source<'static> {
+    fn from(breakpad: BreakpadFile) -> Self {
+        Source::Breakpad(Breakpad::File(breakpad))
+    }
+}
+
+
 /// The description of a source of symbols and debug information.
 ///
 /// The source of symbols and debug information can be an ELF file, kernel
-/// image, or process.
+/// image, process, core file, Gsym file, or Breakpad symbol file.
 #[derive(Clone)]
 #[non_exhaustive]
 pub enum Source<'dat> {
@@ -201,8 +384,12 @@ pub enum Source<'dat> {
     Kernel(Kernel),
     /// Information about a process.
     Process(Process),
+    /// A core file of a process.
+    CoreFile(CoreFile),
     /// A Gsym file.
     Gsym(Gsym<'dat>),
+    /// A Breakpad symbol file.
+    Breakpad(Breakpad<'dat>),
 }
 
 impl Debug for Source<'_> {
@@ -211,7 +398,9 @@ impl Debug for Source<'_> {
             Self::Elf(elf) => Debug::fmt(elf, f),
             Self::Kernel(kernel) => Debug::fmt(kernel, f),
             Self::Process(process) => Debug::fmt(process, f),
+            Self::CoreFile(core_file) => Debug::fmt(core_file, f),
             Self::Gsym(gsym) => Debug::fmt(gsym, f),
+            Self::Breakpad(breakpad) => Debug::fmt(breakpad, f),
         }
     }
 }
@@ -236,5 +425,10 @@ mod tests {
         assert_eq!(format!("{process:?}"), "Process(1234)");
         let src = Source::Process(process);
         assert_eq!(format!("{src:?}"), "Process(1234)");
+
+        let core_file = CoreFile::new("/tmp/core");
+        assert_eq!(format!("{core_file:?}"), "CoreFile(\"/tmp/core\")");
+        let src = Source::CoreFile(core_file);
+        assert_eq!(format!("{src:?}"), "CoreFile(\"/tmp/core\")");
     }
 }
@@ -1,35 +1,82 @@
-use std::ffi::OsStr;
+#[cfg(feature = "parallel")]
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fmt::Debug;
+#[cfg(feature = "parallel")]
+use std::iter;
+use std::mem;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
+#[cfg(feature = "parallel")]
+use rayon::iter::IntoParallelIterator as _;
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator as _;
+
+use crate::bpf;
+use crate::bpf::BpfProgResolver;
+use crate::bpf::BPF_PROG_SYM_PREFIX;
+use crate::breakpad::BreakpadResolver;
+use crate::coredump::parse_core_maps;
+#[cfg(feature = "debuginfod")]
+use crate::elf::DebugInfod;
 use crate::elf::ElfBackend;
 use crate::elf::ElfCache;
+use crate::elf::ElfParser;
 use crate::elf::ElfResolver;
+use crate::error::IntoError as _;
 use crate::gsym::GsymResolver;
+use crate::jitdump::jitdump_entry_path;
+use crate::jitdump::JitDumpResolver;
+use crate::kernel::find_kaslr_offset;
+use crate::kernel::find_kernel_image;
+use crate::kernel::read_kernel_build_id;
+use crate::kernel::verify_kernel_image;
 use crate::kernel::KernelResolver;
+use crate::kernel::SYS_KERNEL_NOTES;
+use crate::kmod;
+use crate::kmod::ModuleResolver;
+use crate::kmod::PROC_MODULES;
+use crate::kmod::SYS_MODULE;
 use crate::ksym::KSymCache;
+use crate::ksym::KSymResolver;
 use crate::ksym::KALLSYMS;
 use crate::log;
 use crate::maps;
 use crate::maps::PathMapsEntry;
 use crate::normalize;
 use crate::normalize::create_apk_elf_path;
+use crate::normalize::is_apk;
 use crate::normalize::normalize_apk_addr;
 use crate::normalize::normalize_elf_addr;
 use crate::normalize::normalize_sorted_user_addrs_with_entries;
+use crate::normalize::normalize_vdso_addr;
+use crate::normalize::ApkElf;
+use crate::normalize::NormalizedUserAddrs;
+use crate::normalize::UserAddrMeta;
+use crate::perf_map::perf_map_path;
+use crate::perf_map::PerfMapResolver;
 use crate::util;
 use crate::util::uname_release;
+use crate::vdso::read_vdso;
+use crate::vdso::VDSO_MAPS_COMPONENT;
+use crate::zip;
 use crate::Addr;
+use crate::Error;
 use crate::ErrorExt as _;
+use crate::IntInlinedFn;
 use crate::IntSym;
 use crate::Pid;
 use crate::Result;
 use crate::SrcLang;
 use crate::SymResolver;
 
+use super::source::Breakpad;
+use super::source::BreakpadData;
+use super::source::BreakpadFile;
+use super::source::CoreFile;
 use super::source::Elf;
 use super::source::Gsym;
 use super::source::GsymData;
@@ -37,6 +84,7 @@ use super::source::GsymFile;
 use super::source::Kernel;
 use super::source::Process;
 use super::source::Source;
+use super::AddrLineInfo;
 
 
 /// Demangle a symbol name using the demangling scheme for the given language.
@@ -69,6 +117,93 @@ fn maybe_demangle(name: &str, _language: SrcLang) -> String {
 }
 
 
+/// A function that got inlined into a symbolized function.
+#[derive(Clone, Debug)]
+pub struct InlinedFn {
+    /// The name of the inlined function.
+    pub name: String,
+    /// The directory in which the source file resides.
+    pub dir: Option<PathBuf>,
+    /// The file containing the inlined function's code.
+    pub file: Option<OsString>,
+    /// The line number of the symbolized instruction inside the inlined
+    /// function (or that of the call to the next inlined function).
+    pub line: Option<u32>,
+    /// The column number of the symbolized instruction inside the
+    /// inlined function.
+    pub column: Option<u16>,
+    /// The struct is non-exhaustive and open to extension.
+    pub(crate) _non_exhaustive: (),
+}
+
+
+/// A set of rules for mapping paths as found on the system on which
+/// data were captured to paths on the local system.
+#[derive(Clone, Debug, Default)]
+struct PathMap {
+    /// The directory to prepend to absolute paths not covered by any of
+    /// the prefix rules.
+    sysroot: Option<PathBuf>,
+    /// Ordered `(from, to)` prefix rewrite rules, of which the first
+    /// matching one is used.
+    prefixes: Vec<(PathBuf, PathBuf)>,
+}
+
+impl PathMap {
+    /// Map `path` to its local counterpart, returning `None` if no rule
+    /// applies.
+    fn map(&self, path: &Path) -> Option<PathBuf> {
+        self.prefixes
+            .iter()
+            .find_map(|(from, to)| path.strip_prefix(from).ok().map(|rest| to.join(rest)))
+            .or_else(|| {
+                let sysroot = self.sysroot.as_ref()?;
+                let rest = path.strip_prefix("/").ok()?;
+                Some(sysroot.join(rest))
+            })
+    }
+}
+
+
+/// Check whether kernel addresses are to be interpreted as stemming
+/// from the running kernel, given the `kallsyms` file and KASLR offset
+/// provided by the user.
+///
+/// That is the case unless a KASLR offset or a kallsyms file other than
+/// that of the running kernel is provided.
+fn is_running_kernel(kallsyms: Option<&Path>, kaslr_offset: Option<u64>) -> bool {
+    kaslr_offset.is_none() && kallsyms.map_or(true, |kallsyms| kallsyms == Path::new(KALLSYMS))
+}
+
+
+/// Convert optional line information into its owned constituents,
+/// mapping source paths using `path_map`.
+#[allow(clippy::type_complexity)]
+fn src_location(
+    linfo: Option<&AddrLineInfo<'_>>,
+    path_map: &PathMap,
+) -> (Option<PathBuf>, Option<OsString>, Option<u32>, Option<u16>) {
+    if let Some(linfo) = linfo {
+        let dir = path_map
+            .map(linfo.dir)
+            .unwrap_or_else(|| linfo.dir.to_path_buf());
+        // Absolute file paths don't depend on the directory and may
+        // need mapping on their own.
+        let file = if Path::new(linfo.file).is_absolute() {
+            path_map
+                .map(Path::new(linfo.file))
+                .map(PathBuf::into_os_string)
+        } else {
+            None
+        }
+        .unwrap_or_else(|| linfo.file.to_os_string());
+        (Some(dir), Some(file), linfo.line, linfo.column)
+    } else {
+        (None, None, None, None)
+    }
+}
+
+
 /// The result of symbolization by [`Symbolizer`].
 #[derive(Clone, Debug)]
 pub struct Sym {
@@ -104,6 +239,23 @@ pub struct Sym {
     /// The column number of the symbolized instruction in the source
     /// code.
     pub column: Option<u16>,
+    /// Functions inlined at the symbolized address, ordered from the
+    /// outermost to the innermost one.
+    ///
+    /// When inlined functions are reported, the source code location
+    /// of the symbol itself (`dir`, `file`, `line`, and `column`) refers
+    /// to the call site of the outermost inlined function, while the
+    /// innermost inlined function carries the location of the
+    /// symbolized instruction.
+    ///
+    /// This list is only populated if reporting of inlined functions
+    /// has been enabled via [`Builder::enable_inlined_fns`].
+    pub inlined: Box<[InlinedFn]>,
+    /// The name of the kernel module the symbol belongs to, if any.
+    ///
+    /// This member is only ever set for kernel addresses residing in a
+    /// loadable module.
+    pub module: Option<String>,
     /// The struct is non-exhaustive and open to extension.
     pub(crate) _non_exhaustive: (),
 }
@@ -111,7 +263,8 @@ pub struct Sym {
 
 /// A builder for configurable construction of [`Symbolizer`] objects.
 ///
-/// By default all features are enabled.
+/// By default all features but the reporting of inlined functions are
+/// enabled.
 #[derive(Clone, Debug)]
 pub struct Builder {
     /// Whether to enable usage of debug symbols.
@@ -127,6 +280,29 @@ pub struct Builder {
     /// languages are Rust and C++ and the flag will have no effect if
     /// the underlying language does not mangle symbols (such as C).
     demangle: bool,
+    /// Whether or not to report inlined functions.
+    ///
+    /// This setting only has an effect if source code location
+    /// information is gathered as well.
+    inlined_fns: bool,
+    /// Additional directories to search for separate debug information.
+    debug_dirs: Vec<PathBuf>,
+    /// The URLs of debuginfod servers to fetch missing debug
+    /// information from.
+    #[cfg(feature = "debuginfod")]
+    debuginfod_urls: Vec<String>,
+    /// The directory in which to cache files fetched from debuginfod
+    /// servers.
+    #[cfg(feature = "debuginfod")]
+    debuginfod_cache_dir: Option<PathBuf>,
+    /// The directory containing the files of the system on which data
+    /// were captured.
+    sysroot: Option<PathBuf>,
+    /// Ordered prefix rewrite rules for paths of ELF files, kernel
+    /// images, and APKs.
+    path_maps: Vec<(PathBuf, PathBuf)>,
+    /// Ordered prefix rewrite rules for reported source code paths.
+    src_path_maps: Vec<(PathBuf, PathBuf)>,
 }
 
 impl Builder {
@@ -152,21 +328,165 @@ impl Builder {
         self
     }
 
+    /// Enable/disable reporting of inlined functions.
+    ///
+    /// Inlined functions are reported as part of [`Sym::inlined`]. Their
+    /// retrieval requires source code location information to be
+    /// enabled (see [`Builder::enable_src_location`]). Because it makes
+    /// debug information parsing more expensive, this feature is
+    /// disabled by default.
+    pub fn enable_inlined_fns(mut self, enable: bool) -> Builder {
+        self.inlined_fns = enable;
+        self
+    }
+
+    /// Set additional directories to search for separate debug
+    /// information.
+    ///
+    /// Stripped ELF binaries may have their debug information stored in
+    /// a separate file, referenced by build ID or via a
+    /// `.gnu_debuglink` section. The provided directories are searched
+    /// for such files (using the `.build-id/xx/yyyy.debug` layout for
+    /// the former) before the system default ones, `/usr/lib/debug` and
+    /// `/lib/debug`.
+    pub fn set_debug_dirs<D, P>(mut self, dirs: D) -> Builder
+    where
+        D: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.debug_dirs = dirs
+            .into_iter()
+            .map(|dir| dir.as_ref().to_path_buf())
+            .collect();
+        self
+    }
+
+    /// Set the URLs of debuginfod servers to fetch debug information
+    /// from.
+    ///
+    /// Debug information for ELF files that do not have any available
+    /// locally is fetched from the provided servers, in order, based on
+    /// the file's build ID. By default, no servers are used.
+    #[cfg(feature = "debuginfod")]
+    pub fn set_debuginfod_urls<U, S>(mut self, urls: U) -> Builder
+    where
+        U: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.debuginfod_urls = urls.into_iter().map(S::into).collect();
+        self
+    }
+
+    /// Set the directory in which to cache files fetched from
+    /// debuginfod servers.
+    ///
+    /// By default, `$XDG_CACHE_HOME/blazesym/debuginfod` is used.
+    #[cfg(feature = "debuginfod")]
+    pub fn set_debuginfod_cache_dir<P>(mut self, dir: P) -> Builder
+    where
+        P: AsRef<Path>,
+    {
+        self.debuginfod_cache_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the directory containing the files of the system on which
+    /// the data to symbolize were captured.
+    ///
+    /// Absolute paths of ELF files, kernel images, and APKs that are not
+    /// covered by any of the rules set via [`Builder::set_path_maps`]
+    /// are looked up relative to this directory. This affects
+    /// [`Source::Elf`], the kernel image of [`Source::Kernel`], as well
+    /// as files found via the paths reported in
+    /// [`UserAddrMeta`] objects passed to
+    /// [`Symbolizer::symbolize_normalized`].
+    pub fn set_sysroot<P>(mut self, sysroot: P) -> Builder
+    where
+        P: AsRef<Path>,
+    {
+        self.sysroot = Some(sysroot.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set rules for rewriting the prefixes of paths of ELF files,
+    /// kernel images, and APKs.
+    ///
+    /// Each rule is a `(from, to)` pair, causing paths starting with
+    /// `from` to have this prefix replaced with `to`. Rules are checked
+    /// in order and the first matching one is used, taking precedence
+    /// over the sysroot (see [`Builder::set_sysroot`]).
+    pub fn set_path_maps<M, P, Q>(mut self, maps: M) -> Builder
+    where
+        M: IntoIterator<Item = (P, Q)>,
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        self.path_maps = maps
+            .into_iter()
+            .map(|(from, to)| (from.as_ref().to_path_buf(), to.as_ref().to_path_buf()))
+            .collect();
+        self
+    }
+
+    /// Set rules for rewriting the prefixes of source code paths
+    /// reported in [`Sym`] and [`InlinedFn`] objects.
+    ///
+    /// This allows for mapping, say, compilation directories on a build
+    /// machine to a local checkout. Rules work as those set via
+    /// [`Builder::set_path_maps`] and are applied to the reported
+    /// directory as well as to absolute file paths.
+    pub fn set_src_path_maps<M, P, Q>(mut self, maps: M) -> Builder
+    where
+        M: IntoIterator<Item = (P, Q)>,
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        self.src_path_maps = maps
+            .into_iter()
+            .map(|(from, to)| (from.as_ref().to_path_buf(), to.as_ref().to_path_buf()))
+            .collect();
+        self
+    }
+
     /// Create the [`Symbolizer`] object.
     pub fn build(self) -> Symbolizer {
         let Builder {
             debug_syms,
             src_location,
             demangle,
+            inlined_fns,
+            debug_dirs,
+            #[cfg(feature = "debuginfod")]
+            debuginfod_urls,
+            #[cfg(feature = "debuginfod")]
+            debuginfod_cache_dir,
+            sysroot,
+            path_maps,
+            src_path_maps,
         } = self;
         let ksym_cache = KSymCache::new();
-        let elf_cache = ElfCache::new(src_location, debug_syms);
+        #[allow(unused_mut)]
+        let mut elf_cache = ElfCache::new(src_location, debug_syms, &debug_dirs);
+        #[cfg(feature = "debuginfod")]
+        if !debuginfod_urls.is_empty() {
+            let debuginfod = DebugInfod::new(debuginfod_urls, debuginfod_cache_dir);
+            let () = elf_cache.set_debuginfod(Some(debuginfod));
+        }
 
         Symbolizer {
             ksym_cache,
             elf_cache,
             src_location,
             demangle,
+            inlined_fns,
+            path_map: PathMap {
+                sysroot,
+                prefixes: path_maps,
+            },
+            src_path_map: PathMap {
+                sysroot: None,
+                prefixes: src_path_maps,
+            },
         }
     }
 }
@@ -177,6 +497,15 @@ impl Default for Builder {
             src_location: true,
             debug_syms: true,
             demangle: true,
+            inlined_fns: false,
+            debug_dirs: Vec::new(),
+            #[cfg(feature = "debuginfod")]
+            debuginfod_urls: Vec::new(),
+            #[cfg(feature = "debuginfod")]
+            debuginfod_cache_dir: None,
+            sysroot: None,
+            path_maps: Vec::new(),
+            src_path_maps: Vec::new(),
         }
     }
 }
@@ -189,6 +518,11 @@ pub struct Symbolizer {
     elf_cache: ElfCache,
     src_location: bool,
     demangle: bool,
+    inlined_fns: bool,
+    /// Mapping of paths of ELF files, kernel images, and APKs.
+    path_map: PathMap,
+    /// Mapping of reported source code paths.
+    src_path_map: PathMap,
 }
 
 impl Symbolizer {
@@ -212,6 +546,45 @@ impl Symbolizer {
         }
     }
 
+    /// Convert the inlined functions reported by a resolver into
+    /// [`InlinedFn`] objects.
+    ///
+    /// Resolvers report the location of the call site for each inlined
+    /// function, but we want to attribute to each function the location
+    /// inside of it. As such, each location is shifted by one level: the
+    /// innermost function receives `linfo` (the location of the address
+    /// itself) and the location returned is the one to use for the
+    /// symbol itself.
+    fn resolve_inlined_fns<'src>(
+        &self,
+        mut linfo: Option<AddrLineInfo<'src>>,
+        inlined: Vec<IntInlinedFn<'src>>,
+    ) -> (Option<AddrLineInfo<'src>>, Box<[InlinedFn]>) {
+        let mut inlined = inlined
+            .into_iter()
+            .rev()
+            .map(|inlined_fn| {
+                let IntInlinedFn {
+                    name,
+                    call_site,
+                    lang,
+                } = inlined_fn;
+                let linfo = mem::replace(&mut linfo, call_site);
+                let (dir, file, line, column) = src_location(linfo.as_ref(), &self.src_path_map);
+                InlinedFn {
+                    name: self.maybe_demangle(name, lang),
+                    dir,
+                    file,
+                    line,
+                    column,
+                    _non_exhaustive: (),
+                }
+            })
+            .collect::<Vec<_>>();
+        let () = inlined.reverse();
+        (linfo, inlined.into_boxed_slice())
+    }
+
     /// Symbolize an address using the provided [`SymResolver`].
     #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{addr:#x}"), resolver = ?resolver)))]
     fn symbolize_with_resolver(&self, addr: Addr, resolver: &dyn SymResolver) -> Result<Vec<Sym>> {
@@ -226,41 +599,34 @@ impl Symbolizer {
             None
         };
 
+        let (linfo, inlined) = if self.src_location && self.inlined_fns {
+            let inlined = resolver.find_inlined_fns(addr)?;
+            self.resolve_inlined_fns(linfo, inlined)
+        } else {
+            (linfo, Box::default())
+        };
+
         let mut results = vec![];
         for sym in syms {
-            if let Some(ref linfo) = linfo {
-                let IntSym {
-                    name,
-                    addr: sym_addr,
-                    lang,
-                } = sym;
-                results.push(Sym {
-                    name: self.maybe_demangle(name, lang),
-                    addr: sym_addr,
-                    offset: addr - sym_addr,
-                    dir: Some(linfo.dir.to_path_buf()),
-                    file: Some(linfo.file.to_os_string()),
-                    line: linfo.line,
-                    column: linfo.column,
-                    _non_exhaustive: (),
-                });
-            } else {
-                let IntSym {
-                    name,
-                    addr: sym_addr,
-                    lang,
-                } = sym;
-                results.push(Sym {
-                    name: self.maybe_demangle(name, lang),
-                    addr: sym_addr,
-                    offset: addr - sym_addr,
-                    dir: None,
-                    file: None,
-                    line: None,
-                    column: None,
-                    _non_exhaustive: (),
-                });
-            }
+            let IntSym {
+                name,
+                addr: sym_addr,
+                lang,
+                module,
+            } = sym;
+            let (dir, file, line, column) = src_location(linfo.as_ref(), &self.src_path_map);
+            results.push(Sym {
+                name: self.maybe_demangle(name, lang),
+                addr: sym_addr,
+                offset: addr - sym_addr,
+                dir,
+                file,
+                line,
+                column,
+                inlined: inlined.clone(),
+                module: module.map(str::to_string),
+                _non_exhaustive: (),
+            });
         }
         Ok(results)
     }
@@ -280,94 +646,403 @@ impl Symbolizer {
         Ok(symbols)
     }
 
-    /// Symbolize the given list of user space addresses in the provided
-    /// process.
-    fn symbolize_user_addrs(&self, addrs: &[Addr], pid: Pid) -> Result<Vec<Vec<Sym>>> {
+    /// Symbolize an address residing in an APK (or zip archive)
+    /// described by the provided [`PathMapsEntry`].
+    fn symbolize_apk_addr(&self, addr: Addr, entry: &PathMapsEntry) -> Result<Vec<Sym>> {
+        let (norm_addr, elf_path, elf_parser) = normalize_apk_addr(addr, entry)?;
+        let apk_path = &entry.path.symbolic_path;
+        // Create an Android-style binary-in-APK path for
+        // reporting purposes.
+        let apk_elf_path = create_apk_elf_path(apk_path, &elf_path)?;
+        let backend = ElfBackend::Elf(Arc::new(elf_parser));
+
+        let resolver = ElfResolver::with_backend(&apk_elf_path, backend)?;
+        let symbols = self.symbolize_with_resolver(norm_addr, &resolver)?;
+        Ok(symbols)
+    }
+
+    /// Symbolize an address residing in an ELF file described by the
+    /// provided [`PathMapsEntry`].
+    fn symbolize_elf_addr(&self, addr: Addr, entry: &PathMapsEntry) -> Result<Vec<Sym>> {
+        let (path, access) = entry.path.local_path()?;
+        let norm_addr = normalize_elf_addr(addr, entry)?;
+        self.resolve_addr_in_elf(norm_addr, path).with_context(|| {
+            format!(
+                "failed to symbolize normalized address {norm_addr:#x} in ELF file {} (accessed via {access:?})",
+                path.display()
+            )
+        })
+    }
+
+    /// Symbolize a user space address residing in the file described
+    /// by the provided [`PathMapsEntry`].
+    fn symbolize_entry_addr(&self, addr: Addr, entry: &PathMapsEntry) -> Result<Vec<Sym>> {
+        if is_apk(&entry.path.symbolic_path) {
+            self.symbolize_apk_addr(addr, entry)
+        } else {
+            self.symbolize_elf_addr(addr, entry)
+        }
+    }
+
+    /// Create a resolver for the ELF file at `elf_path` inside the APK
+    /// (or zip archive) at `apk_path`.
+    fn create_apk_elf_resolver(&self, apk_path: &Path, elf_path: &Path) -> Result<ElfResolver> {
+        let apk = zip::Archive::open(apk_path)?;
+        for entry in apk.entries() {
+            let entry = entry?;
+            if entry.path == elf_path {
+                let mmap = apk.read_entry(&entry)?;
+                let backend = ElfBackend::Elf(Arc::new(ElfParser::from_mmap(mmap)));
+                let apk_elf_path = create_apk_elf_path(apk_path, elf_path)?;
+                return ElfResolver::with_backend(&apk_elf_path, backend)
+            }
+        }
+
+        Err(Error::with_not_found(format!(
+            "{} does not contain {}",
+            apk_path.display(),
+            elf_path.display()
+        )))
+    }
+
+    /// Symbolize an address residing in the vDSO, which is mapped at
+    /// `range` and represented by the provided parser.
+    fn symbolize_vdso_addr(
+        &self,
+        addr: Addr,
+        range: &Range<Addr>,
+        parser: &Arc<ElfParser>,
+    ) -> Result<Vec<Sym>> {
+        let norm_addr = normalize_vdso_addr(addr, range, parser)?;
+        let backend = ElfBackend::Elf(Arc::clone(parser));
+        let resolver = ElfResolver::with_backend(Path::new(VDSO_MAPS_COMPONENT), backend)?;
+        self.symbolize_with_resolver(norm_addr, &resolver)
+    }
+
+    /// Symbolize addresses that could not be attributed to any file
+    /// using the JIT provided jitdump file or perf map, if they reside in
+    /// anonymous executable memory.
+    ///
+    /// `unknown` contains the addresses in question, along with their
+    /// position in `all_symbols`.
+    fn symbolize_jit_addrs(
+        &self,
+        process: &Process,
+        unknown: &[(usize, Addr)],
+        all_symbols: &mut [Vec<Sym>],
+    ) -> Result<()> {
+        let Process {
+            pid,
+            perf_map,
+            jitdump,
+            jitdump_time,
+            _non_exhaustive: (),
+        } = process;
+
+        if unknown.is_empty() {
+            return Ok(())
+        }
+
+        let mut anon_ranges = Vec::new();
+        let mut mapped_jitdump = None;
+        for result in maps::parse(*pid)? {
+            let entry = result?;
+            if maps::is_anon_exec(&entry) {
+                let () = anon_ranges.push(entry.range);
+            } else if mapped_jitdump.is_none() {
+                mapped_jitdump = jitdump_entry_path(&entry, *pid).map(Path::to_path_buf);
+            }
+        }
+
+        let mut unknown = unknown
+            .iter()
+            .filter(|(_pos, addr)| anon_ranges.iter().any(|range| range.contains(addr)))
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(())
+        }
+
+        if let Some(path) = jitdump.as_deref().or(mapped_jitdump.as_deref()) {
+            match JitDumpResolver::from_file(path, *jitdump_time) {
+                Ok(resolver) => {
+                    for (pos, addr) in &unknown {
+                        all_symbols[*pos] = self.symbolize_with_resolver(*addr, &resolver)?;
+                    }
+                    let () = unknown.retain(|(pos, _addr)| all_symbols[*pos].is_empty());
+                }
+                // Only the explicitly provided jitdump file is mandatory.
+                Err(err) if jitdump.is_none() => {
+                    log::warn!("failed to load jitdump file {}: {err}", path.display());
+                }
+                Err(err) => return Err(err),
+            }
+
+            if unknown.is_empty() {
+                return Ok(())
+            }
+        }
+
+        let path = match perf_map {
+            Some(path) => path.clone(),
+            None => {
+                let path = perf_map_path(*pid);
+                if !path.exists() {
+                    return Ok(())
+                }
+                path
+            }
+        };
+
+        let resolver = match PerfMapResolver::from_file(&path) {
+            Ok(resolver) => resolver,
+            // Only the explicitly provided perf map is mandatory.
+            Err(err) if perf_map.is_none() => {
+                log::warn!("failed to load perf map {}: {err}", path.display());
+                return Ok(())
+            }
+            Err(err) => return Err(err),
+        };
+
+        for (pos, addr) in unknown {
+            all_symbols[*pos] = self.symbolize_with_resolver(*addr, &resolver)?;
+        }
+        Ok(())
+    }
+
+    /// Symbolize the given list of user space addresses, attributing
+    /// them to files using the provided proc maps entries.
+    ///
+    /// If the entries describe a live process, `process` should
+    /// reference it, in which case addresses inside the vDSO and
+    /// anonymous executable memory are symbolized as well.
+    fn symbolize_user_addrs_with_entries<E>(
+        &self,
+        addrs: &[Addr],
+        entries: E,
+        process: Option<&Process>,
+    ) -> Result<Vec<Vec<Sym>>>
+    where
+        E: Iterator<Item = Result<maps::MapsEntry>>,
+    {
         struct SymbolizeHandler<'sym> {
             /// The "outer" `Symbolizer` instance.
             symbolizer: &'sym Symbolizer,
             /// Symbols representing the symbolized addresses.
             all_symbols: Vec<Vec<Sym>>,
+            /// Addresses that could not be attributed to a file, along
+            /// with their index in `all_symbols`.
+            unknown: Vec<(usize, Addr)>,
+            /// The live process in which addresses are being
+            /// symbolized, if any.
+            pid: Option<Pid>,
+            /// The parser for the process' vDSO, once it has been read.
+            vdso: Option<Arc<ElfParser>>,
         }
 
-        impl SymbolizeHandler<'_> {
-            fn handle_apk_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()> {
-                let (norm_addr, elf_path, elf_parser) = normalize_apk_addr(addr, entry)?;
-                let apk_path = &entry.path.symbolic_path;
-                // Create an Android-style binary-in-APK path for
-                // reporting purposes.
-                let apk_elf_path = create_apk_elf_path(apk_path, &elf_path)?;
-                let backend = ElfBackend::Elf(Rc::new(elf_parser));
-
-                let resolver = ElfResolver::with_backend(&apk_elf_path, backend)?;
-                let symbols = self
-                    .symbolizer
-                    .symbolize_with_resolver(norm_addr, &resolver)?;
-                let () = self.all_symbols.push(symbols);
+        impl normalize::Handler for SymbolizeHandler<'_> {
+            #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{addr:#x}"))))]
+            fn handle_unknown_addr(&mut self, addr: Addr) -> Result<()> {
+                let () = self.unknown.push((self.all_symbols.len(), addr));
+                let () = self.all_symbols.push(Vec::new());
                 Ok(())
             }
 
-            fn handle_elf_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()> {
-                let path = &entry.path.maps_file;
-                let norm_addr = normalize_elf_addr(addr, entry)?;
-                let symbols = self
-                    .symbolizer
-                    .resolve_addr_in_elf(norm_addr, path)
-                    .with_context(|| {
-                        format!(
-                            "failed to symbolize normalized address {norm_addr:#x} in ELF file {}",
-                            path.display()
-                        )
-                    })?;
+            fn handle_entry_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()> {
+                let symbols = self.symbolizer.symbolize_entry_addr(addr, entry)?;
                 let () = self.all_symbols.push(symbols);
                 Ok(())
             }
-        }
 
-        impl normalize::Handler for SymbolizeHandler<'_> {
-            #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{_addr:#x}"))))]
-            fn handle_unknown_addr(&mut self, _addr: Addr) -> Result<()> {
-                let () = self.all_symbols.push(Vec::new());
+            fn handle_vdso_addr(&mut self, addr: Addr, range: &Range<Addr>) -> Result<()> {
+                let pid = if let Some(pid) = self.pid {
+                    pid
+                } else {
+                    return self.handle_unknown_addr(addr)
+                };
+                let parser = if let Some(parser) = &self.vdso {
+                    parser
+                } else {
+                    self.vdso.insert(Arc::new(read_vdso(pid, range)?))
+                };
+                let symbols = self.symbolizer.symbolize_vdso_addr(addr, range, parser)?;
+                let () = self.all_symbols.push(symbols);
                 Ok(())
             }
-
-            fn handle_entry_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()> {
-                let ext = entry
-                    .path
-                    .symbolic_path
-                    .extension()
-                    .unwrap_or_else(|| OsStr::new(""));
-                match ext.to_str() {
-                    Some("apk") | Some("zip") => self.handle_apk_addr(addr, entry),
-                    _ => self.handle_elf_addr(addr, entry),
-                }
-            }
         }
 
-        let entries = maps::parse(pid)?;
         let handler = SymbolizeHandler {
             symbolizer: self,
             all_symbols: Vec::with_capacity(addrs.len()),
+            unknown: Vec::new(),
+            pid: process.map(|process| process.pid),
+            vdso: None,
         };
 
         let handler = util::with_ordered_elems(
             addrs,
             |handler: &mut SymbolizeHandler<'_>| handler.all_symbols.as_mut_slice(),
-            |sorted_addrs| normalize_sorted_user_addrs_with_entries(sorted_addrs, entries, handler),
+            |sorted_addrs| -> Result<_> {
+                let mut handler =
+                    normalize_sorted_user_addrs_with_entries(sorted_addrs, entries, handler)?;
+                if let Some(process) = process {
+                    let () = self.symbolize_jit_addrs(
+                        process,
+                        &handler.unknown,
+                        &mut handler.all_symbols,
+                    )?;
+                }
+                Ok(handler)
+            },
         )?;
         Ok(handler.all_symbols)
     }
 
+    /// Symbolize the given list of user space addresses in the provided
+    /// process.
+    fn symbolize_user_addrs(&self, addrs: &[Addr], process: &Process) -> Result<Vec<Vec<Sym>>> {
+        let entries = maps::parse(process.pid)?;
+        self.symbolize_user_addrs_with_entries(addrs, entries, Some(process))
+    }
+
+    /// Symbolize the given list of user space addresses in the process
+    /// captured in the provided core file.
+    fn symbolize_core_addrs(&self, addrs: &[Addr], core_file: &CoreFile) -> Result<Vec<Vec<Sym>>> {
+        let CoreFile {
+            path,
+            sysroot,
+            _non_exhaustive: (),
+        } = core_file;
+
+        let entries = parse_core_maps(path, sysroot.as_deref())?;
+        self.symbolize_user_addrs_with_entries(addrs, entries.into_iter().map(Ok), None)
+    }
+
+    /// Symbolize the given list of user space addresses in the provided
+    /// process, resolving addresses belonging to different files in
+    /// parallel.
+    #[cfg(feature = "parallel")]
+    fn symbolize_user_addrs_parallel(
+        &self,
+        addrs: &[Addr],
+        process: &Process,
+    ) -> Result<Vec<Vec<Sym>>> {
+        #[derive(Default)]
+        struct GroupHandler {
+            /// The maps entries that addresses were found to reside in.
+            entries: Vec<PathMapsEntry>,
+            /// Addresses grouped by the file backing them, as tuples of
+            /// (position, address, index into `entries`).
+            groups: HashMap<PathBuf, Vec<(usize, Addr, usize)>>,
+            /// Addresses that could not be attributed to a file, along
+            /// with their position.
+            unknown: Vec<(usize, Addr)>,
+            /// The range at which the vDSO is mapped, if any addresses
+            /// reside in it.
+            vdso_range: Option<Range<Addr>>,
+            /// Addresses residing in the vDSO, along with their
+            /// position.
+            vdso: Vec<(usize, Addr)>,
+            /// The number of addresses handled so far.
+            count: usize,
+        }
+
+        impl normalize::Handler for GroupHandler {
+            fn handle_unknown_addr(&mut self, addr: Addr) -> Result<()> {
+                let () = self.unknown.push((self.count, addr));
+                self.count += 1;
+                Ok(())
+            }
+
+            fn handle_entry_addr(&mut self, addr: Addr, entry: &PathMapsEntry) -> Result<()> {
+                // Addresses arrive sorted, so all addresses of an entry
+                // are reported in one go.
+                if self.entries.last().map(|last| &last.range) != Some(&entry.range) {
+                    let () = self.entries.push(entry.clone());
+                }
+                let idx = self.entries.len() - 1;
+                let () = self
+                    .groups
+                    .entry(entry.path.maps_file.clone())
+                    .or_default()
+                    .push((self.count, addr, idx));
+                self.count += 1;
+                Ok(())
+            }
+
+            fn handle_vdso_addr(&mut self, addr: Addr, range: &Range<Addr>) -> Result<()> {
+                if self.vdso_range.is_none() {
+                    self.vdso_range = Some(range.clone());
+                }
+                let () = self.vdso.push((self.count, addr));
+                self.count += 1;
+                Ok(())
+            }
+        }
+
+        let entries = maps::parse(process.pid)?;
+        util::with_ordered_elems(
+            addrs,
+            |all_symbols: &mut Vec<Vec<Sym>>| all_symbols.as_mut_slice(),
+            |sorted_addrs| {
+                let GroupHandler {
+                    entries,
+                    groups,
+                    unknown,
+                    vdso_range,
+                    vdso,
+                    count,
+                } = normalize_sorted_user_addrs_with_entries(
+                    sorted_addrs,
+                    entries,
+                    GroupHandler::default(),
+                )?;
+
+                let resolved = groups
+                    .into_par_iter()
+                    .map(|(_path, addrs)| {
+                        addrs
+                            .into_iter()
+                            .map(|(pos, addr, idx)| {
+                                let symbols = self.symbolize_entry_addr(addr, &entries[idx])?;
+                                Ok((pos, symbols))
+                            })
+                            .collect::<Result<Vec<_>>>()
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let mut all_symbols = iter::repeat_with(Vec::new).take(count).collect::<Vec<_>>();
+                for (pos, symbols) in resolved.into_iter().flatten() {
+                    all_symbols[pos] = symbols;
+                }
+                if let Some(range) = vdso_range {
+                    let parser = Arc::new(read_vdso(process.pid, &range)?);
+                    for (pos, addr) in vdso {
+                        all_symbols[pos] = self.symbolize_vdso_addr(addr, &range, &parser)?;
+                    }
+                }
+                let () = self.symbolize_jit_addrs(process, &unknown, &mut all_symbols)?;
+                Ok(all_symbols)
+            },
+        )
+    }
+
     fn symbolize_kernel_addrs(&self, addrs: &[Addr], src: &Kernel) -> Result<Vec<Vec<Sym>>> {
         let Kernel {
             kallsyms,
             kernel_image,
+            modules_dir,
+            bpf_prog_info,
+            kaslr_offset,
             _non_exhaustive: (),
         } = src;
 
         let ksym_resolver = if let Some(kallsyms) = kallsyms {
             let ksym_resolver = self.ksym_cache.get_resolver(kallsyms)?;
             Some(ksym_resolver)
+        } else if kaslr_offset.is_some() {
+            // With an explicit KASLR offset the addresses are not
+            // assumed to stem from the running kernel.
+            None
         } else {
             let kallsyms = Path::new(KALLSYMS);
             let result = self.ksym_cache.get_resolver(kallsyms);
@@ -383,19 +1058,43 @@ impl Symbolizer {
             }
         };
 
+        let running_kernel = is_running_kernel(kallsyms.as_deref(), *kaslr_offset);
+
+        // Only if the addresses stem from the running kernel can we
+        // verify that the kernel image matches.
+        let build_id = if running_kernel {
+            let notes = Path::new(SYS_KERNEL_NOTES);
+            match read_kernel_build_id(notes) {
+                Ok(build_id) => build_id,
+                Err(err) => {
+                    log::debug!("{err}; unable to verify kernel image");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let elf_resolver = if let Some(image) = kernel_image {
+            let mapped_image = self.path_map.map(image);
+            let image = mapped_image.as_deref().unwrap_or(image);
             let backend = self.elf_cache.find(image)?;
             let elf_resolver = ElfResolver::with_backend(image, backend)?;
+            if let Some(build_id) = &build_id {
+                let () = verify_kernel_image(image, elf_resolver.get_parser(), build_id)?;
+            }
             Some(elf_resolver)
         } else {
-            let release = uname_release()?.to_str().unwrap().to_string();
-            let basename = "vmlinux-";
-            let dirs = [Path::new("/boot/"), Path::new("/usr/lib/debug/boot/")];
-            let kernel_image = dirs.iter().find_map(|dir| {
-                let path = dir.join(format!("{basename}{release}"));
-                path.exists().then_some(path)
-            });
-
+            let kernel_image = if let Some(build_id) = &build_id {
+                self.elf_cache.find_by_build_id(build_id)?
+            } else {
+                None
+            };
+            let kernel_image = if let Some(kernel_image) = kernel_image {
+                Some(kernel_image)
+            } else {
+                find_kernel_image(build_id.as_deref())?
+            };
             if let Some(image) = kernel_image {
                 let result = self.elf_cache.find(&image);
                 match result {
@@ -425,11 +1124,164 @@ impl Symbolizer {
             }
         };
 
-        let resolver = KernelResolver::new(ksym_resolver, elf_resolver)?;
+        let runtime_offset = if let (Some(ksym_resolver), Some(elf_resolver)) =
+            (ksym_resolver.as_ref(), elf_resolver.as_ref())
+        {
+            find_kaslr_offset(ksym_resolver, elf_resolver.get_parser())?
+        } else {
+            None
+        };
+        // Absent better knowledge we assume that the addresses are
+        // relative to the running kernel and vice versa.
+        let kaslr_offset = kaslr_offset.map(|offset| offset as Addr).or(runtime_offset);
+        let runtime_offset = runtime_offset.or(kaslr_offset).unwrap_or(0);
+        let kaslr_offset = kaslr_offset.unwrap_or(runtime_offset);
+
+        // Kernel modules and BPF programs are looked up using
+        // addresses of the running kernel.
+        let runtime_addrs = addrs
+            .iter()
+            .map(|addr| addr.wrapping_sub(kaslr_offset).wrapping_add(runtime_offset))
+            .collect::<Vec<_>>();
+        // The list of loaded modules describes the running kernel and
+        // would be meaningless for addresses from any other, unless the
+        // user explicitly asked for modules to be used.
+        let modules = if running_kernel || modules_dir.is_some() {
+            self.create_module_resolvers(&runtime_addrs, modules_dir.as_deref())?
+        } else {
+            Vec::new()
+        };
+        let bpf_progs = if *bpf_prog_info {
+            self.create_bpf_resolvers(&runtime_addrs, ksym_resolver.as_deref())
+        } else {
+            Vec::new()
+        };
+        let resolver = KernelResolver::new(
+            ksym_resolver,
+            elf_resolver,
+            modules,
+            bpf_progs,
+            kaslr_offset,
+            runtime_offset,
+        )?;
         let symbols = self.symbolize_addrs(addrs, &resolver)?;
         Ok(symbols)
     }
 
+    /// Create resolvers for the loaded kernel modules that `addrs`
+    /// reside in, using the `.ko` files found in `modules_dir`.
+    ///
+    /// Symbolization of module addresses is best-effort: modules for
+    /// which no `.ko` file is available or whose load addresses are
+    /// unknown are skipped.
+    fn create_module_resolvers(
+        &self,
+        addrs: &[Addr],
+        modules_dir: Option<&Path>,
+    ) -> Result<Vec<ModuleResolver>> {
+        let modules = match kmod::parse_modules(Path::new(PROC_MODULES)) {
+            Ok(modules) => modules,
+            Err(err) => {
+                log::warn!("failed to load kernel modules from {PROC_MODULES}: {err}; ignoring...");
+                return Ok(Vec::new())
+            }
+        };
+
+        let mut modules = modules
+            .into_iter()
+            .filter(|module| addrs.iter().any(|addr| module.range.contains(addr)))
+            .peekable();
+        if modules.peek().is_none() {
+            return Ok(Vec::new())
+        }
+
+        let modules_dir = match modules_dir {
+            Some(dir) => dir.to_path_buf(),
+            None => {
+                let release = uname_release()?.to_str().unwrap().to_string();
+                Path::new("/lib/modules").join(release)
+            }
+        };
+        let files = kmod::find_module_files(&modules_dir);
+
+        let resolvers = modules
+            .filter_map(|module| {
+                let path = files.get(&module.name)?;
+                let sections = Path::new(SYS_MODULE).join(&module.name).join("sections");
+                let mut section_addrs = kmod::read_section_addrs(&sections)
+                    .map_err(|err| log::debug!("{err}"))
+                    .unwrap_or_default();
+                if section_addrs.is_empty() {
+                    // Without information about individual sections we
+                    // assume that the module's code starts at its base
+                    // address.
+                    let () = section_addrs.push((".text".to_string(), module.range.start));
+                }
+
+                let result = self
+                    .elf_cache
+                    .find(path)
+                    .and_then(|backend| ElfResolver::with_backend(path, backend))
+                    .and_then(|resolver| {
+                        ModuleResolver::new(module.name.clone(), &section_addrs, resolver)
+                    });
+                match result {
+                    Ok(resolver) => Some(resolver),
+                    Err(err) => {
+                        log::warn!(
+                            "failed to create resolver for kernel module {} ({}): {err}; ignoring...",
+                            module.name,
+                            path.display()
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        Ok(resolvers)
+    }
+
+    /// Create resolvers for the JITed BPF programs that `addrs` reside
+    /// in.
+    ///
+    /// If kallsyms is available, only addresses attributed to a BPF
+    /// program by it are considered. Failure to retrieve program
+    /// information is not fatal: the corresponding addresses are
+    /// symbolized using kallsyms instead.
+    fn create_bpf_resolvers(
+        &self,
+        addrs: &[Addr],
+        ksym_resolver: Option<&KSymResolver>,
+    ) -> Vec<BpfProgResolver> {
+        let bpf_addrs = if let Some(ksym_resolver) = ksym_resolver {
+            addrs
+                .iter()
+                .copied()
+                .filter(|addr| {
+                    ksym_resolver
+                        .find_addresses_ksym(*addr)
+                        .any(|sym| sym.name.starts_with(BPF_PROG_SYM_PREFIX))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            addrs.to_vec()
+        };
+
+        if bpf_addrs.is_empty() {
+            return Vec::new()
+        }
+
+        match bpf::create_prog_resolvers(&bpf_addrs) {
+            Ok(resolvers) => resolvers,
+            Err(err) => {
+                log::warn!(
+                    "failed to load BPF program information: {err}; falling back to kallsyms..."
+                );
+                Vec::new()
+            }
+        }
+    }
+
     /// Symbolize a list of addresses.
     ///
     /// Symbolize a list of addresses according to the configuration
@@ -441,16 +1293,16 @@ impl Symbolizer {
                 path,
                 _non_exhaustive: (),
             }) => {
+                let mapped_path = self.path_map.map(path);
+                let path = mapped_path.as_deref().unwrap_or(path);
                 let backend = self.elf_cache.find(path)?;
                 let resolver = ElfResolver::with_backend(path, backend)?;
                 let symbols = self.symbolize_addrs(addrs, &resolver)?;
                 Ok(symbols)
             }
             Source::Kernel(kernel) => self.symbolize_kernel_addrs(addrs, kernel),
-            Source::Process(Process {
-                pid,
-                _non_exhaustive: (),
-            }) => self.symbolize_user_addrs(addrs, *pid),
+            Source::Process(process) => self.symbolize_user_addrs(addrs, process),
+            Source::CoreFile(core_file) => self.symbolize_core_addrs(addrs, core_file),
             Source::Gsym(Gsym::Data(GsymData {
                 data,
                 _non_exhaustive: (),
@@ -467,6 +1319,139 @@ impl Symbolizer {
                 let symbols = self.symbolize_addrs(addrs, &resolver)?;
                 Ok(symbols)
             }
+            Source::Breakpad(Breakpad::Data(BreakpadData {
+                data,
+                _non_exhaustive: (),
+            })) => {
+                let resolver = BreakpadResolver::with_data(data)?;
+                let symbols = self.symbolize_addrs(addrs, &resolver)?;
+                Ok(symbols)
+            }
+            Source::Breakpad(Breakpad::File(BreakpadFile {
+                path,
+                _non_exhaustive: (),
+            })) => {
+                let resolver = BreakpadResolver::new(path.clone())?;
+                let symbols = self.symbolize_addrs(addrs, &resolver)?;
+                Ok(symbols)
+            }
+        }
+    }
+
+    /// Symbolize addresses normalized by a
+    /// [`Normalizer`][crate::normalize::Normalizer].
+    ///
+    /// This method enables "offline" symbolization, i.e., of addresses
+    /// normalized on a different system. Each meta entry in `norm_addrs`
+    /// is first mapped to a local ELF file via `find_file`. For
+    /// [`UserAddrMeta::ApkElf`] entries, this file is expected to be
+    /// either the ELF file extracted from the APK or the APK itself, in
+    /// which case the ELF file is read from it (inflating it, if it is
+    /// compressed). If `find_file` returns `None`,
+    /// the file is looked up by build ID in the debug directories (see
+    /// [`Builder::set_debug_dirs`]) and using debuginfod (if configured).
+    ///
+    /// Addresses for which no file could be found, as well as unknown
+    /// ones, are reported without any symbols. Results are reported in
+    /// the order of `norm_addrs.addrs`.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all))]
+    pub fn symbolize_normalized<F>(
+        &self,
+        norm_addrs: &NormalizedUserAddrs,
+        mut find_file: F,
+    ) -> Result<Vec<Vec<Sym>>>
+    where
+        F: FnMut(&UserAddrMeta) -> Option<PathBuf>,
+    {
+        let NormalizedUserAddrs { addrs, meta } = norm_addrs;
+
+        let resolvers = meta
+            .iter()
+            .map(|meta| {
+                let build_id = match meta {
+                    UserAddrMeta::ApkElf(ApkElf { elf_build_id, .. }) => elf_build_id,
+                    UserAddrMeta::Elf(normalize::Elf { build_id, .. }) => build_id,
+                    UserAddrMeta::Vdso(normalize::Vdso { build_id, .. }) => build_id,
+                    UserAddrMeta::Unknown(..) => return Ok(None),
+                };
+
+                let mapped_path = || {
+                    let path = match meta {
+                        UserAddrMeta::ApkElf(ApkElf { apk_path, .. }) => apk_path,
+                        UserAddrMeta::Elf(normalize::Elf { path, .. }) => path,
+                        _ => return None,
+                    };
+                    self.path_map.map(path).filter(|path| path.exists())
+                };
+
+                let path = if let Some(path) = find_file(meta) {
+                    path
+                } else if let Some(path) = mapped_path() {
+                    path
+                } else if let Some(path) = build_id
+                    .as_ref()
+                    .map(|build_id| self.elf_cache.find_by_build_id(build_id))
+                    .transpose()?
+                    .flatten()
+                {
+                    path
+                } else {
+                    log::debug!("failed to find file for {meta:?}");
+                    return Ok(None)
+                };
+
+                let resolver = match meta {
+                    UserAddrMeta::ApkElf(ApkElf { elf_path, .. }) if is_apk(&path) => {
+                        self.create_apk_elf_resolver(&path, elf_path)
+                    }
+                    _ => self
+                        .elf_cache
+                        .find(&path)
+                        .and_then(|backend| ElfResolver::with_backend(&path, backend)),
+                }
+                .with_context(|| format!("failed to load ELF file {}", path.display()))?;
+                Ok(Some(resolver))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        addrs
+            .iter()
+            .map(|(addr, meta_idx)| {
+                let resolver = resolvers.get(*meta_idx).ok_or_invalid_input(|| {
+                    format!("normalized address {addr:#x} references invalid meta index {meta_idx}")
+                })?;
+                match resolver {
+                    Some(resolver) => {
+                        let path = resolver.file_name();
+                        self.symbolize_with_resolver(*addr, resolver)
+                            .with_context(|| {
+                                format!(
+                                    "failed to symbolize normalized address {addr:#x} in ELF file {}",
+                                    path.display()
+                                )
+                            })
+                    }
+                    None => Ok(Vec::new()),
+                }
+            })
+            .collect()
+    }
+
+    /// Symbolize a list of addresses, resolving them in parallel.
+    ///
+    /// Addresses are grouped by the object (e.g., ELF file) backing
+    /// them and groups are resolved concurrently on a thread pool. The
+    /// result is the same as that of [`Symbolizer::symbolize`], in the
+    /// same order. Currently only addresses from a [`Source::Process`]
+    /// are resolved in parallel; all other sources, including
+    /// [`Source::CoreFile`], which may reference more than one object as
+    /// well, are symbolized sequentially.
+    #[cfg(feature = "parallel")]
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, addrs = format_args!("{addrs:#x?}"))))]
+    pub fn symbolize_parallel(&self, src: &Source, addrs: &[Addr]) -> Result<Vec<Vec<Sym>>> {
+        match src {
+            Source::Process(process) => self.symbolize_user_addrs_parallel(addrs, process),
+            _ => self.symbolize(src, addrs),
         }
     }
 }
@@ -482,19 +1467,78 @@ impl Default for Symbolizer {
 mod tests {
     use super::*;
 
+    use std::fs::copy;
+    use std::fs::create_dir_all;
+    use std::fs::read;
+    use std::fs::write;
     use std::mem::transmute;
 
+    use tempfile::tempdir;
+
+    use crate::coredump::tests::make_core_file;
+    use crate::elf::types::PF_R;
+    use crate::elf::types::PF_X;
     use crate::elf::ElfParser;
     use crate::inspect::FindAddrOpts;
     use crate::inspect::SymType;
     use crate::mmap::Mmap;
     use crate::symbolize;
     use crate::symbolize::Symbolizer;
-    use crate::zip;
+    use crate::zip::tests::make_zip;
+    #[cfg(feature = "deflate")]
+    use crate::zip::tests::Compression;
+    use crate::ErrorKind;
+    use crate::Pid;
 
     use test_log::test;
 
 
+    /// Check that we correctly infer whether kernel addresses stem from
+    /// the running kernel.
+    #[test]
+    fn running_kernel_detection() {
+        assert!(is_running_kernel(None, None));
+        assert!(is_running_kernel(Some(Path::new(KALLSYMS)), None));
+        assert!(is_running_kernel(Some(Path::new("/proc/kallsyms")), None));
+        assert!(!is_running_kernel(Some(Path::new("/tmp/kallsyms")), None));
+        assert!(!is_running_kernel(None, Some(0)));
+        assert!(!is_running_kernel(Some(Path::new(KALLSYMS)), Some(0)));
+    }
+
+    /// Check that path mapping rules are applied as expected.
+    #[test]
+    fn path_mapping() {
+        let map = PathMap::default();
+        assert_eq!(map.map(Path::new("/usr/lib/libc.so.6")), None);
+
+        let map = PathMap {
+            sysroot: Some(PathBuf::from("/sysroot")),
+            prefixes: vec![
+                (PathBuf::from("/usr/lib"), PathBuf::from("/opt/lib")),
+                (PathBuf::from("/usr"), PathBuf::from("/opt/usr")),
+            ],
+        };
+        assert_eq!(
+            map.map(Path::new("/usr/lib/libc.so.6")),
+            Some(PathBuf::from("/opt/lib/libc.so.6"))
+        );
+        assert_eq!(
+            map.map(Path::new("/usr/bin/true")),
+            Some(PathBuf::from("/opt/usr/bin/true"))
+        );
+        // Prefixes are matched on a per-component basis.
+        assert_eq!(
+            map.map(Path::new("/usrx/true")),
+            Some(PathBuf::from("/sysroot/usrx/true"))
+        );
+        assert_eq!(
+            map.map(Path::new("/bin/true")),
+            Some(PathBuf::from("/sysroot/bin/true"))
+        );
+        assert_eq!(map.map(Path::new("bin/true")), None);
+    }
+
+
     /// Check that we can symbolize an address residing in a zip archive.
     #[test]
     fn symbolize_zip() {
@@ -553,4 +1597,121 @@ mod tests {
         assert_eq!(result.name, "the_answer");
         assert_eq!(result.addr, sym.addr);
     }
+
+    /// Check that we can symbolize addresses in a process captured in a
+    /// core file, using a sysroot to find mapped files.
+    #[test]
+    fn symbolize_core_file() {
+        let dir = tempdir().unwrap();
+        let sysroot = dir.path().join("sysroot");
+        let () = create_dir_all(sysroot.join("usr/bin")).unwrap();
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let _count = copy(elf, sysroot.join("usr/bin/app")).unwrap();
+
+        // The executable segment starting at file offset 0x1000 (and
+        // virtual address 0x2000000) got mapped at 0x10000000.
+        let core = make_core_file(&[(0x10000000, 0x10001000, 0x1000, "/usr/bin/app", PF_R | PF_X)]);
+        let core_path = dir.path().join("core");
+        let () = write(&core_path, core).unwrap();
+
+        let mut core_file = symbolize::CoreFile::new(&core_path);
+        core_file.sysroot = Some(sysroot);
+        let src = symbolize::Source::CoreFile(core_file);
+        let symbolizer = Symbolizer::new();
+        let results = symbolizer
+            .symbolize(&src, &[0x10000105, 0x20000000])
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].len(), 1);
+        assert_eq!(results[0][0].name, "factorial");
+        assert_eq!(results[0][0].addr, 0x2000100);
+        assert!(results[1].is_empty());
+
+        // Without the sysroot the executable cannot be found.
+        let src = symbolize::Source::CoreFile(symbolize::CoreFile::new(&core_path));
+        let err = symbolizer.symbolize(&src, &[0x10000105]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound, "{err}");
+    }
+
+    /// Check that we can symbolize normalized addresses inside a
+    /// deflate compressed ELF file stored in an APK.
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn symbolize_normalized_compressed_apk_elf() {
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let data = read(elf).unwrap();
+        let zip = make_zip(&[("lib/libapp.so", &data)], Compression::Deflate, false);
+        let dir = tempdir().unwrap();
+        let apk_path = dir.path().join("app.apk");
+        let () = write(&apk_path, zip).unwrap();
+
+        let norm_addrs = NormalizedUserAddrs {
+            addrs: vec![(0x2000105, 0)],
+            meta: vec![UserAddrMeta::ApkElf(ApkElf {
+                apk_path: PathBuf::from("/data/app/app.apk"),
+                elf_path: PathBuf::from("lib/libapp.so"),
+                elf_build_id: None,
+                access: None,
+                _non_exhaustive: (),
+            })],
+        };
+
+        let symbolizer = Symbolizer::new();
+        let results = symbolizer
+            .symbolize_normalized(&norm_addrs, |_meta| Some(apk_path.clone()))
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].len(), 1);
+        assert_eq!(results[0][0].name, "factorial");
+        assert_eq!(results[0][0].addr, 0x2000100);
+
+        // An ELF file that is not part of the APK should be reported.
+        let mut norm_addrs = norm_addrs;
+        if let UserAddrMeta::ApkElf(apk_elf) = &mut norm_addrs.meta[0] {
+            apk_elf.elf_path = PathBuf::from("lib/libother.so");
+        }
+        let err = symbolizer
+            .symbolize_normalized(&norm_addrs, |_meta| Some(apk_path.clone()))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound, "{err}");
+    }
+
+    /// Check that we can symbolize normalized addresses inside an ELF
+    /// file stored in a ZIP64 APK.
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn symbolize_normalized_zip64_apk_elf() {
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addresses.bin");
+        let data = read(elf).unwrap();
+        let zip = make_zip(&[("lib/libapp.so", &data)], Compression::Deflate, true);
+        let dir = tempdir().unwrap();
+        let apk_path = dir.path().join("app.apk");
+        let () = write(&apk_path, zip).unwrap();
+
+        let norm_addrs = NormalizedUserAddrs {
+            addrs: vec![(0x2000105, 0)],
+            meta: vec![UserAddrMeta::ApkElf(ApkElf {
+                apk_path: PathBuf::from("/data/app/app.apk"),
+                elf_path: PathBuf::from("lib/libapp.so"),
+                elf_build_id: None,
+                access: None,
+                _non_exhaustive: (),
+            })],
+        };
+
+        let symbolizer = Symbolizer::new();
+        let results = symbolizer
+            .symbolize_normalized(&norm_addrs, |_meta| Some(apk_path.clone()))
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].len(), 1);
+        assert_eq!(results[0][0].name, "factorial");
+        assert_eq!(results[0][0].addr, 0x2000100);
+    }
 }
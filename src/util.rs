@@ -1,12 +1,14 @@
 use std::cmp::Ordering;
 use std::ffi::CStr;
 use std::ffi::CString;
+use std::ffi::OsStr;
 use std::io;
 use std::iter;
 use std::mem::align_of;
 use std::mem::size_of;
 use std::mem::MaybeUninit;
 use std::os::unix::io::RawFd;
+use std::path::Path;
 use std::ptr::NonNull;
 use std::slice;
 
@@ -67,6 +69,20 @@ pub(crate) unsafe fn slice_from_user_array<'t, T>(items: *const T, num_items: us
     unsafe { slice::from_raw_parts(items, num_items) }
 }
 
+/// Format the provided bytes as a lower case hexadecimal string.
+pub(crate) fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Split a source file path as found in debug information into its
+/// directory and file name components.
+pub(crate) fn split_path(path: &str) -> (&Path, &OsStr) {
+    let path = Path::new(path);
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let file = path.file_name().unwrap_or(path.as_os_str());
+    (dir, file)
+}
+
 pub(crate) fn fstat(fd: RawFd) -> io::Result<libc::stat> {
     let mut dst = MaybeUninit::uninit();
     let rc = unsafe { libc::fstat(fd, dst.as_mut_ptr()) };
@@ -0,0 +1,106 @@
+//! Support for working with the vDSO of a process.
+//!
+//! The vDSO is an ELF shared object that the kernel maps into every
+//! process. It is not backed by any file, so we have to read it from
+//! the process' memory instead.
+
+use std::fs::File;
+use std::ops::Range;
+use std::os::unix::fs::FileExt as _;
+use std::slice;
+
+use crate::elf::ElfParser;
+use crate::maps::MapsEntry;
+use crate::maps::PathName;
+use crate::mmap::Mmap;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Pid;
+use crate::Result;
+
+
+/// The name of the proc maps entry representing the vDSO.
+pub(crate) const VDSO_MAPS_COMPONENT: &str = "[vdso]";
+
+
+/// Check whether a `MapsEntry` describes the vDSO.
+pub(crate) fn is_vdso(entry: &MapsEntry) -> bool {
+    matches!(&entry.path_name, Some(PathName::Component(component)) if component == VDSO_MAPS_COMPONENT)
+}
+
+
+/// Read the vDSO mapped at `range` in the process with the given PID
+/// and create an [`ElfParser`] for it.
+pub(crate) fn read_vdso(pid: Pid, range: &Range<Addr>) -> Result<ElfParser> {
+    let len = range.end - range.start;
+    let mmap = match pid {
+        Pid::Slf => {
+            // SAFETY: `getauxval` is always safe to call.
+            let base = unsafe { libc::getauxval(libc::AT_SYSINFO_EHDR) } as Addr;
+            if base != range.start {
+                return Err(Error::with_not_found(format!(
+                    "vDSO base address {base:#x} does not match proc maps entry at {:#x}",
+                    range.start
+                )))
+            }
+            // SAFETY: The vDSO is mapped at `base` for the lifetime of
+            //         the process and readable in its entirety.
+            let data = unsafe { slice::from_raw_parts(base as *const u8, len) };
+            Mmap::map_bytes(data)?
+        }
+        Pid::Pid(pid) => {
+            let path = format!("/proc/{pid}/mem");
+            let file = File::open(&path).with_context(|| format!("failed to open {path}"))?;
+            let mut data = vec![0; len];
+            let () = file
+                .read_exact_at(&mut data, range.start as u64)
+                .with_context(|| format!("failed to read vDSO from {path}"))?;
+            Mmap::map_bytes(&data)?
+        }
+    };
+
+    let parser = ElfParser::from_mmap(mmap);
+    Ok(parser)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::num::NonZeroU32;
+    use std::process;
+
+    use test_log::test;
+
+    use crate::maps;
+
+
+    /// Find the vDSO's proc maps entry in the given process.
+    fn vdso_range(pid: Pid) -> Range<Addr> {
+        maps::parse(pid)
+            .unwrap()
+            .map(Result::unwrap)
+            .find(is_vdso)
+            .map(|entry| entry.range)
+            .unwrap()
+    }
+
+    /// Check that we can read our own vDSO, both from memory and via
+    /// `/proc/<pid>/mem`.
+    #[test]
+    fn vdso_reading() {
+        let range = vdso_range(Pid::Slf);
+        let parser = read_vdso(Pid::Slf, &range).unwrap();
+        assert!(parser.find_section(".dynsym").unwrap().is_some());
+
+        let pid = Pid::Pid(NonZeroU32::new(process::id()).unwrap());
+        let parser = read_vdso(pid, &range).unwrap();
+        assert!(parser.find_section(".dynsym").unwrap().is_some());
+
+        let range = range.start + 1..range.end;
+        let err = read_vdso(Pid::Slf, &range).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+}
@@ -20,14 +20,20 @@ use crate::mmap::Mmap;
 use crate::util::Pod;
 use crate::util::ReadRaw as _;
 use crate::Error;
+use crate::ErrorExt as _;
 use crate::IntoError as _;
 use crate::Result;
 
 const CD_FILE_HEADER_MAGIC: u32 = 0x02014b50;
 const END_OF_CD_RECORD_MAGIC: u32 = 0x06054b50;
+const ZIP64_END_OF_CD_RECORD_MAGIC: u32 = 0x06064b50;
+const ZIP64_END_OF_CD_LOCATOR_MAGIC: u32 = 0x07064b50;
 const LOCAL_FILE_HEADER_MAGIC: u32 = 0x04034b50;
+/// The header ID of the ZIP64 extended information extra field.
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
 const FLAG_ENCRYPTED: u16 = 1 << 0;
-const FLAG_HAS_DATA_DESCRIPTOR: u16 = 1 << 3;
+pub(crate) const COMPRESSION_STORED: u16 = 0;
+pub(crate) const COMPRESSION_DEFLATE: u16 = 8;
 
 
 /// See section 4.3.16 of the spec.
@@ -62,6 +68,56 @@ struct EndOfCdRecord {
 unsafe impl Pod for EndOfCdRecord {}
 
 
+/// See section 4.3.15 of the spec.
+#[repr(C, packed)]
+struct Zip64EndOfCdLocator {
+    /// Magic value equal to ZIP64_END_OF_CD_LOCATOR_MAGIC.
+    magic: u32,
+    /// Number of the disk containing the ZIP64 end of central directory
+    /// record.
+    cd_end_disk: u32,
+    /// Offset of the ZIP64 end of central directory record from the
+    /// beginning of the archive.
+    cd_end_offset: u64,
+    /// Total number of disks.
+    disks: u32,
+}
+
+// SAFETY: `Zip64EndOfCdLocator` is valid for any bit pattern.
+unsafe impl Pod for Zip64EndOfCdLocator {}
+
+
+/// See section 4.3.14 of the spec.
+#[repr(C, packed)]
+struct Zip64EndOfCdRecord {
+    /// Magic value equal to ZIP64_END_OF_CD_RECORD_MAGIC.
+    magic: u32,
+    /// Size of the remainder of the record, excluding `magic` and this
+    /// field.
+    record_size: u64,
+    version: u16,
+    /// Minimum zip version needed to extract the file.
+    min_version: u16,
+    /// Number of the file containing this structure.
+    this_disk: u32,
+    /// Number of the file containing the beginning of the central
+    /// directory.
+    cd_disk: u32,
+    /// Number of central directory records on this disk.
+    cd_records: u64,
+    /// Number of central directory records on all disks.
+    cd_records_total: u64,
+    /// Size of the central directory.
+    cd_size: u64,
+    /// Offset of the central directory from the beginning of the archive.
+    cd_offset: u64,
+    // Variable sized extensible data sector.
+}
+
+// SAFETY: `Zip64EndOfCdRecord` is valid for any bit pattern.
+unsafe impl Pod for Zip64EndOfCdRecord {}
+
+
 /// See section 4.3.12 of the spec.
 #[repr(C, packed)]
 struct CdFileHeader {
@@ -93,6 +149,68 @@ struct CdFileHeader {
 unsafe impl Pod for CdFileHeader {}
 
 
+/// Information about an entry as provided by its central directory file
+/// header, with ZIP64 extended information already taken into account.
+struct CdEntry {
+    compression: u16,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    /// Offset of the entry's local file header from the beginning of
+    /// the archive.
+    offset: u64,
+}
+
+impl CdEntry {
+    /// Create a `CdEntry` from a central directory file header and its
+    /// extra field data.
+    ///
+    /// Sizes and offset that do not fit into the header are stored in
+    /// the ZIP64 extended information extra field instead (see section
+    /// 4.5.3 of the spec), in a fixed order and only if the
+    /// corresponding header value is saturated.
+    fn from_header(cdfh: &CdFileHeader, mut extra: &[u8]) -> Option<Result<Self>> {
+        let mut uncompressed_size = u64::from(cdfh.uncompressed_size);
+        let mut compressed_size = u64::from(cdfh.compressed_size);
+        let mut offset = u64::from(cdfh.offset);
+
+        while !extra.is_empty() {
+            let id = extra.read_u16()?;
+            let size = extra.read_u16()?;
+            let mut field = extra.read_slice(size.into())?;
+
+            if id == ZIP64_EXTRA_FIELD_ID {
+                if uncompressed_size == u64::from(u32::MAX) {
+                    uncompressed_size = field.read_u64()?;
+                }
+                if compressed_size == u64::from(u32::MAX) {
+                    compressed_size = field.read_u64()?;
+                }
+                if offset == u64::from(u32::MAX) {
+                    offset = field.read_u64()?;
+                }
+                if cdfh.disk == u16::MAX {
+                    let disk = field.read_u32()?;
+                    if disk != 0 {
+                        return Some(Err(Error::with_invalid_data(
+                            "multi-disk archives are unsupported",
+                        )))
+                    }
+                }
+                break
+            }
+        }
+
+        let entry = CdEntry {
+            compression: cdfh.compression,
+            compressed_size,
+            uncompressed_size,
+            offset,
+        };
+        Some(Ok(entry))
+    }
+}
+
+
 /// See section 4.3.7 of the spec.
 #[repr(C, packed)]
 struct LocalFileHeader {
@@ -124,8 +242,10 @@ pub struct Entry<'archive> {
     pub path: &'archive Path,
     /// The offset of the data from the beginning of the archive.
     pub data_offset: usize,
-    /// Pointer to the file data.
+    /// Pointer to the file data, in potentially compressed form.
     pub data: &'archive [u8],
+    /// The size of the file data once uncompressed.
+    pub uncompressed_size: usize,
 }
 
 impl Debug for Entry<'_> {
@@ -135,6 +255,7 @@ impl Debug for Entry<'_> {
             path,
             data_offset,
             data,
+            uncompressed_size,
         } = self;
 
         f.debug_struct(stringify!(Entry))
@@ -142,6 +263,7 @@ impl Debug for Entry<'_> {
             .field("path", path)
             .field("data_offset", data_offset)
             .field("data", &data.get(0..(min(data.len(), 32))))
+            .field("uncompressed_size", uncompressed_size)
             .finish()
     }
 }
@@ -156,13 +278,19 @@ pub struct EntryIter<'archive> {
     /// This read pointer will be advanced as entries are read.
     cd_record_data: &'archive [u8],
     /// The number of remaining records.
-    remaining_records: u16,
+    remaining_records: u64,
 }
 
 impl<'archive> EntryIter<'archive> {
-    fn parse_entry_at_offset(data: &[u8], offset: u32) -> Result<Entry<'_>> {
-        fn entry_impl(data: &[u8], offset: u32) -> Option<Result<Entry<'_>>> {
-            let mut data = data.get(offset as usize..)?;
+    /// Parse the entry whose local file header is located at `offset`.
+    ///
+    /// Entries with a data descriptor may not report the sizes of their
+    /// data in the local file header, so we always use the ones from
+    /// the central directory, provided in `cd_entry`.
+    fn parse_entry_at_offset<'dat>(data: &'dat [u8], cd_entry: &CdEntry) -> Result<Entry<'dat>> {
+        fn entry_impl<'dat>(data: &'dat [u8], cd_entry: &CdEntry) -> Option<Result<Entry<'dat>>> {
+            let offset = usize::try_from(cd_entry.offset).ok()?;
+            let mut data = data.get(offset..)?;
             let start = data.as_ptr();
 
             let lfh = data.read_pod::<LocalFileHeader>()?;
@@ -172,7 +300,7 @@ impl<'archive> EntryIter<'archive> {
                 )))
             }
 
-            if (lfh.flags & FLAG_ENCRYPTED) != 0 || (lfh.flags & FLAG_HAS_DATA_DESCRIPTOR) != 0 {
+            if (lfh.flags & FLAG_ENCRYPTED) != 0 {
                 return Some(Err(Error::with_invalid_data(
                     "attempted lookup of unsupported entry",
                 )))
@@ -183,21 +311,23 @@ impl<'archive> EntryIter<'archive> {
 
             let _extra = data.read_slice(lfh.extra_field_length.into())?;
             // SAFETY: Both pointers point into the same underlying byte array.
-            let data_offset = offset as usize
-                + usize::try_from(unsafe { data.as_ptr().offset_from(start) }).unwrap();
-            let data = data.read_slice(lfh.compressed_size as usize)?;
+            let data_offset =
+                offset + usize::try_from(unsafe { data.as_ptr().offset_from(start) }).unwrap();
+            let data = data.read_slice(usize::try_from(cd_entry.compressed_size).ok()?)?;
+            let uncompressed_size = usize::try_from(cd_entry.uncompressed_size).ok()?;
 
             let entry = Entry {
-                compression: lfh.compression,
+                compression: cd_entry.compression,
                 path,
                 data_offset,
                 data,
+                uncompressed_size,
             };
 
             Some(Ok(entry))
         }
 
-        entry_impl(data, offset)
+        entry_impl(data, cd_entry)
             .unwrap_or_else(|| Err(Error::with_invalid_data("failed to read archive entry")))
     }
 
@@ -214,16 +344,20 @@ impl<'archive> EntryIter<'archive> {
             let _name = iter
                 .cd_record_data
                 .read_slice(cdfh.file_name_length.into())?;
-            let _extra = iter
+            let extra = iter
                 .cd_record_data
                 .read_slice(cdfh.extra_field_length.into())?;
             let _comment = iter
                 .cd_record_data
                 .read_slice(cdfh.file_comment_length.into())?;
 
+            let cd_entry = match CdEntry::from_header(&cdfh, extra)? {
+                Ok(cd_entry) => cd_entry,
+                Err(err) => return Some(Err(err)),
+            };
             Some(EntryIter::parse_entry_at_offset(
                 iter.archive_data,
-                cdfh.offset,
+                &cd_entry,
             ))
         }
 
@@ -245,19 +379,43 @@ impl<'archive> Iterator for EntryIter<'archive> {
 }
 
 
+/// Inflate deflate compressed data, expected to be `size` bytes in size
+/// when inflated.
+#[cfg(feature = "deflate")]
+fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    let inflated =
+        miniz_oxide::inflate::decompress_to_vec_with_limit(data, size).map_err(|err| {
+            Error::with_invalid_data(format!("failed to inflate data: {:?}", err.status))
+        })?;
+    if inflated.len() != size {
+        return Err(Error::with_invalid_data(format!(
+            "inflated data has unexpected size: {} (expected {size})",
+            inflated.len()
+        )))
+    }
+    Ok(inflated)
+}
+
+#[cfg(not(feature = "deflate"))]
+fn inflate(_data: &[u8], _size: usize) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "deflate compressed data is not supported; enable the `deflate` feature",
+    ))
+}
+
+
 /// An open zip archive.
 ///
 /// Only basic ZIP files are supported, in particular the following are not
 /// supported:
 /// - encryption
-/// - streaming
+/// - compression methods other than deflate
 /// - multi-part ZIP files
-/// - ZIP64
 #[derive(Debug)]
 pub(crate) struct Archive {
     mmap: Mmap,
-    cd_offset: u32,
-    cd_records: u16,
+    cd_offset: u64,
+    cd_records: u64,
 }
 
 impl Archive {
@@ -283,29 +441,79 @@ impl Archive {
         Ok(slf)
     }
 
-    fn try_parse_end_of_cd(mut data: &[u8]) -> Option<Result<(u32, u16)>> {
-        let eocd = data.read_pod::<EndOfCdRecord>()?;
+    /// Try parsing the ZIP64 end of central directory record, given the
+    /// offset of the (regular) end of central directory record, which
+    /// the ZIP64 end of central directory locator immediately precedes.
+    ///
+    /// `None` is returned if no locator is present, i.e., if the archive
+    /// is not a ZIP64 one.
+    fn try_parse_zip64_end_of_cd(data: &[u8], eocd_offset: usize) -> Option<Result<(u64, u64)>> {
+        let locator_offset = eocd_offset.checked_sub(size_of::<Zip64EndOfCdLocator>())?;
+        let locator = data
+            .get(locator_offset..)?
+            .read_pod::<Zip64EndOfCdLocator>()?;
+        if locator.magic != ZIP64_END_OF_CD_LOCATOR_MAGIC {
+            return None
+        }
+
+        if locator.cd_end_disk != 0 || locator.disks > 1 {
+            return Some(Err(Error::with_invalid_data(
+                "archive is unsupported and cannot be opened",
+            )))
+        }
+
+        let eocd = usize::try_from(locator.cd_end_offset)
+            .ok()
+            .and_then(|offset| data.get(offset..)?.read_pod::<Zip64EndOfCdRecord>());
+        let eocd = match eocd {
+            Some(eocd) if eocd.magic == ZIP64_END_OF_CD_RECORD_MAGIC => eocd,
+            _ => {
+                return Some(Err(Error::with_invalid_data(
+                    "ZIP64 end of central directory locator references invalid record",
+                )))
+            }
+        };
+
+        if eocd.this_disk != 0 || eocd.cd_disk != 0 || eocd.cd_records_total != eocd.cd_records {
+            // This is a valid eocd, but we only support single-file archives.
+            Some(Err(Error::with_invalid_data(
+                "archive is unsupported and cannot be opened",
+            )))
+        } else {
+            Some(Ok((eocd.cd_offset, eocd.cd_records)))
+        }
+    }
+
+    fn try_parse_end_of_cd(data: &[u8], offset: usize) -> Option<Result<(u64, u64)>> {
+        let mut eocd_data = data.get(offset..)?;
+        let eocd = eocd_data.read_pod::<EndOfCdRecord>()?;
         if eocd.magic != END_OF_CD_RECORD_MAGIC {
             return None
         }
 
         // Make sure that another `comment_length` bytes exist after the end of
         // cd record.
-        let () = data.ensure(eocd.comment_length.into())?;
+        let () = eocd_data.ensure(eocd.comment_length.into())?;
+
+        // ZIP64 archives store the actual central directory information
+        // in a separate record.
+        if let Some(result) = Self::try_parse_zip64_end_of_cd(data, offset) {
+            return Some(result)
+        }
 
         if eocd.this_disk != 0 || eocd.cd_disk != 0 || eocd.cd_records_total != eocd.cd_records {
-            // This is a valid eocd, but we only support single-file non-ZIP64 archives.
+            // This is a valid eocd, but we only support single-file archives.
             Some(Err(Error::with_invalid_data(
                 "archive is unsupported and cannot be opened",
             )))
         } else {
-            Some(Ok((eocd.cd_offset, eocd.cd_records)))
+            Some(Ok((eocd.cd_offset.into(), eocd.cd_records.into())))
         }
     }
 
     /// Search for the central directory at the end of the archive (represented
     /// by the provided slice of bytes).
-    fn find_cd(data: &[u8]) -> Result<(u32, u16)> {
+    fn find_cd(data: &[u8]) -> Result<(u64, u64)> {
         // Because the end of central directory ends with a variable length
         // array of up to 0xFFFF bytes we can't know exactly where it starts and
         // need to search for it at the end of the file, scanning the [start,
@@ -319,17 +527,21 @@ impl Archive {
         let start = end.saturating_sub(1 << 16);
 
         for offset in (start..=end).rev() {
-            let result = Self::try_parse_end_of_cd(data.get(offset..).unwrap());
+            let result = Self::try_parse_end_of_cd(data, offset);
             match result {
                 None => continue,
                 Some(Ok((cd_offset, cd_records))) => {
                     // Validate the offset and records quickly to eliminate
                     // potential error cases later on.
-                    let cd_range = cd_offset as usize
-                        ..cd_offset as usize + usize::from(cd_records) * size_of::<CdFileHeader>();
-                    let _cd = data.get(cd_range).ok_or_unexpected_eof(|| {
-                        "failed to retrieve central directory entries; archive is corrupted"
-                    })?;
+                    let cd_end = cd_records
+                        .checked_mul(size_of::<CdFileHeader>() as u64)
+                        .and_then(|cd_size| cd_size.checked_add(cd_offset))
+                        .and_then(|cd_end| usize::try_from(cd_end).ok());
+                    let _cd = cd_end
+                        .and_then(|cd_end| data.get(cd_offset as usize..cd_end))
+                        .ok_or_unexpected_eof(|| {
+                            "failed to retrieve central directory entries; archive is corrupted"
+                        })?;
                     return Ok((cd_offset, cd_records))
                 }
                 Some(Err(err)) => return Err(err),
@@ -356,16 +568,39 @@ impl Archive {
         iter
     }
 
-    /// Retrieve the [`Mmap`] object used by this `Archive`.
-    #[inline]
-    pub fn mmap(&self) -> &Mmap {
-        &self.mmap
+    /// Retrieve the uncompressed data of the provided entry of this
+    /// archive.
+    ///
+    /// Data of stored entries is referenced directly, while that of
+    /// deflate compressed ones is inflated into a newly created
+    /// mapping.
+    pub fn read_entry(&self, entry: &Entry<'_>) -> Result<Mmap> {
+        match entry.compression {
+            COMPRESSION_STORED => {
+                let bounds = entry.data_offset..entry.data_offset + entry.data.len();
+                self.mmap.constrain(bounds.clone()).ok_or_invalid_data(|| {
+                    format!(
+                        "invalid data bounds ({bounds:?}) of entry {}",
+                        entry.path.display()
+                    )
+                })
+            }
+            COMPRESSION_DEFLATE => {
+                let data = inflate(entry.data, entry.uncompressed_size)
+                    .with_context(|| format!("failed to inflate entry {}", entry.path.display()))?;
+                Mmap::map_bytes(&data)
+            }
+            compression => Err(Error::with_unsupported(format!(
+                "entry {} uses unsupported compression method {compression}",
+                entry.path.display()
+            ))),
+        }
     }
 }
 
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     use std::io::copy;
@@ -381,6 +616,156 @@ mod tests {
     use crate::ErrorKind;
 
 
+    /// Flag indicating that sizes and CRC of an entry are stored in a
+    /// data descriptor following its data.
+    const FLAG_HAS_DATA_DESCRIPTOR: u16 = 1 << 3;
+    const DATA_DESCRIPTOR_MAGIC: u32 = 0x08074b50;
+
+
+    /// The compression methods [`make_zip`] supports for its entries.
+    #[derive(Clone, Copy, Debug)]
+    pub(crate) enum Compression {
+        Stored,
+        #[cfg(feature = "deflate")]
+        Deflate,
+    }
+
+    impl Compression {
+        /// Retrieve the zip compression method identifier.
+        fn method(self) -> u16 {
+            match self {
+                Self::Stored => COMPRESSION_STORED,
+                #[cfg(feature = "deflate")]
+                Self::Deflate => COMPRESSION_DEFLATE,
+            }
+        }
+
+        /// Compress `data` using the compression method.
+        fn compress(self, data: &[u8]) -> Vec<u8> {
+            match self {
+                Self::Stored => data.to_vec(),
+                #[cfg(feature = "deflate")]
+                Self::Deflate => miniz_oxide::deflate::compress_to_vec(data, 6),
+            }
+        }
+    }
+
+
+    /// Create a zip archive containing the provided entries, each
+    /// compressed using `compression` and described by a data
+    /// descriptor, similar to what streaming zip writers produce. If
+    /// `zip64` is set, sizes and offsets are stored in ZIP64 records.
+    ///
+    /// CRCs are not checked by our parser and are left zeroed.
+    pub(crate) fn make_zip(
+        entries: &[(&str, &[u8])],
+        compression: Compression,
+        zip64: bool,
+    ) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut cd = Vec::new();
+
+        for (name, data) in entries {
+            let offset = zip.len() as u64;
+            let compressed = compression.compress(data);
+
+            zip.extend_from_slice(&LOCAL_FILE_HEADER_MAGIC.to_le_bytes());
+            zip.extend_from_slice(&45u16.to_le_bytes());
+            zip.extend_from_slice(&FLAG_HAS_DATA_DESCRIPTOR.to_le_bytes());
+            zip.extend_from_slice(&compression.method().to_le_bytes());
+            // Modification time & date.
+            zip.extend_from_slice(&[0; 4]);
+            // CRC, compressed, and uncompressed size are all deferred
+            // to the data descriptor.
+            zip.extend_from_slice(&[0; 12]);
+            zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            zip.extend_from_slice(&0u16.to_le_bytes());
+            zip.extend_from_slice(name.as_bytes());
+            zip.extend_from_slice(&compressed);
+
+            zip.extend_from_slice(&DATA_DESCRIPTOR_MAGIC.to_le_bytes());
+            zip.extend_from_slice(&0u32.to_le_bytes());
+            if zip64 {
+                zip.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+                zip.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            } else {
+                zip.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            }
+
+            cd.extend_from_slice(&CD_FILE_HEADER_MAGIC.to_le_bytes());
+            cd.extend_from_slice(&45u16.to_le_bytes());
+            cd.extend_from_slice(&45u16.to_le_bytes());
+            cd.extend_from_slice(&FLAG_HAS_DATA_DESCRIPTOR.to_le_bytes());
+            cd.extend_from_slice(&compression.method().to_le_bytes());
+            cd.extend_from_slice(&[0; 4]);
+            cd.extend_from_slice(&0u32.to_le_bytes());
+            if zip64 {
+                cd.extend_from_slice(&u32::MAX.to_le_bytes());
+                cd.extend_from_slice(&u32::MAX.to_le_bytes());
+            } else {
+                cd.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                cd.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            }
+            cd.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            let extra_len = if zip64 { 28u16 } else { 0 };
+            cd.extend_from_slice(&extra_len.to_le_bytes());
+            // Comment length, disk, and internal & external attributes.
+            cd.extend_from_slice(&[0; 10]);
+            if zip64 {
+                cd.extend_from_slice(&u32::MAX.to_le_bytes());
+            } else {
+                cd.extend_from_slice(&(offset as u32).to_le_bytes());
+            }
+            cd.extend_from_slice(name.as_bytes());
+            if zip64 {
+                cd.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+                cd.extend_from_slice(&24u16.to_le_bytes());
+                cd.extend_from_slice(&(data.len() as u64).to_le_bytes());
+                cd.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+                cd.extend_from_slice(&offset.to_le_bytes());
+            }
+        }
+
+        let cd_offset = zip.len() as u64;
+        zip.extend_from_slice(&cd);
+
+        if zip64 {
+            let eocd_offset = zip.len() as u64;
+            zip.extend_from_slice(&ZIP64_END_OF_CD_RECORD_MAGIC.to_le_bytes());
+            zip.extend_from_slice(&44u64.to_le_bytes());
+            zip.extend_from_slice(&45u16.to_le_bytes());
+            zip.extend_from_slice(&45u16.to_le_bytes());
+            zip.extend_from_slice(&[0; 8]);
+            zip.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+            zip.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+            zip.extend_from_slice(&(cd.len() as u64).to_le_bytes());
+            zip.extend_from_slice(&cd_offset.to_le_bytes());
+
+            zip.extend_from_slice(&ZIP64_END_OF_CD_LOCATOR_MAGIC.to_le_bytes());
+            zip.extend_from_slice(&0u32.to_le_bytes());
+            zip.extend_from_slice(&eocd_offset.to_le_bytes());
+            zip.extend_from_slice(&1u32.to_le_bytes());
+
+            zip.extend_from_slice(&END_OF_CD_RECORD_MAGIC.to_le_bytes());
+            zip.extend_from_slice(&[0; 4]);
+            zip.extend_from_slice(&u16::MAX.to_le_bytes());
+            zip.extend_from_slice(&u16::MAX.to_le_bytes());
+            zip.extend_from_slice(&u32::MAX.to_le_bytes());
+            zip.extend_from_slice(&u32::MAX.to_le_bytes());
+        } else {
+            zip.extend_from_slice(&END_OF_CD_RECORD_MAGIC.to_le_bytes());
+            zip.extend_from_slice(&[0; 4]);
+            zip.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+            zip.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+            zip.extend_from_slice(&(cd.len() as u32).to_le_bytes());
+            zip.extend_from_slice(&(cd_offset as u32).to_le_bytes());
+        }
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip
+    }
+
+
     /// Check that the `Debug` representation of [`Entry`] is as expected.
     #[test]
     fn zip_entry_debug() {
@@ -389,12 +774,13 @@ mod tests {
             path: Path::new("some-entry-path.so"),
             data_offset: 56,
             data: &[1, 2, 3, 4],
+            uncompressed_size: 4,
         };
 
         let dbg = format!("{entry:?}");
         assert_eq!(
             dbg,
-            r#"Entry { compression: 42, path: "some-entry-path.so", data_offset: 56, data: Some([1, 2, 3, 4]) }"#
+            r#"Entry { compression: 42, path: "some-entry-path.so", data_offset: 56, data: Some([1, 2, 3, 4]), uncompressed_size: 4 }"#
         );
     }
 
@@ -458,6 +844,85 @@ mod tests {
 
         let elf = ElfParser::open_file(file).unwrap();
         assert!(elf.find_section(".text").is_ok());
+
+        let mmap = archive.read_entry(&entry).unwrap();
+        assert_eq!(mmap.deref(), entry.data);
+    }
+
+    /// Check that we can read deflate compressed entries that are
+    /// described by a data descriptor.
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn zip_deflate_entry_reading() {
+        let data = (0..4096u32).flat_map(u32::to_le_bytes).collect::<Vec<_>>();
+        let zip = make_zip(
+            &[("lib/foo.so", b"foobar"), ("lib/bar.so", &data)],
+            Compression::Deflate,
+            false,
+        );
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&zip).unwrap();
+
+        let archive = Archive::open(file.path()).unwrap();
+        let entries = archive.entries().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let entry = &entries[1];
+        assert_eq!(entry.compression, COMPRESSION_DEFLATE);
+        assert_eq!(entry.path, Path::new("lib/bar.so"));
+        assert_eq!(entry.uncompressed_size, data.len());
+        assert!(entry.data.len() < data.len());
+
+        let mmap = archive.read_entry(entry).unwrap();
+        assert_eq!(mmap.deref(), data.as_slice());
+
+        let mmap = archive.read_entry(&entries[0]).unwrap();
+        assert_eq!(mmap.deref(), b"foobar");
+    }
+
+    /// Check that we can open ZIP64 archives and read their entries.
+    #[test]
+    fn zip64_entry_reading() {
+        let zip = make_zip(
+            &[("lib/foo.so", b"foobar"), ("lib/bar.so", b"bazqux")],
+            Compression::Stored,
+            true,
+        );
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&zip).unwrap();
+
+        let archive = Archive::open(file.path()).unwrap();
+        assert_eq!(archive.cd_records, 2);
+
+        let entries = archive.entries().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, Path::new("lib/foo.so"));
+        assert_eq!(entries[0].data, b"foobar");
+        assert_eq!(entries[0].uncompressed_size, 6);
+        assert_eq!(entries[1].path, Path::new("lib/bar.so"));
+        assert_eq!(entries[1].data, b"bazqux");
+
+        let mmap = archive.read_entry(&entries[1]).unwrap();
+        assert_eq!(mmap.deref(), b"bazqux");
+    }
+
+    /// Check that we fail opening a ZIP64 archive whose end of central
+    /// directory locator references an invalid record.
+    #[test]
+    fn zip64_invalid_locator() {
+        let mut zip = make_zip(&[("lib/foo.so", b"foobar")], Compression::Stored, true);
+        // Corrupt the offset of the ZIP64 end of central directory
+        // record, stored in the locator preceding the regular end of
+        // central directory record.
+        let offset = zip.len() - size_of::<EndOfCdRecord>() - size_of::<Zip64EndOfCdLocator>() + 8;
+        let () = zip[offset..offset + 8].copy_from_slice(&1u64.to_le_bytes());
+
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&zip).unwrap();
+
+        let err = Archive::open(file.path()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{err}");
+        assert!(err.to_string().contains("invalid record"), "{err}");
     }
 
     /// Check that we fail `Archive` creation for corrupted archives.
@@ -475,7 +940,7 @@ mod tests {
             .deref()
             .get(
                 ..archive.cd_offset as usize
-                    + usize::from(archive.cd_records) * size_of::<CdFileHeader>()
+                    + archive.cd_records as usize * size_of::<CdFileHeader>()
                     - 1,
             )
             .unwrap();
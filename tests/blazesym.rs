@@ -3,11 +3,22 @@
 use std::env::current_exe;
 use std::ffi::CString;
 use std::ffi::OsStr;
+use std::fs::copy;
+use std::fs::create_dir_all;
 use std::fs::read as read_file;
+use std::fs::read_to_string;
+use std::fs::write;
+use std::fs::File;
 use std::io::Error;
 use std::os::unix::ffi::OsStringExt as _;
+use std::os::unix::io::AsRawFd as _;
 use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+use std::thread;
 
+use blazesym::helper::read_elf_build_id;
 use blazesym::inspect;
 use blazesym::inspect::Inspector;
 use blazesym::normalize::Normalizer;
@@ -17,6 +28,7 @@ use blazesym::Addr;
 use blazesym::ErrorKind;
 use blazesym::Pid;
 
+use tempfile::tempdir;
 use test_log::test;
 
 
@@ -36,6 +48,67 @@ fn error_on_non_existent_source() {
     }
 }
 
+/// Check that our main entry points can be shared between threads.
+#[test]
+fn send_sync() {
+    fn test<T>()
+    where
+        T: Send + Sync,
+    {
+    }
+
+    test::<Symbolizer>();
+    test::<Normalizer>();
+    test::<Inspector>();
+}
+
+/// Check that we can use a single `Symbolizer` from multiple threads
+/// concurrently.
+#[test]
+fn symbolize_multi_threaded() {
+    let symbolizer = Arc::new(Symbolizer::builder().enable_inlined_fns(true).build());
+    let handles = (0..8)
+        .map(|i| {
+            let symbolizer = Arc::clone(&symbolizer);
+            thread::spawn(move || {
+                // Alternate between files to exercise the cache from
+                // multiple threads at the same time.
+                let file = if i % 2 == 0 {
+                    "test-stable-addresses.bin"
+                } else {
+                    "test-stable-addresses-dwarf-only.bin"
+                };
+                let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+                    .join("data")
+                    .join(file);
+                let src = symbolize::Source::Elf(symbolize::Elf::new(path));
+
+                for _ in 0..10 {
+                    let results = symbolizer
+                        .symbolize(&src, &[0x2000100, 0x2000208])
+                        .unwrap()
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>();
+                    assert_eq!(results.len(), 2);
+
+                    let factorial = &results[0];
+                    assert_eq!(factorial.name, "factorial");
+                    assert_eq!(factorial.line, Some(8));
+
+                    let inline_test = &results[1];
+                    assert_eq!(inline_test.name, "factorial_inline_test");
+                    assert_eq!(inline_test.inlined.len(), 2);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        let () = handle.join().unwrap();
+    }
+}
+
 /// Find the size of a function called `name` inside an ELF file `elf`.
 fn find_function_size(name: &str, elf: &Path) -> usize {
     let src = inspect::Source::Elf(inspect::Elf::new(elf));
@@ -52,7 +125,8 @@ fn find_function_size(name: &str, elf: &Path) -> usize {
     size
 }
 
-/// Check that we can symbolize an address using ELF, DWARF, and GSYM.
+/// Check that we can symbolize an address using ELF, DWARF, GSYM, and
+/// Breakpad.
 #[test]
 fn symbolize_elf_dwarf_gsym() {
     fn test(src: symbolize::Source, has_src_loc: bool) {
@@ -142,6 +216,117 @@ fn symbolize_elf_dwarf_gsym() {
     let data = read_file(&path).unwrap();
     let src = symbolize::Source::from(symbolize::GsymData::new(&data));
     test(src, true);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses.sym");
+    let src = symbolize::Source::from(symbolize::BreakpadFile::new(&path));
+    test(src, true);
+
+    let data = read_file(&path).unwrap();
+    let src = symbolize::Source::from(symbolize::BreakpadData::new(&data));
+    test(src, true);
+}
+
+/// Check that we can symbolize an address with inlined functions.
+#[test]
+fn symbolize_inlined() {
+    fn test(src: symbolize::Source) {
+        let symbolizer = Symbolizer::builder().enable_inlined_fns(true).build();
+        let results = symbolizer
+            .symbolize(&src, &[0x2000208])
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 1);
+
+        let result = results.first().unwrap();
+        assert_eq!(result.name, "factorial_inline_test");
+        assert_eq!(result.addr, 0x2000200);
+        assert_eq!(result.offset, 8);
+        assert_eq!(
+            result.file.as_deref(),
+            Some(OsStr::new("test-stable-addresses.c"))
+        );
+        assert_eq!(result.line, Some(53));
+
+        assert_eq!(result.inlined.len(), 2);
+        let outer = &result.inlined[0];
+        assert_eq!(outer.name, "factorial_inline_wrapper");
+        assert_eq!(
+            outer.file.as_deref(),
+            Some(OsStr::new("test-stable-addresses.c"))
+        );
+        assert_eq!(outer.line, Some(48));
+
+        let inner = &result.inlined[1];
+        assert_eq!(inner.name, "factorial_2nd_layer_inline_wrapper");
+        assert_eq!(
+            inner.file.as_deref(),
+            Some(OsStr::new("test-stable-addresses.c"))
+        );
+        assert_eq!(inner.line, Some(43));
+
+        // Without opting in, no inlined functions should be reported.
+        let symbolizer = Symbolizer::new();
+        let results = symbolizer
+            .symbolize(&src, &[0x2000208])
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 1);
+
+        let result = results.first().unwrap();
+        assert_eq!(result.name, "factorial_inline_test");
+        assert_eq!(result.line, Some(43));
+        assert!(result.inlined.is_empty());
+    }
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(path));
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-dwarf-only.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(path));
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-split.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(path));
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-dwp.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(path));
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses.gsym");
+    let src = symbolize::Source::from(symbolize::GsymFile::new(&path));
+    test(src);
+
+    let data = read_file(&path).unwrap();
+    let src = symbolize::Source::from(symbolize::GsymData::new(&data));
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses.sym");
+    let src = symbolize::Source::from(symbolize::BreakpadFile::new(&path));
+    test(src);
+
+    let data = read_file(&path).unwrap();
+    let src = symbolize::Source::from(symbolize::BreakpadData::new(&data));
+    test(src);
 }
 
 /// Check that we can symbolize the `abort_creds` function inside a
@@ -190,6 +375,204 @@ fn symbolize_dwarf_complex() {
     assert_eq!(result.line, Some(534));
 }
 
+/// Check that we can symbolize an address in a 32 bit ELF file.
+#[test]
+fn symbolize_elf32() {
+    let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-32.bin");
+    let size = find_function_size("factorial", &test_elf);
+    assert_ne!(size, 0);
+
+    let src = symbolize::Source::Elf(symbolize::Elf::new(test_elf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, &[0x2000100])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "factorial");
+    assert_eq!(result.addr, 0x2000100);
+    assert_eq!(
+        result.file.as_deref(),
+        Some(OsStr::new("test-stable-addresses.c"))
+    );
+    assert_eq!(result.line, Some(8));
+}
+
+/// Check that we can symbolize an address in a big-endian ELF file,
+/// using its DWARF debug information.
+#[test]
+fn symbolize_big_endian() {
+    let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-big-endian.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(test_elf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, &[0x2000100])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "factorial");
+    assert_eq!(result.addr, 0x2000100);
+    assert_eq!(
+        result.file.as_deref(),
+        Some(OsStr::new("test-big-endian.rs"))
+    );
+    assert_eq!(result.line, Some(34));
+}
+
+/// Check that we can symbolize an address using compressed DWARF debug
+/// sections.
+#[test]
+fn symbolize_compressed_dwarf() {
+    fn test(file: &str) {
+        let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join(file);
+        let src = symbolize::Source::Elf(symbolize::Elf::new(test_elf));
+        let symbolizer = Symbolizer::new();
+        let results = symbolizer
+            .symbolize(&src, &[0x2000100])
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 1);
+
+        let result = results.first().unwrap();
+        assert_eq!(result.name, "factorial");
+        assert_eq!(result.addr, 0x2000100);
+        assert_eq!(
+            result.file.as_deref(),
+            Some(OsStr::new("test-stable-addresses.c"))
+        );
+        assert_eq!(result.line, Some(8));
+    }
+
+    test("test-stable-addresses-compressed-debug-zlib.bin");
+    test("test-stable-addresses-compressed-debug-zlib-gnu.bin");
+    test("test-stable-addresses-compressed-debug-zstd.bin");
+}
+
+/// Check that we can symbolize an address in a stripped binary using
+/// debug information referenced via `.gnu_debuglink`.
+#[test]
+fn symbolize_debug_link() {
+    let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-stripped-with-link.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(test_elf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, &[0x2000100])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "factorial");
+    assert_eq!(result.addr, 0x2000100);
+    assert_eq!(result.line, Some(8));
+}
+
+/// Check that we can symbolize an address in a stripped binary using
+/// the symbol table embedded in its `.gnu_debugdata` section.
+#[test]
+fn symbolize_mini_debug_info() {
+    let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-mini-debug-info.bin");
+    let src = symbolize::Source::Elf(symbolize::Elf::new(test_elf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, &[0x2000105])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "factorial");
+    assert_eq!(result.addr, 0x2000100);
+    assert_eq!(result.line, None);
+}
+
+/// Check that we can symbolize an address in a stripped binary using
+/// debug information found based on its build ID.
+#[test]
+fn symbolize_debug_build_id() {
+    let data_dir = Path::new(&env!("CARGO_MANIFEST_DIR")).join("data");
+    let unstripped = data_dir.join("test-dwarf-v5.bin");
+    let stripped = data_dir.join("test-dwarf-v5-stripped.bin");
+
+    let build_id = read_elf_build_id(&stripped).unwrap().unwrap();
+    let (first, rest) = build_id.split_first().unwrap();
+    let rest = rest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    let debug_dir = tempdir().unwrap();
+    let build_id_dir = debug_dir
+        .path()
+        .join(".build-id")
+        .join(format!("{first:02x}"));
+    let () = create_dir_all(&build_id_dir).unwrap();
+    let _bytes = copy(&unstripped, build_id_dir.join(format!("{rest}.debug"))).unwrap();
+
+    let src = inspect::Source::Elf(inspect::Elf::new(&unstripped));
+    let inspector = Inspector::new();
+    let results = inspector
+        .lookup(&["fibonacci"], &src)
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    let addr = results.first().unwrap().addr;
+
+    let src = symbolize::Source::Elf(symbolize::Elf::new(&stripped));
+    // Without the debug directory, we should not be able to find the
+    // (static) function.
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, &[addr])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert!(results.iter().all(|sym| sym.name != "fibonacci"));
+
+    let symbolizer = Symbolizer::builder()
+        .set_debug_dirs([debug_dir.path()])
+        .build();
+    let results = symbolizer
+        .symbolize(&src, &[addr])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "fibonacci");
+    assert_eq!(result.addr, addr);
+    assert!(result.line.is_some());
+}
+
 /// Symbolize a normalized address inside an ELF file, with and without
 /// auto-demangling enabled.
 #[test]
@@ -262,6 +645,372 @@ fn symbolize_process() {
     );
 }
 
+/// Check that parallel symbolization of addresses in our own process
+/// reports the same symbols, in the same order, as the sequential one.
+#[test]
+fn symbolize_process_parallel() {
+    let test_so = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("libtest-so.so");
+    let so_cstr = CString::new(test_so.into_os_string().into_vec()).unwrap();
+    let handle = unsafe { libc::dlopen(so_cstr.as_ptr(), libc::RTLD_NOW) };
+    assert!(!handle.is_null());
+
+    let the_answer_addr = unsafe { libc::dlsym(handle, "the_answer\0".as_ptr().cast()) };
+    assert!(!the_answer_addr.is_null());
+
+    let src = symbolize::Source::Process(symbolize::Process::new(Pid::Slf));
+    // Interleave addresses from different objects, along with an
+    // unknown one.
+    let addrs = [
+        Symbolizer::new as *const () as Addr,
+        the_answer_addr as Addr,
+        0x0,
+        symbolize_process_parallel as *const () as Addr,
+        libc::dlopen as *const () as Addr,
+        Symbolizer::symbolize as *const () as Addr,
+    ];
+    let symbolizer = Symbolizer::new();
+    let expected = symbolizer.symbolize(&src, &addrs).unwrap();
+    let results = symbolizer.symbolize_parallel(&src, &addrs).unwrap();
+
+    let rc = unsafe { libc::dlclose(handle) };
+    assert_eq!(rc, 0, "{}", Error::last_os_error());
+
+    assert_eq!(results.len(), addrs.len());
+    assert_eq!(results.len(), expected.len());
+    assert!(results[2].is_empty());
+    assert_eq!(results[1][0].name, "the_answer");
+    assert!(
+        results[3][0].name.contains("symbolize_process_parallel"),
+        "{:x?}",
+        results[3]
+    );
+
+    for (syms, expected) in results.iter().zip(expected.iter()) {
+        assert_eq!(syms.len(), expected.len());
+        for (sym, expected) in syms.iter().zip(expected.iter()) {
+            assert_eq!(sym.name, expected.name);
+            assert_eq!(sym.addr, expected.addr);
+            assert_eq!(sym.offset, expected.offset);
+            assert_eq!(sym.file, expected.file);
+            assert_eq!(sym.line, expected.line);
+        }
+    }
+}
+
+/// Check that we can symbolize addresses in anonymous executable memory
+/// using a perf map file.
+#[test]
+fn symbolize_process_perf_map() {
+    let size = 0x2000;
+    let mem = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(mem, libc::MAP_FAILED);
+    let base = mem as Addr;
+
+    let dir = tempdir().unwrap();
+    let perf_map = dir.path().join("perf.map");
+    let content = format!(
+        "{:x} 100 jitted_fn\n{:x} 80 Interpreter:jitted fn with spaces\n",
+        base,
+        base + 0x1000
+    );
+    let () = write(&perf_map, content).unwrap();
+
+    let addrs = [base + 0x1010, base + 0x20, base + 0x800];
+    let mut process = symbolize::Process::new(Pid::Slf);
+    process.perf_map = Some(perf_map);
+    let src = symbolize::Source::Process(process);
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer.symbolize(&src, &addrs).unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "Interpreter:jitted fn with spaces");
+    assert_eq!(results[0][0].addr, base + 0x1000);
+    assert_eq!(results[0][0].offset, 0x10);
+    assert_eq!(results[1].len(), 1);
+    assert_eq!(results[1][0].name, "jitted_fn");
+    assert_eq!(results[1][0].offset, 0x20);
+    // The address is not covered by any perf map entry.
+    assert!(results[2].is_empty());
+
+    let parallel = symbolizer.symbolize_parallel(&src, &addrs).unwrap();
+    let names = |results: &[Vec<symbolize::Sym>]| {
+        results
+            .iter()
+            .map(|syms| syms.iter().map(|sym| sym.name.clone()).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&parallel), names(&results));
+
+    // Without a perf map we should not be able to symbolize anything.
+    let src = symbolize::Source::Process(symbolize::Process::new(Pid::Slf));
+    let results = symbolizer.symbolize(&src, &addrs).unwrap();
+    assert!(results.iter().all(Vec::is_empty));
+
+    let rc = unsafe { libc::munmap(mem, size) };
+    assert_eq!(rc, 0, "{}", Error::last_os_error());
+}
+
+/// Check that we can symbolize addresses in anonymous executable memory
+/// using a jitdump file mapped into the process.
+#[test]
+fn symbolize_process_jitdump() {
+    fn record(dump: &mut Vec<u8>, id: u32, body: &[u8]) {
+        let () = dump.extend_from_slice(&id.to_ne_bytes());
+        let () = dump.extend_from_slice(&(16 + body.len() as u32).to_ne_bytes());
+        let () = dump.extend_from_slice(&1u64.to_ne_bytes());
+        let () = dump.extend_from_slice(body);
+    }
+
+    let size = 0x1000;
+    let mem = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(mem, libc::MAP_FAILED);
+    let base = mem as Addr;
+
+    let mut dump = Vec::new();
+    for value in [0x4A695444u32, 1, 40, 62, 0, std::process::id()] {
+        let () = dump.extend_from_slice(&value.to_ne_bytes());
+    }
+    let () = dump.extend_from_slice(&[0; 16]);
+
+    // JIT_CODE_DEBUG_INFO
+    let mut body = Vec::new();
+    for value in [base as u64 + 0x100, 1, base as u64 + 0x108] {
+        let () = body.extend_from_slice(&value.to_ne_bytes());
+    }
+    let () = body.extend_from_slice(&42u32.to_ne_bytes());
+    let () = body.extend_from_slice(&0u32.to_ne_bytes());
+    let () = body.extend_from_slice(b"/app/src/jitted.js\0");
+    let () = record(&mut dump, 2, &body);
+
+    // JIT_CODE_LOAD
+    let mut body = Vec::new();
+    let () = body.extend_from_slice(&[0; 8]);
+    for value in [base as u64 + 0x100, base as u64 + 0x100, 0x80, 0] {
+        let () = body.extend_from_slice(&value.to_ne_bytes());
+    }
+    let () = body.extend_from_slice(b"jitted_fn\0");
+    let () = record(&mut dump, 0, &body);
+
+    let dir = tempdir().unwrap();
+    let jitdump = dir.path().join(format!("jit-{}.dump", std::process::id()));
+    let () = write(&jitdump, &dump).unwrap();
+
+    let addrs = [base + 0x110, base + 0x200];
+    let symbolizer = Symbolizer::new();
+    let mut process = symbolize::Process::new(Pid::Slf);
+    process.jitdump = Some(jitdump.clone());
+    let src = symbolize::Source::Process(process);
+    let results = symbolizer.symbolize(&src, &addrs).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].len(), 1);
+    let sym = &results[0][0];
+    assert_eq!(sym.name, "jitted_fn");
+    assert_eq!(sym.addr, base + 0x100);
+    assert_eq!(sym.offset, 0x10);
+    assert_eq!(sym.dir.as_deref(), Some(Path::new("/app/src")));
+    assert_eq!(sym.file.as_deref(), Some(OsStr::new("jitted.js")));
+    assert_eq!(sym.line, Some(42));
+    assert!(results[1].is_empty());
+
+    // The jitdump file should be discovered if the JIT mapped it into
+    // the process, the way perf expects it to.
+    let file = File::open(&jitdump).unwrap();
+    let dump_mem = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            dump.len(),
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    assert_ne!(dump_mem, libc::MAP_FAILED);
+
+    let src = symbolize::Source::Process(symbolize::Process::new(Pid::Slf));
+    let results = symbolizer.symbolize(&src, &addrs).unwrap();
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "jitted_fn");
+    assert_eq!(results[0][0].line, Some(42));
+
+    let parallel = symbolizer.symbolize_parallel(&src, &addrs).unwrap();
+    assert_eq!(parallel[0].len(), 1);
+    assert_eq!(parallel[0][0].name, "jitted_fn");
+
+    let rc = unsafe { libc::munmap(dump_mem, dump.len()) };
+    assert_eq!(rc, 0, "{}", Error::last_os_error());
+    let rc = unsafe { libc::munmap(mem, size) };
+    assert_eq!(rc, 0, "{}", Error::last_os_error());
+}
+
+/// Check that we can symbolize and normalize addresses inside the vDSO
+/// of our own process.
+#[test]
+fn symbolize_normalize_process_vdso() {
+    let maps = read_to_string("/proc/self/maps").unwrap();
+    let (start, end) = maps
+        .lines()
+        .find(|line| line.ends_with("[vdso]"))
+        .and_then(|line| line.split_once(' '))
+        .and_then(|(range, _)| range.split_once('-'))
+        .map(|(start, end)| {
+            (
+                Addr::from_str_radix(start, 16).unwrap(),
+                Addr::from_str_radix(end, 16).unwrap(),
+            )
+        })
+        .unwrap();
+    let base = unsafe { libc::getauxval(libc::AT_SYSINFO_EHDR) } as Addr;
+    assert_eq!(base, start);
+
+    // Dump the vDSO to figure out where `clock_gettime` resides in it.
+    let dir = tempdir().unwrap();
+    let vdso_so = dir.path().join("vdso.so");
+    let data = unsafe { slice::from_raw_parts(start as *const u8, end - start) };
+    let () = write(&vdso_so, data).unwrap();
+
+    let src = inspect::Source::Elf(inspect::Elf::new(&vdso_so));
+    let inspector = Inspector::new();
+    let results = inspector
+        .lookup(&["__vdso_clock_gettime"], &src)
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    let sym_addr = results[0].addr;
+
+    // The vDSO's first loadable segment starts at virtual address 0 on
+    // all architectures we care about.
+    let addr = start + sym_addr + 1;
+
+    let src = symbolize::Source::Process(symbolize::Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, &[addr])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+    assert!(result.name.ends_with("clock_gettime"), "{result:x?}");
+    assert_eq!(result.addr, sym_addr);
+
+    let parallel = symbolizer.symbolize_parallel(&src, &[addr]).unwrap();
+    assert_eq!(parallel.len(), 1);
+    assert_eq!(parallel[0][0].name, result.name);
+
+    let normalizer = Normalizer::new();
+    let norm_addrs = normalizer
+        .normalize_user_addrs_sorted([addr].as_slice(), Pid::Slf)
+        .unwrap();
+    assert_eq!(norm_addrs.addrs.len(), 1);
+    assert_eq!(norm_addrs.meta.len(), 1);
+
+    let (norm_addr, meta_idx) = norm_addrs.addrs[0];
+    assert_eq!(norm_addr, sym_addr + 1);
+    assert!(norm_addrs.meta[meta_idx].vdso().is_some());
+}
+
+/// Check that we can symbolize kernel addresses subject to KASLR using
+/// a kernel image.
+#[test]
+fn symbolize_kernel_kaslr() {
+    let dir = tempdir().unwrap();
+    let kallsyms = dir.path().join("kallsyms");
+    // The kernel image has `_stext` at 0x2000000 and `factorial` at
+    // 0x2000100.
+    let () = write(
+        &kallsyms,
+        b"0000000012000000 T _stext\n0000000012000100 T factorial\n",
+    )
+    .unwrap();
+    let image = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addresses-kernel.bin");
+    let symbolizer = Symbolizer::new();
+
+    // The KASLR offset is inferred from kallsyms.
+    let mut kernel = symbolize::Kernel::default();
+    kernel.kallsyms = Some(kallsyms);
+    kernel.kernel_image = Some(image.clone());
+    let src = symbolize::Source::Kernel(kernel);
+    let results = symbolizer.symbolize(&src, &[0x12000100]).unwrap();
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "factorial");
+    assert_eq!(results[0][0].addr, 0x12000100);
+    assert_eq!(results[0][0].line, Some(8));
+
+    // The KASLR offset is provided by the user and only the kernel
+    // image is used.
+    let mut kernel = symbolize::Kernel::default();
+    kernel.kernel_image = Some(image.clone());
+    kernel.kaslr_offset = Some(0x10000000);
+    let src = symbolize::Source::Kernel(kernel);
+    let results = symbolizer.symbolize(&src, &[0x12000100]).unwrap();
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "factorial");
+    assert_eq!(results[0][0].addr, 0x12000100);
+    assert_eq!(results[0][0].line, Some(8));
+
+    // Normalized addresses have no KASLR offset.
+    let mut kernel = symbolize::Kernel::default();
+    kernel.kernel_image = Some(image);
+    kernel.kaslr_offset = Some(0);
+    let src = symbolize::Source::Kernel(kernel);
+    let results = symbolizer.symbolize(&src, &[0x2000100]).unwrap();
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "factorial");
+    assert_eq!(results[0][0].addr, 0x2000100);
+    assert_eq!(results[0][0].line, Some(8));
+}
+
+/// Check that BPF program addresses fall back to being symbolized
+/// using kallsyms if no program information is available for them.
+#[test]
+fn symbolize_kernel_bpf_prog_fallback() {
+    let dir = tempdir().unwrap();
+    let kallsyms = dir.path().join("kallsyms");
+    let () = write(
+        &kallsyms,
+        b"ffffffffc0001000 t bpf_prog_6deef7357e7b4530_handle_exec\t[bpf]\n\
+          ffffffffc0002000 t bpf_prog_0123456789abcdef_other\t[bpf]\n",
+    )
+    .unwrap();
+
+    let mut kernel = symbolize::Kernel::default();
+    kernel.kallsyms = Some(kallsyms);
+    kernel.bpf_prog_info = true;
+    let src = symbolize::Source::Kernel(kernel);
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer.symbolize(&src, &[0xffffffffc0001010]).unwrap();
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "bpf_prog_6deef7357e7b4530_handle_exec");
+    assert_eq!(results[0][0].addr, 0xffffffffc0001000);
+    assert_eq!(results[0][0].line, None);
+}
+
 /// Check that we can normalize addresses in an ELF shared object.
 #[test]
 fn normalize_elf_addr() {
@@ -308,6 +1057,142 @@ fn normalize_elf_addr() {
 }
 
 
+/// Check that we can symbolize normalized addresses using files found
+/// via a callback or based on their build ID.
+#[test]
+fn symbolize_normalized() {
+    let test_so = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("libtest-so.so");
+    let so_cstr = CString::new(test_so.clone().into_os_string().into_vec()).unwrap();
+    let handle = unsafe { libc::dlopen(so_cstr.as_ptr(), libc::RTLD_NOW) };
+    assert!(!handle.is_null());
+
+    let the_answer_addr = unsafe { libc::dlsym(handle, "the_answer\0".as_ptr().cast()) };
+    assert!(!the_answer_addr.is_null());
+
+    let normalizer = Normalizer::new();
+    let norm_addrs = normalizer
+        .normalize_user_addrs(&[the_answer_addr as Addr, 0x0], Pid::Slf)
+        .unwrap();
+
+    let rc = unsafe { libc::dlclose(handle) };
+    assert_eq!(rc, 0, "{}", Error::last_os_error());
+
+    // Pretend that the shared object is stored in a different location
+    // on the symbolizing system.
+    let dir = tempdir().unwrap();
+    let local_so = dir.path().join("libtest-so.so");
+    let _bytes = copy(&test_so, &local_so).unwrap();
+
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize_normalized(&norm_addrs, |meta| {
+            let elf = meta.elf()?;
+            assert_eq!(elf.path, test_so);
+            Some(local_so.clone())
+        })
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "the_answer");
+    assert!(results[1].is_empty());
+
+    // Without any means of finding the file we should not report any
+    // symbols.
+    let results = symbolizer
+        .symbolize_normalized(&norm_addrs, |_meta| None)
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(Vec::is_empty));
+
+    // But we should be able to find it based on its build ID.
+    let build_id = read_elf_build_id(&test_so).unwrap().unwrap();
+    let (first, rest) = build_id.split_first().unwrap();
+    let rest = rest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let build_id_dir = dir.path().join(".build-id").join(format!("{first:02x}"));
+    let () = create_dir_all(&build_id_dir).unwrap();
+    let _bytes = copy(&test_so, build_id_dir.join(format!("{rest}.debug"))).unwrap();
+
+    let symbolizer = Symbolizer::builder().set_debug_dirs([dir.path()]).build();
+    let results = symbolizer
+        .symbolize_normalized(&norm_addrs, |_meta| None)
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "the_answer");
+    assert!(results[1].is_empty());
+
+    // Lastly, the file should be found when the sysroot contains it
+    // at its original path.
+    let sysroot = tempdir().unwrap();
+    let sysroot_so = sysroot.path().join(test_so.strip_prefix("/").unwrap());
+    let () = create_dir_all(sysroot_so.parent().unwrap()).unwrap();
+    let _bytes = copy(&test_so, &sysroot_so).unwrap();
+
+    let symbolizer = Symbolizer::builder().set_sysroot(sysroot.path()).build();
+    let results = symbolizer
+        .symbolize_normalized(&norm_addrs, |_meta| None)
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].name, "the_answer");
+    assert!(results[1].is_empty());
+}
+
+
+/// Check that ELF file and source code paths get remapped as
+/// configured.
+#[test]
+fn symbolize_elf_path_maps() {
+    let data_dir = Path::new(&env!("CARGO_MANIFEST_DIR")).join("data");
+    let src = symbolize::Source::Elf(symbolize::Elf::new("/build/data/test-stable-addresses.bin"));
+
+    let symbolizer = Symbolizer::new();
+    let err = symbolizer.symbolize(&src, &[0x2000100]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let symbolizer = Symbolizer::builder()
+        .set_path_maps([("/build/data", &data_dir)])
+        .build();
+    let results = symbolizer
+        .symbolize(&src, &[0x2000100])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "factorial");
+    let dir = result.dir.clone().unwrap();
+
+    let symbolizer = Symbolizer::builder()
+        .set_path_maps([("/build/data", &data_dir)])
+        .set_src_path_maps([(&dir, Path::new("/src/checkout"))])
+        .build();
+    let results = symbolizer
+        .symbolize(&src, &[0x2000100])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let result = results.first().unwrap();
+    assert_eq!(result.name, "factorial");
+    assert_eq!(result.dir.as_deref(), Some(Path::new("/src/checkout")));
+    assert_eq!(
+        result.file.as_deref(),
+        Some(OsStr::new("test-stable-addresses.c"))
+    );
+    assert_eq!(result.line, Some(8));
+}
+
+
 /// Check that we can look up an address.
 #[test]
 fn inspect() {